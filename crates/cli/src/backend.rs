@@ -0,0 +1,46 @@
+//! Backend selected at runtime from configuration.
+
+use runtime::{AnthropicBackend, Backend, ModelError, ModelInfo, ModelRequest, ModelResponse};
+
+/// Any backend the CLI can construct from `bosun.toml`.
+pub enum AnyBackend {
+    Anthropic(AnthropicBackend),
+}
+
+impl Backend for AnyBackend {
+    async fn call(&self, request: ModelRequest<'_>) -> Result<ModelResponse, ModelError> {
+        match self {
+            Self::Anthropic(backend) => backend.call(request).await,
+        }
+    }
+
+    fn model_info(&self) -> ModelInfo {
+        match self {
+            Self::Anthropic(backend) => backend.model_info(),
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            Self::Anthropic(backend) => backend.name(),
+        }
+    }
+
+    fn supports_tools(&self) -> bool {
+        match self {
+            Self::Anthropic(backend) => backend.supports_tools(),
+        }
+    }
+
+    async fn estimate_tokens(&self, request: &ModelRequest<'_>) -> Result<u32, ModelError> {
+        match self {
+            Self::Anthropic(backend) => backend.estimate_tokens(request).await,
+        }
+    }
+
+    async fn health_check(&self) -> Result<(), ModelError> {
+        match self {
+            Self::Anthropic(backend) => backend.health_check().await,
+        }
+    }
+}
@@ -0,0 +1,94 @@
+//! Slash commands typed at the chat prompt instead of a message.
+
+use std::path::PathBuf;
+
+/// A command handled by the REPL rather than sent to the model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Show the session's token usage so far.
+    Tokens,
+    /// Start a fresh conversation in the same session.
+    Clear,
+    /// Write the transcript to a file.
+    Save(PathBuf),
+    /// List the tools the model can call.
+    Tools,
+    /// Replace the system prompt; `None` restores the default.
+    System(Option<String>),
+    /// Discard the last response and ask again.
+    Retry,
+    /// List the commands.
+    Help,
+}
+
+/// The command list and input tips, for `/help`.
+pub const HELP: &str = "\
+/tokens         show token usage for this session
+/clear          start a fresh conversation, keeping the session
+/save <file>    write the transcript to a file
+/tools          list the available tools
+/system [text]  set the system prompt, or restore the default
+/retry          regenerate the last response
+/help           show this list
+
+End a line with \\ to continue it, or put \"\"\" on lines before and after
+a multi-line paste to send it as one message.";
+
+/// Parse `input` as a slash command.
+///
+/// Returns `None` for ordinary messages, and an error message for input
+/// that starts with `/` but is not a valid command.
+pub fn parse(input: &str) -> Option<Result<Command, String>> {
+    let rest = input.strip_prefix('/')?;
+    let (name, arg) = match rest.split_once(char::is_whitespace) {
+        Some((name, arg)) => (name, arg.trim()),
+        None => (rest, ""),
+    };
+
+    let command = match (name, arg) {
+        ("tokens", "") => Ok(Command::Tokens),
+        ("clear", "") => Ok(Command::Clear),
+        ("save", "") => Err("usage: /save <file>".to_string()),
+        ("save", path) => Ok(Command::Save(PathBuf::from(path))),
+        ("tools", "") => Ok(Command::Tools),
+        ("system", "") => Ok(Command::System(None)),
+        ("system", text) => Ok(Command::System(Some(text.to_string()))),
+        ("retry", "") => Ok(Command::Retry),
+        ("help", "") => Ok(Command::Help),
+        ("tokens" | "clear" | "tools" | "retry" | "help", _) => {
+            Err(format!("/{name} takes no arguments"))
+        }
+        _ => Err(format!("unknown command /{name}; try /help")),
+    };
+    Some(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commands_and_arguments() {
+        assert_eq!(parse("hello"), None);
+        assert_eq!(parse("/tokens"), Some(Ok(Command::Tokens)));
+        assert_eq!(
+            parse("/save  notes/chat.txt "),
+            Some(Ok(Command::Save("notes/chat.txt".into())))
+        );
+        assert_eq!(
+            parse("/system Answer in French."),
+            Some(Ok(Command::System(Some("Answer in French.".into()))))
+        );
+        assert_eq!(parse("/system"), Some(Ok(Command::System(None))));
+    }
+
+    #[test]
+    fn rejects_unknown_commands_and_bad_arguments() {
+        assert!(matches!(parse("/save"), Some(Err(_))));
+        assert!(matches!(parse("/retry now"), Some(Err(_))));
+        assert_eq!(
+            parse("/frobnicate"),
+            Some(Err("unknown command /frobnicate; try /help".into()))
+        );
+    }
+}
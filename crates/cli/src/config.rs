@@ -1,34 +1,127 @@
 //! Configuration loading from bosun.toml.
 
 use policy::Policy;
-use runtime::AnthropicAuth;
-use serde::Deserialize;
-use std::path::Path;
+use runtime::{AnthropicAuth, AnthropicBackend, ToolCache, ToolTimeouts};
+use serde::{Deserialize, Deserializer, de};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::backend::AnyBackend;
+
+const DEFAULT_PROVIDER: &str = "anthropic";
+
+/// Built-in tools whose results must never come from `[tool_cache]`: they
+/// have side effects, or are checked and audited on every call.
+const UNCACHEABLE_TOOLS: &[&str] = &["exec", "http_get", "read_secret", "write_file"];
+const SUPPORTED_PROVIDERS: &[&str] = &["anthropic"];
+
+/// `[backend]` keys that choose the auth method. A file that sets any of
+/// them replaces all of them from lower-precedence files.
+const AUTH_KEYS: &[&str] = &["api_key", "api_key_file", "oauth_token", "oauth_token_file"];
 
 /// Top-level configuration.
 #[derive(Debug, Deserialize)]
 pub struct Config {
     /// Backend configuration.
-    #[serde(default)]
+    #[serde(default, deserialize_with = "deserialize_backend")]
     pub backend: BackendConfig,
 
     /// MCP tool servers.
     #[serde(default)]
     pub tools: Vec<ToolConfig>,
 
+    /// Offer the built-in file, exec and HTTP tools when no MCP server
+    /// is configured.
+    #[serde(default)]
+    pub local_tools: bool,
+
+    /// Ask the model for a short session title after the first reply in
+    /// interactive chats (`true`), or leave sessions untitled (`false`).
+    /// Unset, the first message is used.
+    #[serde(default)]
+    pub auto_title: Option<bool>,
+
+    /// Reuse results of side-effect-free tools within a session.
+    #[serde(default)]
+    pub tool_cache: Option<ToolCacheConfig>,
+
+    /// How long tools may run before their calls fail.
+    #[serde(default)]
+    pub tool_timeouts: ToolTimeoutsConfig,
+
     /// Policy rules (allow/deny).
     #[serde(flatten)]
     pub policy: Policy,
 }
 
-/// Backend provider configuration.
-#[derive(Debug, Deserialize, Default)]
-pub struct BackendConfig {
-    /// Provider name (currently only "anthropic" supported).
-    #[serde(default = "default_provider")]
-    #[allow(dead_code)]
-    pub provider: String,
+/// Backend provider configuration, selected by the `provider` key.
+///
+/// `provider` defaults to "anthropic" when omitted.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "provider", rename_all = "snake_case")]
+pub enum BackendConfig {
+    /// Anthropic Messages API.
+    Anthropic(AnthropicConfig),
+}
+
+impl Default for BackendConfig {
+    fn default() -> Self {
+        Self::Anthropic(AnthropicConfig::default())
+    }
+}
+
+impl BackendConfig {
+    /// Model the configured backend will use.
+    pub fn model(&self) -> &str {
+        match self {
+            Self::Anthropic(config) => &config.model,
+        }
+    }
+
+    /// Use `model` in place of the configured one.
+    pub fn set_model(&mut self, model: impl Into<String>) {
+        match self {
+            Self::Anthropic(config) => config.model = model.into(),
+        }
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        match self {
+            Self::Anthropic(config) => config.validate(),
+        }
+    }
 
+    /// Construct the configured backend.
+    pub fn build(&self, system: &str) -> Result<AnyBackend, ConfigError> {
+        match self {
+            Self::Anthropic(config) => {
+                let mut builder = AnthropicBackend::builder(config.auth()?, &config.model)
+                    .system(system)
+                    .beta(config.beta.clone())
+                    .extra_headers(config.extra_headers.clone())
+                    .extra_body(serde_json::Value::Object(config.extra_body.clone()));
+                if let Some(version) = &config.api_version {
+                    builder = builder.api_version(version);
+                }
+                if let Some(user_id) = &config.user_id {
+                    builder = builder.user_id(user_id);
+                }
+                if let Some(budget) = config.thinking_budget {
+                    builder = builder.thinking_budget(budget);
+                }
+                let backend = builder.build();
+                Ok(AnyBackend::Anthropic(backend))
+            }
+        }
+    }
+}
+
+/// Anthropic provider settings.
+///
+/// `Debug` masks credentials.
+#[derive(Deserialize)]
+pub struct AnthropicConfig {
     /// Model to use.
     #[serde(default = "default_model")]
     pub model: String,
@@ -40,6 +133,110 @@ pub struct BackendConfig {
     /// Claude Code OAuth token (sk-ant-oat-...).
     /// Mutually exclusive with api_key.
     pub oauth_token: Option<String>,
+
+    /// File containing the API key, so it stays out of bosun.toml.
+    /// A leading `~/` expands to the home directory.
+    pub api_key_file: Option<PathBuf>,
+
+    /// File containing the OAuth token.
+    pub oauth_token_file: Option<PathBuf>,
+
+    /// Override the `anthropic-version` header.
+    pub api_version: Option<String>,
+
+    /// Opaque end-user ID sent as `metadata.user_id`.
+    pub user_id: Option<String>,
+
+    /// Token budget for extended thinking; unset leaves it off.
+    pub thinking_budget: Option<u32>,
+
+    /// Beta feature flags to send in `anthropic-beta`.
+    #[serde(default)]
+    pub beta: Vec<String>,
+
+    /// Extra headers for every request, e.g. for an LLM gateway.
+    #[serde(default)]
+    pub extra_headers: HashMap<String, String>,
+
+    /// Extra top-level fields for every Messages request, for API
+    /// parameters bosun does not model yet.
+    #[serde(default)]
+    pub extra_body: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Default for AnthropicConfig {
+    fn default() -> Self {
+        Self {
+            model: default_model(),
+            api_key: None,
+            oauth_token: None,
+            api_key_file: None,
+            oauth_token_file: None,
+            api_version: None,
+            user_id: None,
+            thinking_budget: None,
+            beta: Vec::new(),
+            extra_headers: HashMap::new(),
+            extra_body: serde_json::Map::new(),
+        }
+    }
+}
+
+impl std::fmt::Debug for AnthropicConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mask = |secret: &Option<String>| secret.as_ref().map(|_| "****");
+        f.debug_struct("AnthropicConfig")
+            .field("model", &self.model)
+            .field("api_key", &mask(&self.api_key))
+            .field("oauth_token", &mask(&self.oauth_token))
+            .field("api_key_file", &self.api_key_file)
+            .field("oauth_token_file", &self.oauth_token_file)
+            .field("api_version", &self.api_version)
+            .field("user_id", &self.user_id)
+            .field("thinking_budget", &self.thinking_budget)
+            .field("beta", &self.beta)
+            .field(
+                "extra_headers",
+                &self.extra_headers.keys().collect::<Vec<_>>(),
+            )
+            .field("extra_body", &self.extra_body)
+            .finish()
+    }
+}
+
+impl AnthropicConfig {
+    /// Build the authentication from config.
+    ///
+    /// Requires exactly one of api_key or oauth_token to be set, either
+    /// inline or through its `_file` variant.
+    pub fn auth(&self) -> Result<AnthropicAuth, ConfigError> {
+        let api_key = resolve_secret("api_key", &self.api_key, &self.api_key_file)?;
+        let oauth_token = resolve_secret("oauth_token", &self.oauth_token, &self.oauth_token_file)?;
+
+        match (api_key, oauth_token) {
+            (Some(key), None) => Ok(AnthropicAuth::ApiKey(key)),
+            (None, Some(token)) => Ok(AnthropicAuth::ClaudeCodeOauth(token)),
+            (Some(_), Some(_)) => Err(ConfigError::AmbiguousAuth),
+            (None, None) => Err(ConfigError::MissingAuth),
+        }
+    }
+}
+
+impl AnthropicConfig {
+    /// Check that the model is set and exactly one auth method is
+    /// configured, without reading any secret files.
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.model.trim().is_empty() {
+            return Err(ConfigError::EmptyModel);
+        }
+        let api_key = secret_source("api_key", &self.api_key, &self.api_key_file)?;
+        let oauth_token = secret_source("oauth_token", &self.oauth_token, &self.oauth_token_file)?;
+        match (api_key, oauth_token) {
+            (true, true) => Err(ConfigError::AmbiguousAuth),
+            (false, false) => Err(ConfigError::MissingAuth),
+            _ => Ok(()),
+        }
+    }
 }
 
 /// MCP tool server configuration.
@@ -51,10 +248,203 @@ pub struct ToolConfig {
     /// Arguments to pass to the command.
     #[serde(default)]
     pub args: Vec<String>,
+
+    /// Check tool arguments against the tool's input schema before calling it.
+    #[serde(default)]
+    pub validate_input: bool,
+}
+
+/// `[tool_cache]`: which tools' results a session may reuse.
+#[derive(Debug, Deserialize, Clone)]
+pub struct ToolCacheConfig {
+    /// Names of tools without side effects, e.g. "read_file".
+    pub tools: Vec<String>,
+
+    /// Seconds a result stays usable.
+    #[serde(default = "default_cache_ttl_secs")]
+    pub ttl_secs: u64,
+
+    /// Most results kept at once.
+    #[serde(default = "default_cache_max_entries")]
+    pub max_entries: usize,
+}
+
+fn default_cache_ttl_secs() -> u64 {
+    300
+}
+
+fn default_cache_max_entries() -> usize {
+    256
+}
+
+impl ToolCacheConfig {
+    pub fn build(&self) -> ToolCache {
+        ToolCache::new(self.tools.iter().cloned())
+            .ttl(Duration::from_secs(self.ttl_secs))
+            .max_entries(self.max_entries)
+    }
+}
+
+/// `[tool_timeouts]`: seconds each tool may run, by name or `prefix*`.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct ToolTimeoutsConfig {
+    /// Limit for tools not listed in `tools`; unlimited when omitted.
+    pub default_secs: Option<u64>,
+
+    /// Limits by tool name, e.g. `run_tests = 600` or `"read_*" = 5`.
+    #[serde(default)]
+    pub tools: HashMap<String, u64>,
+}
+
+impl ToolTimeoutsConfig {
+    pub fn build(&self) -> ToolTimeouts {
+        let mut timeouts = ToolTimeouts::new();
+        if let Some(secs) = self.default_secs {
+            timeouts = timeouts.default_timeout(Duration::from_secs(secs));
+        }
+        for (pattern, secs) in &self.tools {
+            timeouts = timeouts.tool(pattern, Duration::from_secs(*secs));
+        }
+        timeouts
+    }
+}
+
+/// Deserialize `[backend]`, filling in the default provider when omitted.
+fn deserialize_backend<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<BackendConfig, D::Error> {
+    let mut table = toml::Table::deserialize(deserializer)?;
+    let provider = table
+        .entry("provider")
+        .or_insert_with(|| toml::Value::String(DEFAULT_PROVIDER.to_string()));
+    if let Some(name) = provider.as_str()
+        && !SUPPORTED_PROVIDERS.contains(&name)
+    {
+        return Err(de::Error::custom(format!(
+            "unsupported backend.provider \"{name}\" (supported: {})",
+            SUPPORTED_PROVIDERS.join(", ")
+        )));
+    }
+    BackendConfig::deserialize(toml::Value::Table(table)).map_err(de::Error::custom)
+}
+
+/// Whether a secret is configured, inline or by file path.
+fn secret_source(
+    field: &'static str,
+    inline: &Option<String>,
+    file: &Option<PathBuf>,
+) -> Result<bool, ConfigError> {
+    match (inline, file) {
+        (Some(_), Some(_)) => Err(ConfigError::ConflictingSecret(field)),
+        (inline, file) => Ok(inline.is_some() || file.is_some()),
+    }
+}
+
+/// Resolve a secret given inline or by file path.
+///
+/// File contents are trimmed so a trailing newline is not part of the secret.
+fn resolve_secret(
+    field: &'static str,
+    inline: &Option<String>,
+    file: &Option<PathBuf>,
+) -> Result<Option<String>, ConfigError> {
+    match (inline, file) {
+        (Some(_), Some(_)) => Err(ConfigError::ConflictingSecret(field)),
+        (Some(value), None) => Ok(Some(value.clone())),
+        (None, Some(path)) => {
+            let path = expand_home(path);
+            let content = std::fs::read_to_string(&path)
+                .map_err(|source| ConfigError::SecretFile { path, source })?;
+            Ok(Some(content.trim().to_string()))
+        }
+        (None, None) => Ok(None),
+    }
+}
+
+/// Expand a leading `~/` to the user's home directory.
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
 }
 
-fn default_provider() -> String {
-    "anthropic".to_string()
+/// Merge `overlay` into `base`: tables merge key by key and any other
+/// value replaces the one in `base`.
+fn merge_tables(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overlay)) => {
+                merge_tables(base, overlay);
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Drop the auth keys from `base` if `overlay` sets any, so a project
+/// can switch auth method without conflicting with the user's.
+fn override_auth(base: &mut toml::Table, overlay: &toml::Table) {
+    let sets_auth = |table: &toml::Table| {
+        table
+            .get("backend")
+            .and_then(toml::Value::as_table)
+            .is_some_and(|backend| AUTH_KEYS.iter().any(|key| backend.contains_key(*key)))
+    };
+    if sets_auth(overlay)
+        && let Some(toml::Value::Table(backend)) = base.get_mut("backend")
+    {
+        backend.retain(|key, _| !AUTH_KEYS.contains(&key));
+    }
+}
+
+/// Replace `$VAR` and `${VAR}` in `input` with values from `lookup`.
+///
+/// `$$` is a literal `$`, as is a `$` not followed by a variable name.
+/// Errors name only the config field, since values may be secrets.
+fn expand_vars(
+    field: &str,
+    input: &str,
+    lookup: &impl Fn(&str) -> Option<String>,
+) -> Result<String, ConfigError> {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let is_name = |s: &str| {
+        s.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') && s.chars().all(is_name_char)
+    };
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find('$') {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let (name, tail) = if let Some(tail) = after.strip_prefix('$') {
+            output.push('$');
+            rest = tail;
+            continue;
+        } else if let Some(braced) = after.strip_prefix('{') {
+            match braced.find('}') {
+                Some(end) if is_name(&braced[..end]) => (&braced[..end], &braced[end + 1..]),
+                _ => return Err(ConfigError::InvalidVar(field.to_string())),
+            }
+        } else {
+            let end = after.find(|c| !is_name_char(c)).unwrap_or(after.len());
+            if !is_name(&after[..end]) {
+                output.push('$');
+                rest = after;
+                continue;
+            }
+            after.split_at(end)
+        };
+        let value = lookup(name).ok_or_else(|| ConfigError::UnsetVar {
+            field: field.to_string(),
+            name: name.to_string(),
+        })?;
+        output.push_str(&value);
+        rest = tail;
+    }
+    output.push_str(rest);
+    Ok(output)
 }
 
 fn default_model() -> String {
@@ -62,15 +452,117 @@ fn default_model() -> String {
 }
 
 impl Config {
-    /// Load configuration from a TOML file.
-    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
-        let content = std::fs::read_to_string(path.as_ref())?;
-        Self::parse(&content)
+    /// Load the config files among `paths` that exist, merge them with
+    /// later files taking precedence, and validate the result.
+    ///
+    /// Tables merge key by key; other values, including the `[[tools]]`
+    /// list, are replaced whole. Returns the merged config with the files
+    /// that were read, or the default config when none exist.
+    pub fn load_layered(paths: &[PathBuf]) -> Result<(Self, Vec<PathBuf>), ConfigError> {
+        let mut merged = toml::Table::new();
+        let mut loaded = Vec::new();
+        for path in paths {
+            if !path.exists() {
+                continue;
+            }
+            let content = std::fs::read_to_string(path)?;
+            // Parse each file on its own first, so errors point at the
+            // file and line they come from rather than the merged table.
+            Self::parse(&content).map_err(|e| match e {
+                ConfigError::Parse(message) => {
+                    ConfigError::Parse(format!("{}: {message}", path.display()))
+                }
+                other => other,
+            })?;
+            let layer: toml::Table =
+                toml::from_str(&content).map_err(|e| ConfigError::Parse(e.to_string()))?;
+            override_auth(&mut merged, &layer);
+            merge_tables(&mut merged, layer);
+            loaded.push(path.clone());
+        }
+        if loaded.is_empty() {
+            return Ok((Self::default_config(), loaded));
+        }
+
+        let mut config = Self::deserialize(toml::Value::Table(merged))
+            .map_err(|e| ConfigError::Parse(e.to_string()))?;
+        config.expand_vars(&|name| std::env::var(name).ok())?;
+        config.validate()?;
+        Ok((config, loaded))
+    }
+
+    /// Check for settings that parse but cannot work: an empty model,
+    /// missing or conflicting credentials, a tool server without a
+    /// command, or a cached tool with side effects. Unsupported providers
+    /// are already rejected by `parse`.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        self.backend.validate()?;
+        for (index, tool) in self.tools.iter().enumerate() {
+            if tool.command.trim().is_empty() {
+                return Err(ConfigError::EmptyToolCommand(index + 1));
+            }
+        }
+        if let Some(cache) = &self.tool_cache
+            && let Some(tool) = cache
+                .tools
+                .iter()
+                .find(|tool| UNCACHEABLE_TOOLS.contains(&tool.as_str()))
+        {
+            return Err(ConfigError::UncacheableTool(tool.clone()));
+        }
+        Ok(())
     }
 
-    /// Parse configuration from TOML string.
+    /// Parse configuration from TOML string, expanding environment
+    /// variables in secrets, headers, tool commands and policy paths.
     pub fn parse(toml: &str) -> Result<Self, ConfigError> {
-        toml::from_str(toml).map_err(|e| ConfigError::Parse(e.to_string()))
+        let mut config: Self =
+            toml::from_str(toml).map_err(|e| ConfigError::Parse(e.to_string()))?;
+        config.expand_vars(&|name| std::env::var(name).ok())?;
+        Ok(config)
+    }
+
+    /// Expand `$VAR` and `${VAR}` (see [`expand_vars`]) in the string
+    /// fields that commonly hold secrets or machine-specific paths.
+    fn expand_vars(&mut self, lookup: &impl Fn(&str) -> Option<String>) -> Result<(), ConfigError> {
+        let BackendConfig::Anthropic(backend) = &mut self.backend;
+        for (field, value) in [
+            ("backend.api_key", &mut backend.api_key),
+            ("backend.oauth_token", &mut backend.oauth_token),
+        ] {
+            if let Some(value) = value {
+                *value = expand_vars(field, value, lookup)?;
+            }
+        }
+        for (field, path) in [
+            ("backend.api_key_file", &mut backend.api_key_file),
+            ("backend.oauth_token_file", &mut backend.oauth_token_file),
+        ] {
+            if let Some(path) = path
+                && let Some(text) = path.to_str()
+            {
+                *path = PathBuf::from(expand_vars(field, text, lookup)?);
+            }
+        }
+        for value in backend.extra_headers.values_mut() {
+            *value = expand_vars("backend.extra_headers", value, lookup)?;
+        }
+        for tool in &mut self.tools {
+            tool.command = expand_vars("tools.command", &tool.command, lookup)?;
+            for arg in &mut tool.args {
+                *arg = expand_vars("tools.args", arg, lookup)?;
+            }
+        }
+        let allow = &mut self.policy.allow;
+        for (field, paths) in [
+            ("allow.fs_read", &mut allow.fs_read),
+            ("allow.fs_write", &mut allow.fs_write),
+        ] {
+            for path in paths {
+                *path = expand_vars(field, path, lookup)?;
+            }
+        }
+        Ok(())
     }
 
     /// Create a default configuration.
@@ -78,21 +570,13 @@ impl Config {
         Self {
             backend: BackendConfig::default(),
             tools: Vec::new(),
+            local_tools: false,
+            auto_title: None,
+            tool_cache: None,
+            tool_timeouts: ToolTimeoutsConfig::default(),
             policy: Policy::restrictive(),
         }
     }
-
-    /// Build the authentication from config.
-    ///
-    /// Requires exactly one of api_key or oauth_token to be set.
-    pub fn auth(&self) -> Result<AnthropicAuth, ConfigError> {
-        match (&self.backend.api_key, &self.backend.oauth_token) {
-            (Some(key), None) => Ok(AnthropicAuth::ApiKey(key.clone())),
-            (None, Some(token)) => Ok(AnthropicAuth::ClaudeCodeOauth(token.clone())),
-            (Some(_), Some(_)) => Err(ConfigError::AmbiguousAuth),
-            (None, None) => Err(ConfigError::MissingAuth),
-        }
-    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -103,11 +587,344 @@ pub enum ConfigError {
     #[error("failed to parse config: {0}")]
     Parse(String),
 
-    #[error("authentication not configured: set backend.api_key or backend.oauth_token")]
+    #[error(
+        "authentication not configured: set backend.api_key or backend.oauth_token \
+         (or their _file variants)"
+    )]
     MissingAuth,
 
     #[error(
         "ambiguous authentication: set either backend.api_key OR backend.oauth_token, not both"
     )]
     AmbiguousAuth,
+
+    #[error("conflicting secret: set either backend.{0} OR backend.{0}_file, not both")]
+    ConflictingSecret(&'static str),
+
+    #[error(
+        "backend.model is empty: set it to a model name such as \"{}\"",
+        default_model()
+    )]
+    EmptyModel,
+
+    #[error("[[tools]] entry {0} has an empty command: set it to the MCP server to run")]
+    EmptyToolCommand(usize),
+
+    #[error("[tool_cache] cannot cache {0}: its calls must always run")]
+    UncacheableTool(String),
+
+    #[error("{field} references environment variable {name}, which is not set")]
+    UnsetVar { field: String, name: String },
+
+    #[error("{0} has a malformed ${{...}} reference: use ${{NAME}}, or $$ for a literal $")]
+    InvalidVar(String),
+
+    #[error("failed to read secret file {path}: {source}", path = path.display())]
+    SecretFile {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_backend_provider_defaults_to_anthropic() {
+        let config = Config::parse(
+            r#"
+[backend]
+model = "claude-test"
+api_key = "sk-test"
+"#,
+        )
+        .unwrap();
+
+        let BackendConfig::Anthropic(anthropic) = &config.backend;
+        assert_eq!(anthropic.model, "claude-test");
+        assert!(anthropic.auth().is_ok());
+    }
+
+    #[test]
+    fn parse_tool_cache() {
+        let config = Config::parse(
+            r#"
+[tool_cache]
+tools = ["read_file"]
+ttl_secs = 60
+"#,
+        )
+        .unwrap();
+
+        let cache = config.tool_cache.unwrap();
+        assert_eq!(cache.tools, ["read_file"]);
+        assert_eq!(cache.ttl_secs, 60);
+        assert_eq!(cache.max_entries, 256);
+        assert!(cache.build().is_cacheable("read_file"));
+    }
+
+    #[test]
+    fn parse_auto_title() {
+        assert_eq!(Config::parse("").unwrap().auto_title, None);
+        assert_eq!(
+            Config::parse("auto_title = false").unwrap().auto_title,
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn parse_tool_timeouts() {
+        let config = Config::parse(
+            r#"
+[tool_timeouts]
+default_secs = 30
+tools = { run_tests = 600, "read_*" = 5 }
+"#,
+        )
+        .unwrap();
+
+        let timeouts = config.tool_timeouts.build();
+        assert_eq!(timeouts.get("run_tests"), Some(Duration::from_secs(600)));
+        assert_eq!(timeouts.get("read_file"), Some(Duration::from_secs(5)));
+        assert_eq!(timeouts.get("exec"), Some(Duration::from_secs(30)));
+
+        let config = Config::parse("").unwrap();
+        assert_eq!(config.tool_timeouts.build().get("exec"), None);
+    }
+
+    #[test]
+    fn parse_backend_explicit_provider() {
+        let config = Config::parse(
+            r#"
+[backend]
+provider = "anthropic"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.backend.model(), default_model());
+    }
+
+    #[test]
+    fn parse_extra_headers_and_mask_values() {
+        let config = Config::parse(
+            r#"
+[backend]
+extra_headers = { "Helicone-Auth" = "Bearer secret" }
+"#,
+        )
+        .unwrap();
+
+        let BackendConfig::Anthropic(anthropic) = &config.backend;
+        assert_eq!(anthropic.extra_headers["Helicone-Auth"], "Bearer secret");
+        let debug = format!("{anthropic:?}");
+        assert!(debug.contains("Helicone-Auth"));
+        assert!(!debug.contains("secret"));
+    }
+
+    #[test]
+    fn parse_extra_body_as_json() {
+        let config = Config::parse(
+            r#"
+[backend]
+extra_body = { service_tier = "auto", container = { id = "c1" } }
+"#,
+        )
+        .unwrap();
+
+        let BackendConfig::Anthropic(anthropic) = &config.backend;
+        assert_eq!(anthropic.extra_body["service_tier"], "auto");
+        assert_eq!(anthropic.extra_body["container"]["id"], "c1");
+    }
+
+    #[test]
+    fn parse_backend_unknown_provider_returns_error() {
+        let result = Config::parse(
+            r#"
+[backend]
+provider = "nope"
+"#,
+        );
+        assert!(matches!(result, Err(ConfigError::Parse(_))));
+    }
+
+    #[test]
+    fn parse_unknown_provider_lists_supported() {
+        let Err(ConfigError::Parse(message)) = Config::parse("[backend]\nprovider = \"nope\"\n")
+        else {
+            panic!("expected a parse error");
+        };
+        assert!(message.contains(r#"unsupported backend.provider "nope" (supported: anthropic)"#));
+    }
+
+    #[test]
+    fn validate_accepts_one_auth_method() {
+        let config = Config::parse("[backend]\napi_key_file = \"/nonexistent\"\n").unwrap();
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_unusable_settings() {
+        let cases = [
+            (
+                "[backend]\nmodel = \" \"\napi_key = \"k\"\n",
+                "backend.model is empty",
+            ),
+            ("[backend]\n", "authentication not configured"),
+            (
+                "[backend]\napi_key = \"k\"\noauth_token_file = \"t\"\n",
+                "ambiguous authentication",
+            ),
+            (
+                "[backend]\napi_key = \"k\"\n[[tools]]\ncommand = \"mcp\"\n[[tools]]\ncommand = \"\"\n",
+                "[[tools]] entry 2 has an empty command",
+            ),
+            (
+                "[backend]\napi_key = \"k\"\n[tool_cache]\ntools = [\"read_file\", \"read_secret\"]\n",
+                "[tool_cache] cannot cache read_secret",
+            ),
+        ];
+        for (toml, expected) in cases {
+            let error = Config::parse(toml).unwrap().validate().unwrap_err();
+            assert!(error.to_string().starts_with(expected), "{toml}: {error}");
+        }
+    }
+
+    #[test]
+    fn expand_vars_substitutes_and_escapes() {
+        let lookup = |name: &str| (name == "KEY").then(|| "sk-env".to_string());
+        let expand = |input| expand_vars("field", input, &lookup);
+        assert_eq!(expand("${KEY}").unwrap(), "sk-env");
+        assert_eq!(expand("a/$KEY/b").unwrap(), "a/sk-env/b");
+        assert_eq!(expand("$$KEY costs $5 $").unwrap(), "$KEY costs $5 $");
+        assert!(matches!(
+            expand("${MISSING}"),
+            Err(ConfigError::UnsetVar { name, .. }) if name == "MISSING"
+        ));
+        assert!(matches!(expand("${KEY"), Err(ConfigError::InvalidVar(_))));
+        assert!(matches!(expand("${}"), Err(ConfigError::InvalidVar(_))));
+    }
+
+    #[test]
+    fn expand_vars_covers_config_fields() {
+        let mut config: Config = toml::from_str(
+            r#"
+[backend]
+api_key = "${KEY}"
+extra_headers = { "X-Auth" = "Bearer $KEY" }
+
+[[tools]]
+command = "$KEY"
+args = ["--root", "${KEY}/src"]
+
+[allow]
+fs_read = ["$KEY/**"]
+"#,
+        )
+        .unwrap();
+        config
+            .expand_vars(&|_: &str| Some("v".to_string()))
+            .unwrap();
+
+        let BackendConfig::Anthropic(anthropic) = &config.backend;
+        assert_eq!(anthropic.api_key.as_deref(), Some("v"));
+        assert_eq!(anthropic.extra_headers["X-Auth"], "Bearer v");
+        assert_eq!(config.tools[0].command, "v");
+        assert_eq!(config.tools[0].args, ["--root", "v/src"]);
+        assert_eq!(config.policy.allow.fs_read, ["v/**"]);
+    }
+
+    #[test]
+    fn load_layered_project_overrides_user() {
+        let dir = std::env::temp_dir().join(format!("bosun-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let user = dir.join("config.toml");
+        let project = dir.join("bosun.toml");
+        std::fs::write(
+            &user,
+            r#"
+[backend]
+model = "claude-user"
+api_key = "sk-user"
+extra_headers = { "X-User" = "1" }
+
+[[tools]]
+command = "user-server"
+
+[allow]
+fs_read = ["~/notes"]
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            &project,
+            r#"
+[backend]
+oauth_token = "sk-ant-oat-project"
+
+[[tools]]
+command = "project-server"
+"#,
+        )
+        .unwrap();
+
+        let paths = [user.clone(), dir.join("missing.toml"), project.clone()];
+        let loaded = Config::load_layered(&paths);
+        let (defaults, none) = Config::load_layered(&[dir.join("missing.toml")]).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let (config, files) = loaded.unwrap();
+        assert_eq!(files, [user, project]);
+        let BackendConfig::Anthropic(anthropic) = &config.backend;
+        assert_eq!(anthropic.model, "claude-user");
+        assert_eq!(anthropic.extra_headers["X-User"], "1");
+        assert!(matches!(
+            anthropic.auth(),
+            Ok(AnthropicAuth::ClaudeCodeOauth(_))
+        ));
+        let commands: Vec<_> = config.tools.iter().map(|t| t.command.as_str()).collect();
+        assert_eq!(commands, ["project-server"]);
+        assert_eq!(config.policy.allow.fs_read, ["~/notes"]);
+
+        assert!(none.is_empty());
+        assert_eq!(defaults.backend.model(), default_model());
+    }
+
+    #[test]
+    fn auth_reads_api_key_file() {
+        let path = std::env::temp_dir().join(format!("bosun-key-{}", std::process::id()));
+        std::fs::write(&path, "sk-from-file\n").unwrap();
+
+        let config = AnthropicConfig {
+            api_key_file: Some(path.clone()),
+            ..Default::default()
+        };
+        let auth = config.auth();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(auth, Ok(AnthropicAuth::ApiKey(key)) if key == "sk-from-file"));
+    }
+
+    #[test]
+    fn auth_inline_and_file_conflict() {
+        let config = AnthropicConfig {
+            api_key: Some("sk-inline".into()),
+            api_key_file: Some(PathBuf::from("/nonexistent")),
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.auth(),
+            Err(ConfigError::ConflictingSecret("api_key"))
+        ));
+    }
+
+    #[test]
+    fn auth_missing_secret_file_returns_error() {
+        let config = AnthropicConfig {
+            oauth_token_file: Some(PathBuf::from("/nonexistent/bosun/token")),
+            ..Default::default()
+        };
+        assert!(matches!(config.auth(), Err(ConfigError::SecretFile { .. })));
+    }
 }
@@ -0,0 +1,38 @@
+//! Ask on the terminal before each tool call runs.
+
+use runtime::{ToolApproval, ToolCall};
+use std::io::{self, BufRead, Write};
+
+/// Show the call on stderr and wait for a yes or no on stdin.
+///
+/// Anything but `y` or `yes`, including end of input, denies the call.
+pub fn ask(call: &ToolCall) -> ToolApproval {
+    eprint!("┄ run {} {}? [y/N] ", call.name, call.input);
+    let _ = io::stderr().flush();
+
+    let mut answer = String::new();
+    if io::stdin().lock().read_line(&mut answer).is_err() {
+        return ToolApproval::Deny("could not read confirmation".into());
+    }
+    decide(&answer)
+}
+
+fn decide(answer: &str) -> ToolApproval {
+    match answer.trim().to_ascii_lowercase().as_str() {
+        "y" | "yes" => ToolApproval::Allow,
+        _ => ToolApproval::Deny("the user declined this tool call".into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_yes_allows() {
+        assert_eq!(decide("y\n"), ToolApproval::Allow);
+        assert_eq!(decide(" YES "), ToolApproval::Allow);
+        assert!(matches!(decide("n\n"), ToolApproval::Deny(_)));
+        assert!(matches!(decide(""), ToolApproval::Deny(_)));
+    }
+}
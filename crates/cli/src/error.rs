@@ -3,6 +3,7 @@
 use std::path::PathBuf;
 
 use crate::config::ConfigError;
+use runtime::ApiErrorKind;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -13,17 +14,30 @@ pub enum Error {
     #[error("no session found matching '{prefix}'")]
     SessionNotFound { prefix: String },
 
+    #[error("no sessions recorded yet")]
+    NoSessions,
+
     #[error("multiple sessions match '{prefix}': {matches:?}")]
     AmbiguousSession {
         prefix: String,
         matches: Vec<String>,
     },
 
+    #[error("backend check failed: {hint}\n  {error}")]
+    HealthCheck {
+        hint: String,
+        error: runtime::ModelError,
+    },
+
     #[error(transparent)]
     Config(#[from] ConfigError),
 
-    #[error("tool error: {0}")]
-    Tool(String),
+    #[error("invalid message in {path} line {line}: {message}")]
+    Import {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
 
     #[error(transparent)]
     Runtime(#[from] runtime::Error),
@@ -38,4 +52,24 @@ pub enum Error {
     Io(#[from] std::io::Error),
 }
 
+impl Error {
+    /// A failed backend check, with what the failure most likely means.
+    pub fn health_check(model: &str, error: runtime::ModelError) -> Self {
+        let hint = match &error {
+            runtime::ModelError::Api { kind, .. } => match kind {
+                ApiErrorKind::Authentication => "the API key or token was rejected".to_string(),
+                ApiErrorKind::Permission => format!("the credentials cannot use {model}"),
+                ApiErrorKind::NotFound => format!("model {model} does not exist"),
+                _ => "the provider returned an error".to_string(),
+            },
+            runtime::ModelError::Http { status: 401, .. } => {
+                "the API key or token was rejected".to_string()
+            }
+            runtime::ModelError::Network(_) => "the provider could not be reached".to_string(),
+            _ => "the provider returned an error".to_string(),
+        };
+        Self::HealthCheck { hint, error }
+    }
+}
+
 pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,119 @@
+//! Read chat input that may span several lines.
+
+use std::io::{self, BufRead, Write};
+
+const FENCE: &str = "\"\"\"";
+const PROMPT: &str = "› ";
+const CONTINUATION_PROMPT: &str = "… ";
+
+/// What the user entered at the chat prompt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Input {
+    /// A single line, which may be a command such as `quit` or `/help`.
+    Line(String),
+    /// Several lines, always sent to the model as one message.
+    Block(String),
+}
+
+/// Prompt on `out` and read one message from `input`, or `None` at end
+/// of input.
+///
+/// A line ending in `\` continues on the next line, and a line holding
+/// only `"""` starts a block that runs until the next such line, so
+/// pasted code or logs arrive as one message.
+pub fn read<R: BufRead, W: Write>(input: &mut R, out: &mut W) -> io::Result<Option<Input>> {
+    write!(out, "{PROMPT}")?;
+    out.flush()?;
+    let Some(first) = next_line(input)? else {
+        return Ok(None);
+    };
+
+    if first.trim() == FENCE {
+        let mut lines = Vec::new();
+        loop {
+            write!(out, "{CONTINUATION_PROMPT}")?;
+            out.flush()?;
+            // An unclosed block is sent as far as it got.
+            match next_line(input)? {
+                Some(line) if line.trim() == FENCE => break,
+                Some(line) => lines.push(line),
+                None => break,
+            }
+        }
+        return Ok(Some(Input::Block(lines.join("\n"))));
+    }
+
+    let Some(mut text) = first.strip_suffix('\\').map(str::to_string) else {
+        return Ok(Some(Input::Line(first)));
+    };
+    loop {
+        write!(out, "{CONTINUATION_PROMPT}")?;
+        out.flush()?;
+        let Some(line) = next_line(input)? else { break };
+        text.push('\n');
+        match line.strip_suffix('\\') {
+            Some(line) => text.push_str(line),
+            None => {
+                text.push_str(&line);
+                break;
+            }
+        }
+    }
+    Ok(Some(Input::Block(text)))
+}
+
+/// The next line without its line ending.
+fn next_line<R: BufRead>(input: &mut R) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    let len = line.trim_end_matches(['\n', '\r']).len();
+    line.truncate(len);
+    Ok(Some(line))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_all(text: &str) -> Vec<Input> {
+        let mut input = text.as_bytes();
+        let mut out = Vec::new();
+        std::iter::from_fn(|| read(&mut input, &mut out).unwrap()).collect()
+    }
+
+    #[test]
+    fn single_lines_stay_separate() {
+        assert_eq!(
+            read_all("hello\n/help\r\n"),
+            [Input::Line("hello".into()), Input::Line("/help".into())]
+        );
+    }
+
+    #[test]
+    fn backslashes_continue_lines() {
+        assert_eq!(
+            read_all("fix this:\\\n  let x = 1;\\\nplease\nnext\n"),
+            [
+                Input::Block("fix this:\n  let x = 1;\nplease".into()),
+                Input::Line("next".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn fenced_blocks_are_read_to_the_closing_fence() {
+        assert_eq!(
+            read_all("\"\"\"\n/usr/bin/ls: error\n\n  at main\n\"\"\"\nquit\n"),
+            [
+                Input::Block("/usr/bin/ls: error\n\n  at main".into()),
+                Input::Line("quit".into()),
+            ]
+        );
+        assert_eq!(
+            read_all("\"\"\"\nunclosed"),
+            [Input::Block("unclosed".into())]
+        );
+    }
+}
@@ -1,20 +1,42 @@
+mod backend;
+mod commands;
 mod config;
+mod confirm;
 mod error;
-
-use std::io::{self, BufRead, Write};
-use std::path::PathBuf;
-
-use chrono::{Local, TimeZone};
+mod input;
+mod pricing;
+mod render;
+mod replay;
+mod show_tools;
+mod term;
+
+use std::io::{self, BufRead, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, TimeZone, Utc};
 use clap::{Parser, Subcommand};
-use runtime::{AnthropicBackend, EmptyToolHost, McpToolHost, Session, ToolHost};
-use storage::{Event, EventKind, EventStore, Role};
-
-use config::Config;
+use runtime::{
+    AutoTitle, Backend, LocalToolHost, McpToolHost, Message, MultiToolHost, RequestLog, Session,
+    SessionOutcome, ToolHost,
+};
+use storage::{
+    Event, EventKind, EventStore, PrefixMatch, Role, SessionFilter, SessionId, SessionStatus,
+    UsageStats,
+};
+
+use commands::Command;
+use config::{Config, ToolConfig};
 use error::{Error, Result};
+use input::Input;
+use replay::{MockTools, Recording};
+use show_tools::ShowTools;
+use term::Term;
 
 const SYSTEM_PROMPT: &str = "You are Bosun, a helpful AI assistant. Be concise and direct.";
 const CONFIG_FILE: &str = "bosun.toml";
 const APP_NAME: &str = "bosun";
+const DATA_DIR_ENV: &str = "BOSUN_DATA_DIR";
 
 #[derive(Parser)]
 #[command(name = "bosun")]
@@ -23,26 +45,98 @@ const APP_NAME: &str = "bosun";
 struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
+
+    /// Disable colored output (also honors NO_COLOR)
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Directory holding the event database (also BOSUN_DATA_DIR)
+    #[arg(long, global = true, value_name = "DIR")]
+    data_dir: Option<PathBuf>,
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// Start an interactive chat session
-    Chat,
+    Chat {
+        /// Seed the session with messages from a JSONL file, one per line
+        #[arg(long, value_name = "FILE")]
+        import: Option<PathBuf>,
+        /// Log a summary of each model request; repeat (-vv) to log the
+        /// full request
+        #[arg(short, long, action = clap::ArgAction::Count)]
+        verbose: u8,
+        /// Print each tool call and its result to stderr, with secrets masked
+        #[arg(long)]
+        show_tools: bool,
+        /// Ask before running each tool call
+        #[arg(long)]
+        confirm_tools: bool,
+        /// Check the API key and model with the provider before starting
+        #[arg(long)]
+        check: bool,
+    },
     /// List all sessions
     Sessions {
         /// Show only the last N sessions
         #[arg(short, long, default_value = "10")]
         limit: usize,
+        /// Show only sessions that have not ended
+        #[arg(long, conflicts_with = "ended")]
+        active: bool,
+        /// Show only sessions that have ended
+        #[arg(long)]
+        ended: bool,
+        /// Show only sessions started on or after this date (YYYY-MM-DD, local time)
+        #[arg(long)]
+        since: Option<NaiveDate>,
+        /// Show only sessions started on or before this date (YYYY-MM-DD, local time)
+        #[arg(long)]
+        until: Option<NaiveDate>,
+        /// Show only sessions tagged with TAG; repeat to require several
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
     },
     /// Show event logs for a session
     Logs {
         /// Session ID (prefix match supported)
-        #[arg(short, long)]
-        session: String,
-        /// Filter by event kind (message, tool_call, tool_result)
+        #[arg(short, long, required_unless_present = "last")]
+        session: Option<String>,
+        /// Show the session with the most recent activity
+        #[arg(long, conflicts_with = "session")]
+        last: bool,
+        /// Filter by event kind (message, tool_call, tool_result, usage, error)
         #[arg(short, long)]
         kind: Option<String>,
+        /// Keep printing new events as they arrive (Ctrl-C to stop)
+        #[arg(short, long)]
+        follow: bool,
+        /// Poll interval in milliseconds when following
+        #[arg(long, default_value = "1000", requires = "follow")]
+        interval: u64,
+        /// Print how often each tool ran and failed instead of the events
+        #[arg(long, conflicts_with_all = ["kind", "follow"])]
+        tools_summary: bool,
+    },
+    /// Re-run a session's user messages in a new session, e.g. against another model
+    Replay {
+        /// Session ID (prefix match supported)
+        session: String,
+        /// Model to replay with, in place of the configured one
+        #[arg(long)]
+        model: Option<String>,
+        /// Answer tool calls with the results recorded in the original session
+        #[arg(long)]
+        mock_tools: bool,
+        /// Show a line diff of each new reply against the original
+        #[arg(long)]
+        diff: bool,
+    },
+    /// Summarize token usage and estimated cost across sessions
+    Stats {
+        /// Only count activity on or after this date (YYYY-MM-DD, local time)
+        #[arg(long)]
+        since: Option<NaiveDate>,
     },
 }
 
@@ -56,67 +150,248 @@ async fn main() {
 
 async fn run() -> Result<()> {
     let cli = Cli::parse();
+    let term = Term::detect(cli.no_color);
+    let data_dir = &data_dir(cli.data_dir);
 
     match cli.command {
-        Some(Commands::Chat) | None => cmd_chat().await,
-        Some(Commands::Sessions { limit }) => cmd_sessions(limit),
-        Some(Commands::Logs { session, kind }) => cmd_logs(&session, kind.as_deref()),
+        Some(Commands::Chat {
+            import,
+            verbose,
+            show_tools,
+            confirm_tools,
+            check,
+        }) => {
+            cmd_chat(
+                data_dir,
+                import.as_deref(),
+                verbose,
+                show_tools,
+                confirm_tools,
+                check,
+            )
+            .await
+        }
+        None => cmd_chat(data_dir, None, 0, false, false, false).await,
+        Some(Commands::Sessions {
+            limit,
+            active,
+            ended,
+            since,
+            until,
+            tags,
+        }) => {
+            let status = match (active, ended) {
+                (true, _) => Some(SessionStatus::Active),
+                (_, true) => Some(SessionStatus::Ended),
+                _ => None,
+            };
+            let filter = SessionFilter {
+                status,
+                since: since.map(local_midnight),
+                until: until.and_then(|date| date.succ_opt()).map(local_midnight),
+                tags,
+                limit: Some(limit),
+            };
+            cmd_sessions(data_dir, &filter, &term)
+        }
+        Some(Commands::Logs {
+            session,
+            // Clap requires --last whenever --session is missing.
+            last: _,
+            kind,
+            follow,
+            interval,
+            tools_summary,
+        }) => {
+            if tools_summary {
+                return cmd_tools_summary(data_dir, session.as_deref());
+            }
+            let follow = follow.then(|| Duration::from_millis(interval));
+            cmd_logs(data_dir, session.as_deref(), kind.as_deref(), follow, &term).await
+        }
+        Some(Commands::Replay {
+            session,
+            model,
+            mock_tools,
+            diff,
+        }) => cmd_replay(data_dir, &session, model, mock_tools, diff, &term).await,
+        Some(Commands::Stats { since }) => cmd_stats(data_dir, since),
     }
 }
 
-async fn cmd_chat() -> Result<()> {
+async fn cmd_chat(
+    data_dir: &Path,
+    import: Option<&Path>,
+    verbose: u8,
+    show_tools: bool,
+    confirm_tools: bool,
+    check: bool,
+) -> Result<()> {
     println!("bosun v{}", env!("CARGO_PKG_VERSION"));
     println!();
 
     // Load configuration
-    let config = load_config()?;
-
-    // Get authentication (from config or env)
-    let auth = config.auth()?;
+    let (config, config_files) = load_config()?;
+    if verbose > 0 {
+        for path in &config_files {
+            println!("Config: {}", path.display());
+        }
+    }
 
     // Initialize LLM backend
-    let backend = AnthropicBackend::builder(auth, &config.backend.model)
-        .system(SYSTEM_PROMPT)
-        .build();
+    let backend = config.backend.build(SYSTEM_PROMPT)?;
+    if check {
+        backend
+            .health_check()
+            .await
+            .map_err(|error| Error::health_check(config.backend.model(), error))?;
+        println!("Backend check passed");
+    }
+    let supports_tools = backend.supports_tools();
+    let backend_name = backend.name().to_string();
 
     // Initialize event store
-    let data_dir = data_dir();
-    std::fs::create_dir_all(&data_dir)?;
+    std::fs::create_dir_all(data_dir)?;
     let db_path = data_dir.join("events.db");
     let store = EventStore::open(&db_path)?;
 
     // Create session
-    let mut session = Session::new(store, backend, config.policy)?;
+    let request_log = match verbose {
+        0 => RequestLog::Off,
+        1 => RequestLog::Summary,
+        _ => RequestLog::Full,
+    };
+    // A model-written title costs a call, so piped one-shot runs make do
+    // with the first message.
+    let auto_title = match config.auto_title {
+        Some(false) => AutoTitle::Off,
+        Some(true) if io::stdin().is_terminal() => AutoTitle::Model,
+        _ => AutoTitle::FirstMessage,
+    };
+    let mut session = Session::new(store.clone(), backend, config.policy.clone())?
+        .log_requests(request_log)
+        .auto_title(auto_title);
+    if confirm_tools {
+        session = session.on_tool_call(confirm::ask);
+    }
+    if let Some(cache) = &config.tool_cache {
+        session = session.cache_tool_results(cache.build());
+    }
+    session = session.tool_timeouts(config.tool_timeouts.build());
 
-    println!("  Model:   {}", config.backend.model);
+    println!("  Model:   {}", config.backend.model());
     println!("  Session: {}", session.id);
 
-    // Initialize tool host
-    if let Some(tool_config) = config.tools.first() {
-        let tool_host = McpToolHost::spawn(&tool_config.command, &tool_config.args)
-            .await
-            .map_err(|e| Error::Tool(e.to_string()))?;
+    if let Some(path) = import {
+        let messages = read_messages(path)?;
+        println!("  Imported: {} messages", messages.len());
+        session.import_messages(messages)?;
+    }
 
-        let tool_count = tool_host.specs().len();
-        println!("  Tools:   {} from {}", tool_count, tool_config.command);
-        println!();
-        println!("Type 'quit' to exit.");
-        println!("─────────────────────────────────────────");
-        println!();
+    let tools_configured = !config.tools.is_empty() || config.local_tools;
+    if tools_configured && !supports_tools {
+        eprintln!(
+            "  Warning: the {backend_name} backend cannot use tools; configured tools will be ignored"
+        );
+    }
 
-        chat_loop(&mut session, &tool_host).await
+    // Initialize tool host
+    let outcome = if !config.tools.is_empty() {
+        let tool_host = spawn_tool_servers(&config.tools).await;
+        println!(
+            "  Tools:   {} from {} of {} servers",
+            tool_host.specs().len(),
+            tool_host.len(),
+            config.tools.len()
+        );
+        print_ready();
+
+        let mut session = session.with_tool_host(ShowTools::new(tool_host, show_tools));
+        chat_loop(&mut session).await?;
+        session.end_and_shutdown().await?
+    } else if config.local_tools {
+        let tool_host = LocalToolHost::new(config.policy).audit_to(store, session.id);
+        println!("  Tools:   {} built-in", tool_host.specs().len());
+        print_ready();
+
+        let mut session = session.with_tool_host(ShowTools::new(tool_host, show_tools));
+        chat_loop(&mut session).await?;
+        session.end_and_shutdown().await?
     } else {
         println!("  Tools:   none");
-        println!();
-        println!("Type 'quit' to exit.");
-        println!("─────────────────────────────────────────");
-        println!();
+        print_ready();
+
+        chat_loop(&mut session).await?;
+        session.end_and_shutdown().await?
+    };
 
-        chat_loop(&mut session, &EmptyToolHost).await
+    print_summary(&outcome);
+    Ok(())
+}
+
+fn print_summary(outcome: &SessionOutcome) {
+    println!();
+    println!("─────────────────────────────────────────");
+    println!("  Session complete");
+    println!(
+        "  Turns:  {} in {}s",
+        outcome.turns,
+        outcome.duration.as_secs()
+    );
+    println!(
+        "  Tokens: {} in → {} out",
+        outcome.usage.input_tokens, outcome.usage.output_tokens
+    );
+    println!("─────────────────────────────────────────");
+}
+
+/// Start every configured tool server, skipping any that fail.
+///
+/// A broken server is reported and left out so the others stay usable.
+async fn spawn_tool_servers(configs: &[ToolConfig]) -> MultiToolHost<McpToolHost> {
+    let mut hosts = Vec::new();
+    for tool_config in configs {
+        match McpToolHost::spawn(&tool_config.command, &tool_config.args).await {
+            Ok(host) => hosts.push(
+                host.validate_input(tool_config.validate_input)
+                    .on_progress(show_tools::print_progress),
+            ),
+            Err(e) => eprintln!(
+                "  Warning: tool server '{}' failed to start: {e}",
+                tool_config.command
+            ),
+        }
     }
+    MultiToolHost::new(hosts)
+}
+
+/// Read a JSONL transcript with one serialized message per line.
+fn read_messages(path: &Path) -> Result<Vec<Message>> {
+    let file = std::fs::File::open(path)?;
+    let mut messages = Vec::new();
+    for (index, line) in io::BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let message = serde_json::from_str(&line).map_err(|e| Error::Import {
+            path: path.to_path_buf(),
+            line: index + 1,
+            message: e.to_string(),
+        })?;
+        messages.push(message);
+    }
+    Ok(messages)
+}
+
+fn print_ready() {
+    println!();
+    println!("Type 'quit' to exit, or /help for commands.");
+    println!("─────────────────────────────────────────");
+    println!();
 }
 
-async fn chat_loop<B, H>(session: &mut Session<B>, tool_host: &H) -> Result<()>
+async fn chat_loop<B, H>(session: &mut Session<B, H>) -> Result<()>
 where
     B: runtime::Backend,
     H: ToolHost,
@@ -125,30 +400,45 @@ where
     let mut stdout = io::stdout();
 
     loop {
-        print!("› ");
-        stdout.flush()?;
-
-        let mut line = String::new();
-        if stdin.lock().read_line(&mut line)? == 0 {
+        // Locked per message, so tool confirmations can read stdin too.
+        let Some(input) = input::read(&mut stdin.lock(), &mut stdout)? else {
             break;
-        }
-
-        let input = line.trim();
+        };
+        let (input, single_line) = match &input {
+            Input::Line(line) => (line.trim(), true),
+            Input::Block(text) => (text.trim(), false),
+        };
         if input.is_empty() {
             continue;
         }
-        if input == "quit" || input == "exit" {
+        if single_line && matches!(input, "quit" | "exit") {
             break;
         }
+        let command = single_line.then(|| commands::parse(input)).flatten();
 
-        match session.chat_with_tools(input, tool_host).await {
-            Ok((response, usage)) => {
+        let result = match command {
+            None => session.chat(input).await.map(Some).map_err(Error::from),
+            Some(Ok(command)) => run_command(session, command).await,
+            Some(Err(message)) => {
+                eprintln!("{message}");
+                println!();
+                continue;
+            }
+        };
+        match result {
+            Ok(Some((response, usage))) => {
                 println!();
                 println!("{response}");
                 println!();
                 println!("  {} in → {} out", usage.input_tokens, usage.output_tokens);
                 println!();
             }
+            Ok(None) => println!(),
+            Err(Error::Runtime(runtime::Error::Refused { text })) if !text.is_empty() => {
+                eprintln!("The model refused to answer:");
+                eprintln!("{text}");
+                println!();
+            }
             Err(e) => {
                 eprintln!("Error: {e}");
                 println!();
@@ -156,81 +446,217 @@ where
         }
     }
 
-    // Session summary
-    let total = session.usage();
-    println!();
-    println!("─────────────────────────────────────────");
-    println!("  Session complete");
-    println!(
-        "  Tokens: {} in → {} out",
-        total.input_tokens, total.output_tokens
-    );
-    println!("─────────────────────────────────────────");
-
     Ok(())
 }
 
-fn cmd_sessions(limit: usize) -> Result<()> {
-    let store = open_store()?;
-    let sessions = store.list_sessions()?;
+/// Carry out a slash command, returning the new reply for `/retry`.
+async fn run_command<B, H>(
+    session: &mut Session<B, H>,
+    command: Command,
+) -> Result<Option<(String, runtime::Usage)>>
+where
+    B: runtime::Backend,
+    H: ToolHost,
+{
+    match command {
+        Command::Tokens => {
+            let usage = session.usage();
+            println!("  {} in → {} out", usage.input_tokens, usage.output_tokens);
+        }
+        Command::Clear => {
+            session.clear()?;
+            println!("  Conversation cleared");
+        }
+        Command::Save(path) => {
+            std::fs::write(&path, session.transcript())?;
+            println!("  Transcript saved to {}", path.display());
+        }
+        Command::Tools => {
+            let specs = session.tool_specs();
+            if specs.is_empty() {
+                println!("  No tools available");
+            }
+            for spec in specs {
+                println!("  {:<24} {}", spec.name, spec.description);
+            }
+        }
+        Command::System(prompt) => {
+            let restored = prompt.is_none();
+            session.set_system_prompt(prompt);
+            if restored {
+                println!("  System prompt restored to the default");
+            } else {
+                println!("  System prompt updated");
+            }
+        }
+        Command::Retry => {
+            return Ok(Some(session.regenerate_last().await?));
+        }
+        Command::Help => println!("{}", commands::HELP),
+    }
+    Ok(None)
+}
+
+fn cmd_sessions(data_dir: &Path, filter: &SessionFilter, term: &Term) -> Result<()> {
+    let store = open_store(data_dir)?;
+    let sessions = store.list_sessions_matching(filter)?;
 
     if sessions.is_empty() {
         println!("No sessions found.");
         return Ok(());
     }
 
+    let id_width = term.id_width();
     println!(
-        "{:<36}  {:<20}  {:<8}  STATUS",
-        "SESSION ID", "STARTED", "MSGS"
+        "{:<id_width$}  {:<16}  {:<6}  {:<6}  TITLE",
+        "SESSION ID", "STARTED", "MSGS", "STATUS"
     );
-    println!("{}", "─".repeat(80));
+    println!("{}", term.rule());
 
-    for summary in sessions.into_iter().take(limit) {
+    for summary in sessions {
         let started = Local
             .from_utc_datetime(&summary.started_at.naive_utc())
             .format("%Y-%m-%d %H:%M");
+        // Pad before painting so escape codes don't count toward the width.
         let status = if summary.ended_at.is_some() {
-            "ended"
+            term.paint(term::DIM, "ended ")
         } else {
-            "active"
+            term.paint(term::ACTIVE, "active")
         };
-        println!(
-            "{:<36}  {:<20}  {:<8}  {status}",
-            summary.id, started, summary.message_count
+        let id = summary.id.to_string();
+        let id: String = id.chars().take(id_width).collect();
+        let title = render::session_title(summary.title.as_deref(), &summary.tags);
+        let line = format!(
+            "{id:<id_width$}  {started:<16}  {:<6}  {status}  {title}",
+            summary.message_count
         );
+        println!("{}", line.trim_end());
     }
 
     Ok(())
 }
 
-fn cmd_logs(session_prefix: &str, kind_filter: Option<&str>) -> Result<()> {
-    let store = open_store()?;
+/// Resolve a session ID prefix to exactly one session.
+fn find_session(store: &EventStore, prefix: &str) -> Result<SessionId> {
+    match store.find_session_by_prefix(prefix)? {
+        PrefixMatch::None => Err(Error::SessionNotFound {
+            prefix: prefix.to_string(),
+        }),
+        PrefixMatch::One(id) => Ok(id),
+        PrefixMatch::Ambiguous(ids) => Err(Error::AmbiguousSession {
+            prefix: prefix.to_string(),
+            matches: ids.iter().map(ToString::to_string).collect(),
+        }),
+    }
+}
 
-    // Find session by prefix
-    let sessions = store.list_sessions()?;
-    let matching: Vec<_> = sessions
-        .iter()
-        .filter(|s| s.id.to_string().starts_with(session_prefix))
-        .collect();
-
-    let session_id = match matching.len() {
-        0 => {
-            return Err(Error::SessionNotFound {
-                prefix: session_prefix.to_string(),
-            });
-        }
-        1 => matching[0].id,
-        _ => {
-            return Err(Error::AmbiguousSession {
-                prefix: session_prefix.to_string(),
-                matches: matching.iter().map(|s| s.id.to_string()).collect(),
-            });
-        }
+async fn cmd_replay(
+    data_dir: &Path,
+    session_prefix: &str,
+    model: Option<String>,
+    mock_tools: bool,
+    diff: bool,
+    term: &Term,
+) -> Result<()> {
+    let (mut config, _) = load_config()?;
+    if let Some(model) = model {
+        config.backend.set_model(model);
+    }
+    let store = open_store(data_dir)?;
+    let source = find_session(&store, session_prefix)?;
+    let recording = Recording::from_events(&store.load_session(source)?);
+    if recording.turns.is_empty() {
+        println!("No user messages in session {source}");
+        return Ok(());
+    }
+
+    let backend = config.backend.build(SYSTEM_PROMPT)?;
+    let mut session = Session::new(store.clone(), backend, config.policy.clone())?;
+    session.set_title(format!("Replay of {source}"))?;
+    session.set_tags(vec!["replay".to_string()])?;
+
+    println!("  Source:  {source}");
+    println!("  Model:   {}", config.backend.model());
+    println!("  Session: {}", session.id);
+    println!("  Turns:   {}", recording.turns.len());
+    println!();
+
+    // With --mock-tools the servers still start, but only for their specs.
+    let tool_results = recording.tool_results.clone();
+    let outcome = if !config.tools.is_empty() {
+        let tool_host = spawn_tool_servers(&config.tools).await;
+        let mut session =
+            session.with_tool_host(MockTools::new(tool_host, tool_results, mock_tools));
+        replay_turns(&mut session, &recording, diff, term).await?;
+        session.end_and_shutdown().await?
+    } else if config.local_tools {
+        let tool_host = LocalToolHost::new(config.policy).audit_to(store, session.id);
+        let mut session =
+            session.with_tool_host(MockTools::new(tool_host, tool_results, mock_tools));
+        replay_turns(&mut session, &recording, diff, term).await?;
+        session.end_and_shutdown().await?
+    } else {
+        replay_turns(&mut session, &recording, diff, term).await?;
+        session.end_and_shutdown().await?
     };
 
+    print_summary(&outcome);
+    Ok(())
+}
+
+async fn replay_turns<B, H>(
+    session: &mut Session<B, H>,
+    recording: &Recording,
+    diff: bool,
+    term: &Term,
+) -> Result<()>
+where
+    B: runtime::Backend,
+    H: ToolHost,
+{
+    for turn in &recording.turns {
+        println!("{} {}", term.paint(term::USER, "›"), turn.input);
+        let reply = match session.chat(&turn.input).await {
+            Ok((reply, _)) => reply,
+            Err(e) => {
+                eprintln!("Error: {e}");
+                println!();
+                continue;
+            }
+        };
+        println!();
+        match (&turn.reply, diff) {
+            (Some(original), true) => {
+                for line in replay::line_diff(original, &reply) {
+                    let style = match line.as_bytes().first() {
+                        Some(b'-') => term::ERROR,
+                        Some(b'+') => term::ACTIVE,
+                        _ => term::DIM,
+                    };
+                    println!("{}", term.paint(style, &line));
+                }
+            }
+            _ => println!("{reply}"),
+        }
+        println!();
+    }
+    Ok(())
+}
+
+/// Print a session's events, then keep polling for new ones if `follow` is set.
+async fn cmd_logs(
+    data_dir: &Path,
+    session_prefix: Option<&str>,
+    kind_filter: Option<&str>,
+    follow: Option<Duration>,
+    term: &Term,
+) -> Result<()> {
+    let store = open_store(data_dir)?;
+    let session_id = resolve_session(&store, session_prefix)?;
+
     let events = store.load_events(session_id, kind_filter)?;
 
-    if events.is_empty() {
+    if events.is_empty() && follow.is_none() {
         println!("No events found for session {session_id}");
         return Ok(());
     }
@@ -238,24 +664,166 @@ fn cmd_logs(session_prefix: &str, kind_filter: Option<&str>) -> Result<()> {
     println!("Session: {session_id}");
     println!();
 
-    for event in events {
-        print_event(&event);
+    for event in &events {
+        print_event(event, term);
+    }
+
+    let Some(interval) = follow else {
+        return Ok(());
+    };
+
+    // Events are only ever appended, so anything past what was already
+    // printed is new. The cheap count avoids reloading on idle polls.
+    let mut printed = events.len();
+    let mut known_count = store.count_events(session_id)?;
+    loop {
+        tokio::time::sleep(interval).await;
+
+        let count = store.count_events(session_id)?;
+        if count == known_count {
+            continue;
+        }
+        known_count = count;
+
+        let events = store.load_events(session_id, kind_filter)?;
+        for event in events.iter().skip(printed) {
+            print_event(event, term);
+        }
+        printed = events.len();
+    }
+}
+
+/// The start of `date` in local time.
+fn local_midnight(date: NaiveDate) -> DateTime<Utc> {
+    let midnight = date.and_time(NaiveTime::MIN);
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .map_or_else(|| midnight.and_utc(), |t| t.with_timezone(&Utc))
+}
+
+/// The session named by ID prefix, or the most recently active one.
+fn resolve_session(store: &EventStore, prefix: Option<&str>) -> Result<SessionId> {
+    match prefix {
+        Some(prefix) => find_session(store, prefix),
+        None => store.latest_session()?.ok_or(Error::NoSessions),
+    }
+}
+
+fn cmd_tools_summary(data_dir: &Path, session_prefix: Option<&str>) -> Result<()> {
+    let store = open_store(data_dir)?;
+    let session_id = resolve_session(&store, session_prefix)?;
+    let usage = store.tool_usage(session_id)?;
+
+    println!("Session: {session_id}");
+    println!();
+    if usage.is_empty() {
+        println!("No tool calls");
+        return Ok(());
+    }
+
+    let width = usage
+        .iter()
+        .map(|stat| stat.tool.len())
+        .max()
+        .unwrap_or_default()
+        .max("TOOL".len());
+    println!(
+        "{:<width$}  {:>6}  {:>6}  {:>6}  {:>7}",
+        "TOOL", "CALLS", "OK", "FAILED", "SUCCESS"
+    );
+    for stat in &usage {
+        let rate = stat
+            .success_rate()
+            .map_or_else(|| "-".to_string(), |rate| format!("{:.0}%", rate * 100.0));
+        println!(
+            "{:<width$}  {:>6}  {:>6}  {:>6}  {rate:>7}",
+            stat.tool, stat.calls, stat.succeeded, stat.failed
+        );
+    }
+
+    Ok(())
+}
+
+fn cmd_stats(data_dir: &Path, since: Option<NaiveDate>) -> Result<()> {
+    let store = open_store(data_dir)?;
+    let stats = store.usage_stats(since.map(local_midnight))?;
+
+    println!("  Sessions:  {}", stats.sessions);
+    println!("  Messages:  {}", stats.messages);
+    println!(
+        "  Tokens:    {} in → {} out",
+        stats.input_tokens(),
+        stats.output_tokens()
+    );
+    println!("  Est. cost: {}", total_cost(&stats));
+
+    if stats.models.is_empty() {
+        return Ok(());
+    }
+
+    let width = stats
+        .models
+        .iter()
+        .map(|m| m.model.len())
+        .max()
+        .unwrap_or_default()
+        .max("MODEL".len());
+    println!();
+    println!(
+        "{:<width$}  {:>8}  {:>12}  {:>12}  {:>10}",
+        "MODEL", "CALLS", "INPUT", "OUTPUT", "COST"
+    );
+    for usage in &stats.models {
+        let cost = pricing::lookup(&usage.model).map_or_else(
+            || "?".to_string(),
+            |price| {
+                format!(
+                    "${:.2}",
+                    price.cost(usage.input_tokens, usage.output_tokens)
+                )
+            },
+        );
+        println!(
+            "{:<width$}  {:>8}  {:>12}  {:>12}  {cost:>10}",
+            usage.model, usage.calls, usage.input_tokens, usage.output_tokens
+        );
     }
 
     Ok(())
 }
 
-fn print_event(event: &Event) {
+/// Format the estimated spend, noting when some models have no known price.
+fn total_cost(stats: &UsageStats) -> String {
+    let mut total = 0.0;
+    let mut unpriced = false;
+    for usage in &stats.models {
+        match pricing::lookup(&usage.model) {
+            Some(price) => total += price.cost(usage.input_tokens, usage.output_tokens),
+            None => unpriced = true,
+        }
+    }
+
+    if unpriced {
+        format!("${total:.2} (excludes models without known prices)")
+    } else {
+        format!("${total:.2}")
+    }
+}
+
+fn print_event(event: &Event, term: &Term) {
     let time = Local
         .from_utc_datetime(&event.timestamp.naive_utc())
-        .format("%H:%M:%S");
+        .format("%H:%M:%S")
+        .to_string();
+    let time = term.paint(term::DIM, &format!("[{time}]"));
 
     match &event.kind {
         EventKind::SessionStart => {
-            println!("[{time}] ─── Session started ───");
+            println!("{time} ─── Session started ───");
         }
         EventKind::SessionEnd => {
-            println!("[{time}] ─── Session ended ───");
+            println!("{time} ─── Session ended ───");
         }
         EventKind::Message { role, content } => {
             let role_str = match role {
@@ -263,35 +831,106 @@ fn print_event(event: &Event) {
                 Role::Assistant => "ASST",
                 Role::System => "SYS",
             };
-            // Truncate long messages for display
-            let display_content = if content.len() > 200 {
-                format!("{}...", &content[..200])
+            let role_str = term.paint(term::role_style(*role), role_str);
+            let display_content = render::truncate(content, 200);
+            println!("{time} {role_str}: {display_content}");
+        }
+        EventKind::MessageEdited { content } => {
+            println!("{time} {}: {content}", term.paint(term::USER, "EDIT"));
+        }
+        EventKind::Regenerated => {
+            println!("{time} ─── Response regenerated ───");
+        }
+        EventKind::Cleared => {
+            println!("{time} ─── Conversation cleared ───");
+        }
+        EventKind::Error { source, message } => {
+            let label = term.paint(term::ERROR, &format!("ERROR ({source})"));
+            println!("{time} {label}: {message}");
+        }
+        EventKind::Usage {
+            model,
+            input_tokens,
+            output_tokens,
+        } => {
+            let line = format!("USAGE: {model} {input_tokens} in → {output_tokens} out");
+            println!("{time} {}", term.paint(term::DIM, &line));
+        }
+        EventKind::SecretAccess { key, allowed } => {
+            let decision = if *allowed { "allowed" } else { "denied" };
+            println!(
+                "{time} {}: {key} ({decision})",
+                term.paint(term::TOOL, "SECRET")
+            );
+        }
+        EventKind::ModelRequest {
+            message_count,
+            tool_names,
+            ..
+        } => {
+            let line = format!(
+                "REQUEST: {message_count} messages, {} tools",
+                tool_names.len()
+            );
+            println!("{time} {}", term.paint(term::DIM, &line));
+        }
+        EventKind::SessionMeta { title, tags } => {
+            let line = format!("TITLE: {}", render::session_title(title.as_deref(), tags));
+            println!("{time} {}", term.paint(term::DIM, &line));
+        }
+        EventKind::Checkpoint {
+            messages,
+            turn_complete,
+        } => {
+            let count = messages.as_array().map_or(0, Vec::len);
+            let state = if *turn_complete {
+                "complete"
             } else {
-                content.clone()
+                "in flight"
             };
-            println!("[{time}] {role_str}: {display_content}");
+            let line = format!("CHECKPOINT: {count} messages, turn {state}");
+            println!("{time} {}", term.paint(term::DIM, &line));
         }
         EventKind::ToolCall { name, input } => {
-            println!("[{time}] CALL: {name} {input:?}");
+            println!("{time} {}: {name}", term.paint(term::TOOL, "CALL"));
+            print_detail(&render::tool_input(input));
         }
         EventKind::ToolResult { name, output } => {
-            println!("[{time}] RESULT: {name} {output:?}");
+            println!("{time} {}: {name}", term.paint(term::TOOL, "RESULT"));
+            print_detail(&render::tool_output(output));
         }
     }
 }
 
-fn load_config() -> Result<Config> {
-    let config_path = PathBuf::from(CONFIG_FILE);
-
-    if config_path.exists() {
-        Ok(Config::load(&config_path)?)
-    } else {
-        Ok(Config::default_config())
+/// Print lines indented under an event header.
+fn print_detail(lines: &[String]) {
+    for line in lines {
+        println!("           {line}");
     }
 }
 
-fn open_store() -> Result<EventStore> {
-    let data_dir = data_dir();
+/// Load the user config merged with the project's `bosun.toml`, along
+/// with the files that were read.
+fn load_config() -> Result<(Config, Vec<PathBuf>)> {
+    Ok(Config::load_layered(&config_paths())?)
+}
+
+/// Config files in increasing precedence: the user-level
+/// `$XDG_CONFIG_HOME/bosun/config.toml` (`~/.config/bosun/config.toml`
+/// when unset, on every platform, like git), then `bosun.toml` in the
+/// current directory.
+fn config_paths() -> Vec<PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| dirs::home_dir().map(|home| home.join(".config")));
+    let user = config_home.map(|dir| dir.join(APP_NAME).join("config.toml"));
+    user.into_iter()
+        .chain([PathBuf::from(CONFIG_FILE)])
+        .collect()
+}
+
+fn open_store(data_dir: &Path) -> Result<EventStore> {
     let db_path = data_dir.join("events.db");
 
     if !db_path.exists() {
@@ -301,9 +940,17 @@ fn open_store() -> Result<EventStore> {
     Ok(EventStore::open(&db_path)?)
 }
 
-/// Returns the platform-appropriate data directory for Bosun.
-fn data_dir() -> PathBuf {
-    dirs::data_dir()
-        .map(|p| p.join(APP_NAME))
-        .unwrap_or_else(|| PathBuf::from(".bosun"))
+/// The data directory: `--data-dir`, else `BOSUN_DATA_DIR`, else the
+/// platform-appropriate one for Bosun.
+fn data_dir(flag: Option<PathBuf>) -> PathBuf {
+    flag.or_else(|| {
+        std::env::var_os(DATA_DIR_ENV)
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+    })
+    .unwrap_or_else(|| {
+        dirs::data_dir()
+            .map(|p| p.join(APP_NAME))
+            .unwrap_or_else(|| PathBuf::from(".bosun"))
+    })
 }
@@ -0,0 +1,50 @@
+//! Per-model token prices for cost estimates.
+
+/// Price in US dollars per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Price {
+    pub input: f64,
+    pub output: f64,
+}
+
+impl Price {
+    /// Estimated cost in US dollars for the given token counts.
+    pub fn cost(self, input_tokens: u64, output_tokens: u64) -> f64 {
+        (input_tokens as f64 * self.input + output_tokens as f64 * self.output) / 1_000_000.0
+    }
+}
+
+/// List prices by model-name prefix. More specific prefixes come first.
+const PRICE_TABLE: &[(&str, Price)] = &[
+    ("claude-opus-4", price(15.0, 75.0)),
+    ("claude-sonnet-4", price(3.0, 15.0)),
+    ("claude-3-7-sonnet", price(3.0, 15.0)),
+    ("claude-3-5-sonnet", price(3.0, 15.0)),
+    ("claude-3-5-haiku", price(0.8, 4.0)),
+    ("claude-3-opus", price(15.0, 75.0)),
+    ("claude-3-haiku", price(0.25, 1.25)),
+];
+
+const fn price(input: f64, output: f64) -> Price {
+    Price { input, output }
+}
+
+/// Look up the price for a model, if it is known.
+pub fn lookup(model: &str) -> Option<Price> {
+    PRICE_TABLE
+        .iter()
+        .find(|(prefix, _)| model.starts_with(prefix))
+        .map(|(_, price)| *price)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_matches_dated_model_names() {
+        let price = lookup("claude-sonnet-4-20250514").unwrap();
+        assert_eq!(price.cost(1_000_000, 1_000_000), 18.0);
+        assert!(lookup("gpt-4o").is_none());
+    }
+}
@@ -0,0 +1,140 @@
+//! Human-readable rendering of tool inputs and outputs.
+
+use serde_json::Value;
+
+/// Longest value shown before truncating.
+const MAX_VALUE_CHARS: usize = 200;
+
+/// Truncate `text` to `max` characters, noting how much was cut.
+pub fn truncate(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max) {
+        Some((cut, _)) => {
+            let remaining = text.len() - cut;
+            format!("{}... ({remaining} more bytes)", &text[..cut])
+        }
+        None => text.to_string(),
+    }
+}
+
+/// Render tool call arguments, one `key: value` line per argument.
+pub fn tool_input(input: &Value) -> Vec<String> {
+    match input {
+        Value::Null => Vec::new(),
+        Value::Object(map) => map
+            .iter()
+            .map(|(key, value)| format!("{key}: {}", inline_value(value)))
+            .collect(),
+        other => vec![inline_value(other)],
+    }
+}
+
+/// Render a tool result, summarizing large or binary content.
+///
+/// MCP results are arrays of content items (`text`, `image`, ...); each
+/// becomes one line. Anything else is shown as compact JSON.
+pub fn tool_output(output: &Value) -> Vec<String> {
+    match output {
+        Value::Array(items) => items.iter().map(content_item).collect(),
+        Value::Object(map) if map.contains_key("error") => {
+            vec![format!("error: {}", inline_value(&map["error"]))]
+        }
+        other => vec![inline_value(other)],
+    }
+}
+
+fn content_item(item: &Value) -> String {
+    match item.get("type").and_then(Value::as_str) {
+        Some("text") => {
+            let text = item.get("text").and_then(Value::as_str).unwrap_or_default();
+            truncate(text, MAX_VALUE_CHARS)
+        }
+        Some("image") => {
+            let data = item.get("data").and_then(Value::as_str).unwrap_or_default();
+            let mime = item
+                .get("mimeType")
+                .and_then(Value::as_str)
+                .unwrap_or("image");
+            format!("<{mime} {}>", human_size(base64_decoded_len(data)))
+        }
+        _ => inline_value(item),
+    }
+}
+
+/// Strings are shown without quotes; other values as compact JSON.
+fn inline_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => truncate(s, MAX_VALUE_CHARS),
+        other => truncate(&other.to_string(), MAX_VALUE_CHARS),
+    }
+}
+
+fn base64_decoded_len(data: &str) -> usize {
+    let padding = data.bytes().rev().take_while(|&b| b == b'=').count();
+    (data.len() * 3 / 4).saturating_sub(padding)
+}
+
+fn human_size(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{bytes}B")
+    } else {
+        format!("{}KB", bytes / 1024)
+    }
+}
+
+/// Render a session's title followed by its tags as `#tag`.
+pub fn session_title(title: Option<&str>, tags: &[String]) -> String {
+    let tags = tags.iter().map(|tag| format!("#{tag}"));
+    title
+        .map(str::to_string)
+        .into_iter()
+        .chain(tags)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn session_title_appends_tags() {
+        let tags = ["bug".to_string(), "parser".to_string()];
+        assert_eq!(session_title(Some("Fix it"), &tags), "Fix it #bug #parser");
+        assert_eq!(session_title(None, &tags[..1]), "#bug");
+        assert_eq!(session_title(None, &[]), "");
+    }
+
+    #[test]
+    fn truncate_long_text_reports_remaining() {
+        assert_eq!(truncate("abcdef", 3), "abc... (3 more bytes)");
+        assert_eq!(truncate("abc", 3), "abc");
+    }
+
+    #[test]
+    fn truncate_respects_char_boundaries() {
+        assert_eq!(truncate("héllo", 2), "hé... (3 more bytes)");
+    }
+
+    #[test]
+    fn tool_input_labels_arguments_by_key() {
+        let lines = tool_input(&json!({ "path": "./a.txt", "limit": 10 }));
+        assert_eq!(lines, vec!["limit: 10", "path: ./a.txt"]);
+    }
+
+    #[test]
+    fn tool_output_summarizes_images() {
+        let data = "A".repeat(32 * 1024);
+        let lines = tool_output(&json!([
+            { "type": "text", "text": "screenshot taken" },
+            { "type": "image", "data": data, "mimeType": "image/png" },
+        ]));
+        assert_eq!(lines, vec!["screenshot taken", "<image/png 24KB>"]);
+    }
+
+    #[test]
+    fn tool_output_shows_errors() {
+        let lines = tool_output(&json!({ "error": "tool not found: x" }));
+        assert_eq!(lines, vec!["error: tool not found: x"]);
+    }
+}
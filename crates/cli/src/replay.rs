@@ -0,0 +1,218 @@
+//! Re-run a stored session's user turns, optionally with recorded tool results.
+
+use runtime::{ToolCall, ToolError, ToolHost, ToolSpec};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use storage::{Event, EventKind, Role};
+
+/// One user message and the reply it got.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedTurn {
+    pub input: String,
+    /// The last assistant message before the next user message, if any.
+    pub reply: Option<String>,
+}
+
+/// The parts of a session's event log a replay needs.
+#[derive(Debug, Default)]
+pub struct Recording {
+    pub turns: Vec<RecordedTurn>,
+    /// Logged tool outputs per tool name, oldest first.
+    pub tool_results: HashMap<String, VecDeque<Value>>,
+}
+
+impl Recording {
+    pub fn from_events(events: &[Event]) -> Self {
+        let mut recording = Self::default();
+        for event in events {
+            match &event.kind {
+                EventKind::Message {
+                    role: Role::User,
+                    content,
+                } => recording.turns.push(RecordedTurn {
+                    input: content.clone(),
+                    reply: None,
+                }),
+                EventKind::Message {
+                    role: Role::Assistant,
+                    content,
+                } => {
+                    if let Some(turn) = recording.turns.last_mut() {
+                        turn.reply = Some(content.clone());
+                    }
+                }
+                EventKind::ToolResult { name, output } => recording
+                    .tool_results
+                    .entry(name.clone())
+                    .or_default()
+                    .push_back(output.clone()),
+                _ => {}
+            }
+        }
+        recording
+    }
+}
+
+/// Wraps a tool host, answering calls from recorded results when enabled.
+///
+/// When enabled, the inner host only supplies the tool specs and is never
+/// called. Each tool's results are handed out in the order they were logged.
+pub struct MockTools<H> {
+    inner: H,
+    recorded: Option<Mutex<HashMap<String, VecDeque<Value>>>>,
+}
+
+impl<H> MockTools<H> {
+    pub fn new(inner: H, recorded: HashMap<String, VecDeque<Value>>, enabled: bool) -> Self {
+        let recorded = enabled.then(|| Mutex::new(recorded));
+        Self { inner, recorded }
+    }
+}
+
+impl<H: ToolHost> ToolHost for MockTools<H> {
+    fn specs(&self) -> &[ToolSpec] {
+        self.inner.specs()
+    }
+
+    async fn execute(&self, call: &ToolCall) -> Result<Value, ToolError> {
+        let Some(recorded) = &self.recorded else {
+            return self.inner.execute(call).await;
+        };
+        let output = recorded
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get_mut(&call.name)
+            .and_then(VecDeque::pop_front);
+        match output {
+            // Failures were logged as `{"error": "..."}`.
+            Some(Value::Object(map)) if map.len() == 1 && map.contains_key("error") => {
+                let error = map["error"].as_str().unwrap_or_default().to_string();
+                Err(ToolError::Execution(error))
+            }
+            Some(output) => Ok(output),
+            None => Err(ToolError::Execution(format!(
+                "no recorded result left for {}",
+                call.name
+            ))),
+        }
+    }
+
+    fn log_output(&self, call: &ToolCall, output: &Value) -> Value {
+        self.inner.log_output(call, output)
+    }
+
+    fn answered(&self, call: &ToolCall, result: &Result<Value, ToolError>) {
+        self.inner.answered(call, result);
+    }
+
+    async fn shutdown(self) {
+        self.inner.shutdown().await;
+    }
+}
+
+/// Line-by-line diff of two replies: `- ` lines only in `old`, `+ ` lines
+/// only in `new`, and two spaces before lines both share.
+pub fn line_diff(old: &str, new: &str) -> Vec<String> {
+    let old: Vec<_> = old.lines().collect();
+    let new: Vec<_> = new.lines().collect();
+
+    // common[i][j] is the longest common subsequence of old[i..] and new[j..].
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::new();
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(format!("  {}", old[i]));
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && common[i + 1][j] >= common[i][j + 1]) {
+            lines.push(format!("- {}", old[i]));
+            i += 1;
+        } else {
+            lines.push(format!("+ {}", new[j]));
+            j += 1;
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use runtime::EmptyToolHost;
+    use serde_json::json;
+    use storage::SessionId;
+
+    #[test]
+    fn records_turns_and_tool_results() {
+        let id = SessionId::new();
+        let events = [
+            Event::new(id, EventKind::SessionStart),
+            Event::message(id, Role::User, "list files"),
+            Event::new(
+                id,
+                EventKind::ToolResult {
+                    name: "ls".into(),
+                    output: json!(["a.txt"]),
+                },
+            ),
+            Event::message(id, Role::Assistant, "Let me look."),
+            Event::message(id, Role::Assistant, "There is a.txt."),
+            Event::message(id, Role::User, "thanks"),
+        ];
+
+        let recording = Recording::from_events(&events);
+        assert_eq!(
+            recording.turns,
+            [
+                RecordedTurn {
+                    input: "list files".into(),
+                    reply: Some("There is a.txt.".into()),
+                },
+                RecordedTurn {
+                    input: "thanks".into(),
+                    reply: None,
+                },
+            ]
+        );
+        assert_eq!(recording.tool_results["ls"], [json!(["a.txt"])]);
+    }
+
+    #[tokio::test]
+    async fn mock_tools_replay_results_in_order() {
+        let recorded = HashMap::from([(
+            "ls".to_string(),
+            VecDeque::from([json!(1), json!({ "error": "denied" })]),
+        )]);
+        let host = MockTools::new(EmptyToolHost, recorded, true);
+        let call = ToolCall {
+            id: "call_1".into(),
+            name: "ls".into(),
+            input: json!({}),
+        };
+
+        assert_eq!(host.execute(&call).await.unwrap(), json!(1));
+        assert!(matches!(host.execute(&call).await, Err(ToolError::Execution(e)) if e == "denied"));
+        assert!(host.execute(&call).await.is_err());
+    }
+
+    #[test]
+    fn diffs_lines() {
+        assert_eq!(
+            line_diff("one\ntwo\nthree", "one\n2\nthree\nfour"),
+            ["  one", "- two", "+ 2", "  three", "+ four"]
+        );
+        assert_eq!(line_diff("same", "same"), ["  same"]);
+    }
+}
@@ -0,0 +1,147 @@
+//! Echo tool calls, their results and progress to stderr while chatting.
+
+use runtime::{ToolCall, ToolError, ToolHost, ToolProgress, ToolSpec};
+use serde_json::Value;
+
+/// Wraps a tool host, printing every call and its result to stderr when
+/// enabled, so they stay apart from the model's reply on stdout. Results
+/// are shown as the event log records them, so secrets stay masked.
+pub struct ShowTools<H> {
+    inner: H,
+    enabled: bool,
+}
+
+impl<H> ShowTools<H> {
+    pub fn new(inner: H, enabled: bool) -> Self {
+        Self { inner, enabled }
+    }
+}
+
+impl<H: ToolHost> ShowTools<H> {
+    fn format_logged_result(&self, call: &ToolCall, result: &Result<Value, ToolError>) -> String {
+        match result {
+            Ok(output) => format_result(&Ok(self.inner.log_output(call, output))),
+            Err(_) => format_result(result),
+        }
+    }
+}
+
+impl<H: ToolHost> ToolHost for ShowTools<H> {
+    fn specs(&self) -> &[ToolSpec] {
+        self.inner.specs()
+    }
+
+    async fn execute(&self, call: &ToolCall) -> Result<Value, ToolError> {
+        if self.enabled {
+            eprintln!("{}", format_call(call));
+        }
+        let result = self.inner.execute(call).await;
+        if self.enabled {
+            eprintln!("{}", self.format_logged_result(call, &result));
+        }
+        result
+    }
+
+    fn log_output(&self, call: &ToolCall, output: &Value) -> Value {
+        self.inner.log_output(call, output)
+    }
+
+    fn answered(&self, call: &ToolCall, result: &Result<Value, ToolError>) {
+        if self.enabled {
+            eprintln!("{}", format_call(call));
+            eprintln!("{}", self.format_logged_result(call, result));
+        }
+        self.inner.answered(call, result);
+    }
+
+    async fn shutdown(self) {
+        self.inner.shutdown().await;
+    }
+}
+
+/// Print a progress update from a running tool to stderr.
+pub fn print_progress(progress: ToolProgress) {
+    eprintln!("{}", format_progress(&progress));
+}
+
+fn format_progress(progress: &ToolProgress) -> String {
+    let mut line = format!("┄ {} {}", progress.tool, progress.progress);
+    if let Some(total) = progress.total {
+        line.push_str(&format!("/{total}"));
+    }
+    if let Some(message) = &progress.message {
+        line.push_str(&format!(" {message}"));
+    }
+    line
+}
+
+fn format_call(call: &ToolCall) -> String {
+    format!("┄ tool call {} {}", call.name, call.input)
+}
+
+fn format_result(result: &Result<Value, ToolError>) -> String {
+    match result {
+        Ok(output) => {
+            let output =
+                serde_json::to_string_pretty(output).unwrap_or_else(|_| output.to_string());
+            format!("┄ tool result\n{output}")
+        }
+        Err(error) => format!("┄ tool error: {error}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use policy::Policy;
+    use runtime::LocalToolHost;
+    use serde_json::json;
+
+    #[test]
+    fn formats_calls_and_results() {
+        let call = ToolCall {
+            id: "call_1".into(),
+            name: "read_file".into(),
+            input: json!({ "path": "a.txt" }),
+        };
+        assert_eq!(
+            format_call(&call),
+            "┄ tool call read_file {\"path\":\"a.txt\"}"
+        );
+        assert_eq!(
+            format_result(&Ok(json!({ "n": 1 }))),
+            "┄ tool result\n{\n  \"n\": 1\n}"
+        );
+        assert_eq!(
+            format_result(&Err(ToolError::NotFound("x".into()))),
+            "┄ tool error: tool not found: x"
+        );
+    }
+
+    #[test]
+    fn masks_results_the_event_log_masks() {
+        let show = ShowTools::new(LocalToolHost::new(Policy::default()), true);
+        let call = ToolCall {
+            id: "call_1".into(),
+            name: "read_secret".into(),
+            input: json!({ "name": "TOKEN" }),
+        };
+        let shown = show.format_logged_result(&call, &Ok(json!({ "value": "hunter2" })));
+        assert!(!shown.contains("hunter2"), "{shown}");
+        assert!(shown.contains("****"), "{shown}");
+    }
+
+    #[test]
+    fn formats_progress() {
+        let mut progress = ToolProgress {
+            tool: "run_tests".into(),
+            progress: 3.0,
+            total: Some(10.0),
+            message: Some("compiling".into()),
+        };
+        assert_eq!(format_progress(&progress), "┄ run_tests 3/10 compiling");
+        progress.total = None;
+        progress.message = None;
+        assert_eq!(format_progress(&progress), "┄ run_tests 3");
+    }
+}
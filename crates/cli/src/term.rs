@@ -0,0 +1,111 @@
+//! Terminal-aware output: color and width detection.
+
+use std::io::IsTerminal;
+
+use anstyle::{AnsiColor, Style};
+use storage::Role;
+
+/// Width assumed when stdout is not a terminal.
+const DEFAULT_WIDTH: usize = 80;
+
+/// Full session IDs are this long; narrower terminals show a prefix.
+const FULL_ID_WIDTH: usize = 36;
+const SHORT_ID_WIDTH: usize = 8;
+
+pub const USER: Style = AnsiColor::Cyan.on_default().bold();
+pub const ASSISTANT: Style = AnsiColor::Green.on_default().bold();
+pub const TOOL: Style = AnsiColor::Yellow.on_default();
+pub const ERROR: Style = AnsiColor::Red.on_default().bold();
+pub const ACTIVE: Style = AnsiColor::Green.on_default();
+pub const DIM: Style = Style::new().dimmed();
+
+/// How output should be formatted for the current terminal.
+#[derive(Debug, Clone, Copy)]
+pub struct Term {
+    color: bool,
+    width: usize,
+}
+
+impl Term {
+    /// Detect color support and width for stdout.
+    ///
+    /// Color is off when `no_color` is set, when `NO_COLOR` is set to a
+    /// non-empty value, or when stdout is not a terminal.
+    pub fn detect(no_color: bool) -> Self {
+        let stdout = std::io::stdout();
+        let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        let width = terminal_size::terminal_size_of(&stdout)
+            .map(|(w, _)| usize::from(w.0))
+            .unwrap_or(DEFAULT_WIDTH);
+
+        Self {
+            color: !no_color && !no_color_env && stdout.is_terminal(),
+            width,
+        }
+    }
+
+    /// Wrap `text` in `style` if color is enabled.
+    pub fn paint(&self, style: Style, text: &str) -> String {
+        if self.color {
+            format!("{style}{text}{style:#}")
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// A horizontal rule spanning the terminal, capped at the default width.
+    pub fn rule(&self) -> String {
+        "─".repeat(self.width.min(DEFAULT_WIDTH))
+    }
+
+    /// Columns given to session IDs in tables.
+    pub fn id_width(&self) -> usize {
+        if self.width >= DEFAULT_WIDTH {
+            FULL_ID_WIDTH
+        } else {
+            SHORT_ID_WIDTH
+        }
+    }
+}
+
+/// Style for a message role.
+pub fn role_style(role: Role) -> Style {
+    match role {
+        Role::User => USER,
+        Role::Assistant => ASSISTANT,
+        Role::System => DIM,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(color: bool, width: usize) -> Term {
+        Term { color, width }
+    }
+
+    #[test]
+    fn paint_is_plain_without_color() {
+        assert_eq!(term(false, 80).paint(USER, "USER"), "USER");
+    }
+
+    #[test]
+    fn paint_wraps_with_escape_codes() {
+        let painted = term(true, 80).paint(ERROR, "ERROR");
+        assert!(painted.starts_with("\x1b["));
+        assert!(painted.contains("ERROR"));
+        assert!(painted.ends_with("\x1b[0m"));
+    }
+
+    #[test]
+    fn narrow_terminals_shorten_ids_and_rules() {
+        let narrow = term(false, 40);
+        assert_eq!(narrow.id_width(), SHORT_ID_WIDTH);
+        assert_eq!(narrow.rule().chars().count(), 40);
+
+        let wide = term(false, 200);
+        assert_eq!(wide.id_width(), FULL_ID_WIDTH);
+        assert_eq!(wide.rule().chars().count(), 80);
+    }
+}
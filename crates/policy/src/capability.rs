@@ -46,18 +46,25 @@ impl fmt::Display for CapabilityKind {
 #[derive(Debug, Clone)]
 pub struct CapabilityRequest {
     pub kind: CapabilityKind,
-    pub scope: Option<String>, // e.g., path, domain, command
+    pub scope: Option<String>, // e.g., path, domain, program
+    /// Arguments of an exec request; `scope` holds the program.
+    pub args: Vec<String>,
 }
 
 impl CapabilityRequest {
     pub fn new(kind: CapabilityKind) -> Self {
-        Self { kind, scope: None }
+        Self {
+            kind,
+            scope: None,
+            args: Vec::new(),
+        }
     }
 
     pub fn with_scope(kind: CapabilityKind, scope: impl Into<String>) -> Self {
         Self {
             kind,
             scope: Some(scope.into()),
+            args: Vec::new(),
         }
     }
 
@@ -73,8 +80,24 @@ impl CapabilityRequest {
         Self::with_scope(CapabilityKind::NetHttp, domain)
     }
 
-    pub fn exec(command: impl Into<String>) -> Self {
-        Self::with_scope(CapabilityKind::Exec, command)
+    /// Request to run a command line, split on whitespace into the
+    /// program and its arguments. Quoting is not interpreted; use
+    /// [`Self::exec_args`] when arguments may contain spaces.
+    pub fn exec(command: impl AsRef<str>) -> Self {
+        let mut words = command.as_ref().split_whitespace();
+        let program = words.next().unwrap_or_default();
+        Self::exec_args(program, words)
+    }
+
+    /// Request to run `program` with exactly these arguments.
+    pub fn exec_args(
+        program: impl Into<String>,
+        args: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Self {
+            args: args.into_iter().map(Into::into).collect(),
+            ..Self::with_scope(CapabilityKind::Exec, program)
+        }
     }
 
     pub fn secrets_read(key: impl Into<String>) -> Self {
@@ -105,6 +128,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn exec_splits_program_and_args() {
+        let request = CapabilityRequest::exec("  git log  --oneline ");
+        assert_eq!(request.scope.as_deref(), Some("git"));
+        assert_eq!(request.args, ["log", "--oneline"]);
+
+        let request = CapabilityRequest::exec_args("echo", ["hello world"]);
+        assert_eq!(request.args, ["hello world"]);
+    }
+
     #[test]
     fn capability_kind_display_uses_name() {
         // Verify Display produces the same output as name()
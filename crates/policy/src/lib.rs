@@ -8,4 +8,4 @@ mod policy;
 
 pub use capability::{CapabilityKind, CapabilityRequest};
 pub use error::{Error, Result};
-pub use policy::{Decision, Policy};
+pub use policy::{CallLimit, Decision, DefaultDecision, Policy};
@@ -2,12 +2,16 @@
 
 use crate::{CapabilityKind, CapabilityRequest, Error, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
 /// Policy configuration loaded from TOML.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Policy {
+    /// Decision for requests that no allow rule matches.
+    #[serde(default)]
+    pub default_decision: DefaultDecision,
+
     /// Capabilities that are explicitly allowed.
     #[serde(default)]
     pub allow: AllowRules,
@@ -15,6 +19,10 @@ pub struct Policy {
     /// Capabilities that are explicitly denied (overrides allow).
     #[serde(default)]
     pub deny: DenyRules,
+
+    /// Per-session call limits, keyed by capability kind.
+    #[serde(default)]
+    pub limits: HashMap<CapabilityKind, CallLimit>,
 }
 
 /// Rules for allowed capabilities.
@@ -32,7 +40,8 @@ pub struct AllowRules {
     #[serde(default)]
     pub net_http: Vec<String>,
 
-    /// Allowed commands (exact or prefix match).
+    /// Allowed commands: a program, optionally followed by argument
+    /// patterns (see [`Policy::check`]).
     #[serde(default)]
     pub exec: Vec<String>,
 
@@ -49,6 +58,26 @@ pub struct DenyRules {
     pub all: HashSet<CapabilityKind>,
 }
 
+/// A cap on how many times a capability may be used in one session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CallLimit {
+    /// Maximum number of allowed calls.
+    pub max_calls: u32,
+}
+
+/// What [`Policy::check`] decides when no allow rule matches.
+///
+/// Explicit denials in [`DenyRules`] apply either way.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DefaultDecision {
+    /// Only allowlisted requests pass.
+    #[default]
+    Deny,
+    /// Everything not explicitly denied passes; allow rules are unused.
+    Allow,
+}
+
 /// Result of a capability check.
 #[derive(Debug, Clone)]
 pub enum Decision {
@@ -82,16 +111,56 @@ impl Policy {
         deny_all.insert(CapabilityKind::SecretsRead);
 
         Self {
+            default_decision: DefaultDecision::Deny,
             allow: AllowRules {
                 fs_read: vec![".".to_string()],  // Current dir only
                 fs_write: vec![".".to_string()], // Current dir only
                 ..Default::default()
             },
             deny: DenyRules { all: deny_all },
+            limits: HashMap::new(),
+        }
+    }
+
+    /// Create a permissive policy (allow everything not explicitly denied).
+    pub fn permissive() -> Self {
+        Self {
+            default_decision: DefaultDecision::Allow,
+            ..Default::default()
+        }
+    }
+
+    /// Check whether another call of `kind` fits within its configured limit.
+    ///
+    /// `calls_made` is the number of calls already granted in the session.
+    /// Kinds without a limit are always allowed.
+    pub fn check_limit(&self, kind: CapabilityKind, calls_made: u32) -> Decision {
+        let Some(limit) = self.limits.get(&kind) else {
+            return Decision::Allow;
+        };
+
+        if calls_made < limit.max_calls {
+            Decision::Allow
+        } else {
+            let kind_name = kind.name();
+            let max_calls = limit.max_calls;
+            Decision::Deny {
+                reason: format!("{kind_name} call limit reached ({max_calls} calls)"),
+            }
         }
     }
 
     /// Check if a capability request is allowed.
+    ///
+    /// An exec allowlist entry is a program followed by argument patterns.
+    /// A program alone (`git`) allows any arguments. Otherwise the
+    /// arguments must match word for word (`git status` allows only
+    /// `git status`), except that a trailing `*` on the last word matches
+    /// any word with that prefix plus whatever follows (`git log*` allows
+    /// `git log --oneline`).
+    ///
+    /// A request without a scope only matches a `*` entry. Anything no
+    /// rule matches gets [`Policy::default_decision`].
     pub fn check(&self, request: &CapabilityRequest) -> Decision {
         // Check explicit denials first
         if self.deny.all.contains(&request.kind) {
@@ -110,20 +179,25 @@ impl Policy {
             CapabilityKind::NetHttp => {
                 self.check_domain_allowed(&self.allow.net_http, &request.scope)
             }
-            CapabilityKind::Exec => self.check_command_allowed(&self.allow.exec, &request.scope),
+            CapabilityKind::Exec => {
+                self.check_command_allowed(&self.allow.exec, &request.scope, &request.args)
+            }
             CapabilityKind::SecretsRead => {
                 self.check_exact_allowed(&self.allow.secrets_read, &request.scope)
             }
         };
 
-        if allowed {
+        if allowed || self.default_decision == DefaultDecision::Allow {
             Decision::Allow
         } else {
             let kind_name = request.kind.name();
             let scope_info = request
                 .scope
                 .as_ref()
-                .map(|s| format!(" (scope: {s})"))
+                .map(|s| {
+                    let args = request.args.iter().map(|arg| format!(" {arg}"));
+                    format!(" (scope: {s}{})", args.collect::<String>())
+                })
                 .unwrap_or_default();
             Decision::Deny {
                 reason: format!("{kind_name} not in allowlist{scope_info}"),
@@ -133,7 +207,7 @@ impl Policy {
 
     fn check_path_allowed(&self, allowlist: &[String], scope: &Option<String>) -> bool {
         let Some(path) = scope else {
-            return !allowlist.is_empty(); // No scope = any path, allow if list non-empty
+            return allows_any(allowlist);
         };
 
         for pattern in allowlist {
@@ -162,47 +236,103 @@ impl Policy {
     }
 
     fn check_domain_allowed(&self, allowlist: &[String], scope: &Option<String>) -> bool {
-        let Some(domain) = scope else {
-            return !allowlist.is_empty();
+        let Some(scope) = scope else {
+            return allows_any(allowlist);
         };
+        let host = normalize_host(scope);
+        if host.is_empty() {
+            return false;
+        }
 
-        for allowed in allowlist {
+        allowlist.iter().any(|allowed| {
             if allowed == "*" {
                 return true;
             }
-            if domain == allowed || domain.ends_with(&format!(".{allowed}")) {
-                return true;
-            }
-        }
-        false
+            // "*.example.com" covers subdomains only; "example.com" covers
+            // the domain itself and its subdomains.
+            let (allowed, subdomains_only) = match allowed.strip_prefix("*.") {
+                Some(rest) => (normalize_host(rest), true),
+                None => (normalize_host(allowed), false),
+            };
+            !allowed.is_empty()
+                && ((!subdomains_only && host == allowed)
+                    || host
+                        .strip_suffix(allowed.as_str())
+                        .is_some_and(|prefix| prefix.ends_with('.')))
+        })
     }
 
-    fn check_command_allowed(&self, allowlist: &[String], scope: &Option<String>) -> bool {
-        let Some(cmd) = scope else {
-            return !allowlist.is_empty();
+    fn check_command_allowed(
+        &self,
+        allowlist: &[String],
+        scope: &Option<String>,
+        args: &[String],
+    ) -> bool {
+        let Some(program) = scope else {
+            return allows_any(allowlist);
         };
 
-        for allowed in allowlist {
-            if allowed == "*" {
-                return true;
-            }
-            // Exact match or prefix match (e.g., "git" allows "git status")
-            if cmd == allowed || cmd.starts_with(&format!("{allowed} ")) {
-                return true;
-            }
-        }
-        false
+        allowlist
+            .iter()
+            .any(|allowed| allowed == "*" || command_matches(allowed, program, args))
     }
 
     fn check_exact_allowed(&self, allowlist: &[String], scope: &Option<String>) -> bool {
         let Some(key) = scope else {
-            return !allowlist.is_empty();
+            return allows_any(allowlist);
         };
 
         allowlist.iter().any(|a| a == "*" || a == key)
     }
 }
 
+/// Whether an allowlist covers every scope, as a scopeless request needs.
+fn allows_any(allowlist: &[String]) -> bool {
+    allowlist.iter().any(|a| a == "*" || a == "**")
+}
+
+/// Reduce a domain or URL to its lowercase host name.
+///
+/// Strips any scheme, user info, port, path and trailing dot, so that
+/// `HTTPS://User@API.Example.com.:443/v1` becomes `api.example.com`.
+fn normalize_host(scope: &str) -> String {
+    let scope = scope.trim();
+    let rest = scope.split_once("://").map_or(scope, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let host = match host.strip_prefix('[') {
+        // Bracketed IPv6 literal, possibly followed by a port.
+        Some(v6) => v6.split(']').next().unwrap_or_default(),
+        None => host.split(':').next().unwrap_or_default(),
+    };
+    host.trim_end_matches('.').to_ascii_lowercase()
+}
+
+/// Whether `program args` fits an exec allowlist entry.
+fn command_matches(entry: &str, program: &str, args: &[String]) -> bool {
+    let mut words = entry.split_whitespace();
+    if words.next() != Some(program) {
+        return false;
+    }
+    let patterns: Vec<&str> = words.collect();
+    let Some((last, fixed)) = patterns.split_last() else {
+        return true;
+    };
+
+    if args.len() < fixed.len() || !fixed.iter().zip(args).all(|(p, a)| p == a) {
+        return false;
+    }
+    let rest = &args[fixed.len()..];
+    match last.strip_suffix('*') {
+        Some(prefix) => rest
+            .first()
+            .map_or(prefix.is_empty(), |arg| arg.starts_with(prefix)),
+        None => matches!(rest, [arg] if arg == last),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -258,4 +388,150 @@ all = ["exec"]
                 .is_allowed()
         );
     }
+
+    #[test]
+    fn test_exec_argument_patterns() {
+        let policy = Policy::parse(
+            r#"
+[allow]
+exec = ["cargo", "git status", "git log*"]
+"#,
+        )
+        .unwrap();
+        let allowed = |command: &str| policy.check(&CapabilityRequest::exec(command)).is_allowed();
+
+        assert!(allowed("cargo build --release"));
+        assert!(allowed("git status"));
+        assert!(allowed("git log"));
+        assert!(allowed("git log --oneline -5"));
+
+        assert!(!allowed("git"));
+        assert!(!allowed("git status --porcelain"));
+        assert!(!allowed("git push --force"));
+        assert!(!allowed("gitk"));
+    }
+
+    #[test]
+    fn test_exec_denial_names_full_command() {
+        let policy = Policy::default();
+        let Decision::Deny { reason } = policy.check(&CapabilityRequest::exec("rm -rf /")) else {
+            panic!("expected deny");
+        };
+        assert_eq!(reason, "exec not in allowlist (scope: rm -rf /)");
+    }
+
+    #[test]
+    fn test_net_http_matches_on_label_boundaries() {
+        let policy = Policy::parse(
+            r#"
+[allow]
+net_http = ["Example.com", "*.internal.dev"]
+"#,
+        )
+        .unwrap();
+        let allowed = |host: &str| {
+            policy
+                .check(&CapabilityRequest::net_http(host))
+                .is_allowed()
+        };
+
+        assert!(allowed("example.com"));
+        assert!(allowed("api.example.com"));
+        assert!(allowed("API.EXAMPLE.COM"));
+        assert!(allowed("example.com."));
+        assert!(allowed("https://api.example.com:8443/v1?q=1"));
+        assert!(allowed("http://user@example.com/"));
+        assert!(allowed("svc.internal.dev"));
+
+        assert!(!allowed("notexample.com"));
+        assert!(!allowed("example.com.evil.net"));
+        assert!(!allowed("https://evil.net/example.com"));
+        assert!(!allowed("http://example.com@evil.net/"));
+        assert!(!allowed("example.co"));
+        assert!(!allowed("internal.dev"));
+        assert!(!allowed(""));
+    }
+
+    #[test]
+    fn test_normalize_host() {
+        assert_eq!(
+            normalize_host(" HTTPS://API.Example.com.:443/x "),
+            "api.example.com"
+        );
+        assert_eq!(normalize_host("http://[::1]:8080/"), "::1");
+        assert_eq!(normalize_host("example.com:80"), "example.com");
+    }
+
+    #[test]
+    fn test_default_decision() {
+        let policy = Policy::parse(
+            r#"
+default_decision = "allow"
+
+[deny]
+all = ["exec"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(policy.default_decision, DefaultDecision::Allow);
+        assert!(
+            policy
+                .check(&CapabilityRequest::net_http("example.com"))
+                .is_allowed()
+        );
+        assert!(!policy.check(&CapabilityRequest::exec("ls")).is_allowed());
+
+        let policy = Policy::parse("[allow]\nnet_http = [\"example.com\"]").unwrap();
+        assert_eq!(policy.default_decision, DefaultDecision::Deny);
+        assert!(
+            !policy
+                .check(&CapabilityRequest::new(CapabilityKind::NetHttp))
+                .is_allowed()
+        );
+        assert!(
+            Policy::permissive()
+                .check(&CapabilityRequest::new(CapabilityKind::NetHttp))
+                .is_allowed()
+        );
+    }
+
+    #[test]
+    fn test_parse_limits() {
+        let toml = r#"
+[allow]
+exec = ["git"]
+
+[limits]
+exec = { max_calls = 2 }
+"#;
+        let policy = Policy::parse(toml).unwrap();
+
+        assert_eq!(
+            policy.limits.get(&CapabilityKind::Exec),
+            Some(&CallLimit { max_calls: 2 })
+        );
+        assert!(!policy.limits.contains_key(&CapabilityKind::NetHttp));
+    }
+
+    #[test]
+    fn test_check_limit_denies_at_max() {
+        let mut policy = Policy::default();
+        policy
+            .limits
+            .insert(CapabilityKind::Exec, CallLimit { max_calls: 2 });
+
+        assert!(policy.check_limit(CapabilityKind::Exec, 0).is_allowed());
+        assert!(policy.check_limit(CapabilityKind::Exec, 1).is_allowed());
+        assert!(!policy.check_limit(CapabilityKind::Exec, 2).is_allowed());
+    }
+
+    #[test]
+    fn test_check_limit_unlimited_kind_allowed() {
+        let policy = Policy::default();
+        assert!(
+            policy
+                .check_limit(CapabilityKind::NetHttp, u32::MAX)
+                .is_allowed()
+        );
+    }
 }
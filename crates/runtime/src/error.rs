@@ -1,5 +1,7 @@
 //! Runtime error types.
 
+use crate::model::{ModelError, StopReason};
+use crate::tools::ToolError;
 use thiserror::Error;
 
 /// Runtime errors.
@@ -17,9 +19,23 @@ pub enum Error {
     #[error("network error: {0}")]
     Network(String),
 
-    /// The LLM API returned an error.
+    /// A backend call failed; match on the inner error for the cause,
+    /// e.g. [`ModelError::is_retryable`].
     #[error("API error: {0}")]
-    Api(String),
+    Model(#[from] ModelError),
+
+    /// The model answered with neither text nor tool calls, e.g. only
+    /// thinking blocks.
+    #[error(
+        "model returned an empty response (stop reason: {})",
+        stop_reason.as_ref().map_or("unknown", StopReason::name)
+    )]
+    EmptyResponse { stop_reason: Option<StopReason> },
+
+    /// The model declined to answer. `text` is whatever it wrote before
+    /// stopping, often an explanation; the turn is not kept.
+    #[error("model refused to answer")]
+    Refused { text: String },
 
     /// The requested session was not found.
     #[error("session not found: {0}")]
@@ -40,6 +56,29 @@ pub enum Error {
     /// An error occurred in the policy layer.
     #[error(transparent)]
     Policy(#[from] policy::Error),
+
+    /// A tool failed; match on the inner error for the cause.
+    #[error("tool error: {0}")]
+    Tool(#[from] ToolError),
+}
+
+impl Error {
+    /// Short, stable name of the error category (e.g., "api", "storage").
+    pub fn category(&self) -> &'static str {
+        match self {
+            Self::Config(_) => "config",
+            Self::Network(_) => "network",
+            Self::Model(_) => "api",
+            Self::EmptyResponse { .. } => "empty_response",
+            Self::Refused { .. } => "refused",
+            Self::SessionNotFound(_) => "session_not_found",
+            Self::InvalidState(_) => "invalid_state",
+            Self::CapabilityDenied(_) => "capability_denied",
+            Self::Storage(_) => "storage",
+            Self::Policy(_) => "policy",
+            Self::Tool(_) => "tool",
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
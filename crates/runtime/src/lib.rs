@@ -1,7 +1,16 @@
 //! Bosun runtime — session and LLM backend management.
+//!
+//! The `anthropic` feature provides `AnthropicBackend` and `local-tools`
+//! provides `LocalToolHost`. Both are on by default; without them the
+//! crate has no HTTP client and offers only the traits, sessions and MCP
+//! tool hosts, for use with a custom [`Backend`].
 
 mod error;
 mod session;
+mod sink;
+mod summarize;
+mod tool_cache;
+mod tool_timeouts;
 
 pub mod model;
 pub mod tools;
@@ -10,16 +19,30 @@ pub mod tools;
 pub use error::{Error, Result};
 
 // Session
-pub use session::Session;
+pub use session::{AutoTitle, RequestLog, Session, SessionOutcome, ToolApproval};
+pub use sink::{EventSink, StdoutJsonSink};
+pub use summarize::SummarizingTrimmer;
+pub use tool_cache::ToolCache;
+pub use tool_timeouts::ToolTimeouts;
 
 // Model types
+#[cfg(feature = "anthropic")]
+pub use model::{AnthropicAuth, AnthropicBackend, AnthropicBackendBuilder};
 pub use model::{
-    AnthropicAuth, AnthropicBackend, AnthropicBackendBuilder, Backend, Message, ModelError,
-    ModelRequest, ModelResponse, Part, Role, Usage,
+    ApiErrorKind, Backend, FallbackBackend, LoggingBackend, Message, ModelError, ModelInfo,
+    ModelRequest, ModelRequestBuilder, ModelResponse, Part, Role, StopReason, Thinking, ToolChoice,
+    Usage,
 };
+/// The HTTP client crate, so a client for [`AnthropicBackendBuilder::client`]
+/// comes from the same version.
+#[cfg(feature = "anthropic")]
+pub use reqwest;
 
 // Tool types
+#[cfg(feature = "local-tools")]
+pub use tools::LocalToolHost;
 pub use tools::{
-    CallToolResult, EmptyToolHost, McpClient, McpError, McpToolHost, Tool, ToolArguments, ToolCall,
-    ToolError, ToolHost, ToolResult, ToolSpec,
+    CallToolResult, CallToolResultExt, EmptyToolHost, McpClient, McpError, McpToolHost,
+    MultiToolHost, StartupTimeout, Tool, ToolArguments, ToolCall, ToolError, ToolHost,
+    ToolProgress, ToolResult, ToolSpec,
 };
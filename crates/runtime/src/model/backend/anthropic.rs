@@ -1,24 +1,113 @@
 //! Anthropic API backend.
 
-use crate::model::{Backend, Message, ModelError, ModelRequest, ModelResponse, Part, Role, Usage};
+mod stream;
+
+use crate::model::{
+    ApiErrorKind, Backend, Message, ModelError, ModelInfo, ModelRequest, ModelResponse, Part, Role,
+    StopReason, Thinking, ToolChoice, Usage,
+};
 use crate::tools::{ToolCall, ToolResult, ToolSpec};
 use reqwest::RequestBuilder;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use stream::{SseAccumulator, SseDecoder, StreamEvent};
+use tokio::sync::{Semaphore, SemaphorePermit};
 
 const ANTHROPIC_API_URL: &str = "https://api.anthropic.com/v1/messages";
+const COUNT_TOKENS_URL: &str = "https://api.anthropic.com/v1/messages/count_tokens";
+const DEFAULT_API_VERSION: &str = "2023-06-01";
+
+/// Headers the backend sets itself, which extra headers may not replace.
+const RESERVED_HEADERS: &[&str] = &[
+    "accept",
+    "anthropic-beta",
+    "anthropic-dangerous-direct-browser-access",
+    "anthropic-version",
+    "authorization",
+    "content-type",
+    "idempotency-key",
+    "user-agent",
+    "x-api-key",
+    "x-app",
+];
+
+/// Request fields the backend controls, even when it leaves them out,
+/// which an extra body may not supply.
+const RESERVED_BODY_FIELDS: &[&str] = &[
+    "max_tokens",
+    "messages",
+    "metadata",
+    "model",
+    "stream",
+    "system",
+    "thinking",
+    "tool_choice",
+    "tools",
+];
+
+/// Token counts remembered before the cache is cleared.
+const TOKEN_COUNT_CACHE_SIZE: usize = 64;
+
+/// Smallest thinking budget the API accepts.
+const MIN_THINKING_BUDGET: u32 = 1024;
 
 // Claude Code OAuth constants
 const CLAUDE_CODE_VERSION: &str = "2.1.2";
 const OAUTH_BETA_HEADER: &str = "claude-code-20250219,oauth-2025-04-20,fine-grained-tool-streaming-2025-05-14,interleaved-thinking-2025-05-14";
 const OAUTH_SYSTEM_PREFIX: &str = "You are Claude Code, Anthropic's official CLI for Claude.";
 
+/// Known models by name prefix. More specific prefixes come first.
+const MODEL_TABLE: &[(&str, ModelInfo)] = &[
+    ("claude-opus-4", model_info(200_000, 32_000)),
+    ("claude-sonnet-4", model_info(200_000, 64_000)),
+    ("claude-3-7-sonnet", model_info(200_000, 64_000)),
+    ("claude-3-5-sonnet", model_info(200_000, 8_192)),
+    ("claude-3-5-haiku", model_info(200_000, 8_192)),
+    ("claude-3-opus", model_info(200_000, 4_096)),
+    ("claude-3-haiku", model_info(200_000, 4_096)),
+];
+
+/// Conservative limits for models missing from the table.
+const FALLBACK_MODEL_INFO: ModelInfo = model_info(200_000, 4_096);
+
+const fn model_info(context_window: u32, max_output_tokens: u32) -> ModelInfo {
+    ModelInfo {
+        context_window,
+        max_output_tokens,
+        supports_tools: true,
+        supports_vision: true,
+    }
+}
+
+/// Look up the limits for a model name, falling back for unknown models.
+fn lookup_model_info(model: &str) -> ModelInfo {
+    MODEL_TABLE
+        .iter()
+        .find(|(prefix, _)| model.starts_with(prefix))
+        .map(|(_, info)| *info)
+        .unwrap_or(FALLBACK_MODEL_INFO)
+}
+
+/// Placeholder shown instead of credentials.
+const REDACTED: &str = "****";
+
 /// Authentication mode for Anthropic API.
-#[derive(Debug, Clone)]
+///
+/// `Debug` and `Display` never include the secret.
+#[derive(Clone)]
 pub enum AnthropicAuth {
     /// Standard API key authentication.
     ApiKey(String),
     /// Claude Code OAuth token authentication.
+    ///
+    /// Requests identify as the Claude Code CLI: its headers are sent, and
+    /// the [`system_prefix`](Self::system_prefix) is put ahead of the
+    /// system prompt as its own block unless the backend was built with
+    /// [`oauth_system_prefix(false)`](AnthropicBackendBuilder::oauth_system_prefix).
     ClaudeCodeOauth(String),
 }
 
@@ -31,14 +120,38 @@ impl std::fmt::Display for AnthropicAuth {
     }
 }
 
+impl std::fmt::Debug for AnthropicAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ApiKey(_) => write!(f, "ApiKey({REDACTED})"),
+            Self::ClaudeCodeOauth(_) => write!(f, "ClaudeCodeOauth({REDACTED})"),
+        }
+    }
+}
+
 impl AnthropicAuth {
+    fn secret(&self) -> &str {
+        match self {
+            Self::ApiKey(key) => key,
+            Self::ClaudeCodeOauth(token) => token,
+        }
+    }
+
+    /// Mask any occurrence of the secret in text bound for errors or logs.
+    fn redact(&self, text: &str) -> String {
+        let secret = self.secret();
+        if secret.is_empty() {
+            return text.to_string();
+        }
+        text.replace(secret, REDACTED)
+    }
+
     fn apply_headers(&self, req: RequestBuilder) -> RequestBuilder {
         match self {
             Self::ApiKey(key) => req.header("x-api-key", key),
             Self::ClaudeCodeOauth(token) => req
                 .header("anthropic-dangerous-direct-browser-access", "true")
                 .header("Authorization", format!("Bearer {token}"))
-                .header("anthropic-beta", OAUTH_BETA_HEADER)
                 .header(
                     "user-agent",
                     format!("claude-cli/{CLAUDE_CODE_VERSION} (external, cli)"),
@@ -47,27 +160,42 @@ impl AnthropicAuth {
         }
     }
 
-    fn build_system(&self, system: Option<&str>) -> Option<ApiSystemPrompt> {
+    /// Text this auth mode puts ahead of the system prompt by default.
+    ///
+    /// The API only serves most models to OAuth tokens when the system
+    /// prompt starts with the Claude Code identity.
+    pub fn system_prefix(&self) -> Option<&'static str> {
+        match self {
+            Self::ApiKey(_) => None,
+            Self::ClaudeCodeOauth(_) => Some(OAUTH_SYSTEM_PREFIX),
+        }
+    }
+
+    /// Beta flags this auth mode needs, comma-separated.
+    fn beta_flags(&self) -> Option<&'static str> {
+        match self {
+            Self::ApiKey(_) => None,
+            Self::ClaudeCodeOauth(_) => Some(OAUTH_BETA_HEADER),
+        }
+    }
+
+    fn build_system(&self, system: Option<&str>, with_prefix: bool) -> Option<ApiSystemPrompt> {
         match self {
             Self::ApiKey(_) => system.map(|s| ApiSystemPrompt::Simple(s.to_string())),
             Self::ClaudeCodeOauth(_) => {
-                let mut blocks = vec![ApiSystemBlock {
-                    block_type: "text",
-                    text: OAUTH_SYSTEM_PREFIX.to_string(),
-                    cache_control: ApiCacheControl {
-                        control_type: "ephemeral",
-                    },
-                }];
-                if let Some(s) = system {
-                    blocks.push(ApiSystemBlock {
+                let prefix = self.system_prefix().filter(|_| with_prefix);
+                let blocks: Vec<_> = prefix
+                    .into_iter()
+                    .chain(system)
+                    .map(|text| ApiSystemBlock {
                         block_type: "text",
-                        text: s.to_string(),
+                        text: text.to_string(),
                         cache_control: ApiCacheControl {
                             control_type: "ephemeral",
                         },
-                    });
-                }
-                Some(ApiSystemPrompt::Blocks(blocks))
+                    })
+                    .collect();
+                (!blocks.is_empty()).then_some(ApiSystemPrompt::Blocks(blocks))
             }
         }
     }
@@ -86,6 +214,76 @@ struct ApiRequest {
     system: Option<ApiSystemPrompt>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     tools: Vec<ApiTool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<ApiToolChoice>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metadata: Option<ApiMetadata>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thinking: Option<ApiThinking>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ApiThinking {
+    Enabled { budget_tokens: u32 },
+}
+
+#[derive(Debug, Serialize)]
+struct ApiMetadata {
+    user_id: String,
+}
+
+/// Body of a `count_tokens` request: a message request minus the
+/// generation settings.
+#[derive(Debug, Serialize)]
+struct ApiCountTokensRequest {
+    model: String,
+    messages: Vec<ApiMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<ApiSystemPrompt>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<ApiTool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<ApiToolChoice>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thinking: Option<ApiThinking>,
+}
+
+impl From<ApiRequest> for ApiCountTokensRequest {
+    fn from(request: ApiRequest) -> Self {
+        Self {
+            model: request.model,
+            messages: request.messages,
+            system: request.system,
+            tools: request.tools,
+            tool_choice: request.tool_choice,
+            thinking: request.thinking,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiCountTokensResponse {
+    input_tokens: u32,
+}
+
+#[derive(Debug, Serialize)]
+struct ApiToolChoice {
+    #[serde(flatten)]
+    kind: ApiToolChoiceKind,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    disable_parallel_tool_use: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ApiToolChoiceKind {
+    Auto,
+    Any,
+    Tool { name: String },
+    None,
 }
 
 #[derive(Debug, Serialize)]
@@ -139,6 +337,13 @@ enum ApiContentBlock {
         #[serde(skip_serializing_if = "std::ops::Not::not")]
         is_error: bool,
     },
+    Thinking {
+        thinking: String,
+        signature: String,
+    },
+    RedactedThinking {
+        data: String,
+    },
 }
 
 #[derive(Debug, Serialize)]
@@ -150,8 +355,11 @@ struct ApiTool {
 
 #[derive(Debug, Deserialize)]
 struct ApiResponse {
+    model: String,
     content: Vec<ApiResponseBlock>,
     usage: ApiUsage,
+    #[serde(default)]
+    stop_reason: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -165,10 +373,50 @@ enum ApiResponseBlock {
         name: String,
         input: Value,
     },
+    Thinking {
+        thinking: String,
+        #[serde(default)]
+        signature: String,
+    },
+    RedactedThinking {
+        data: String,
+    },
     #[serde(other)]
     Unknown,
 }
 
+/// Error response body: `{"type": "error", "error": {...}}`.
+#[derive(Debug, Deserialize)]
+struct ApiErrorBody {
+    error: ApiErrorDetail,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiErrorDetail {
+    #[serde(rename = "type")]
+    kind: String,
+    message: String,
+}
+
+impl ApiErrorDetail {
+    fn into_model_error(self) -> ModelError {
+        let kind = match self.kind.as_str() {
+            "rate_limit_error" => ApiErrorKind::RateLimit,
+            "overloaded_error" => ApiErrorKind::Overloaded,
+            "invalid_request_error" => ApiErrorKind::InvalidRequest,
+            "authentication_error" => ApiErrorKind::Authentication,
+            "permission_error" => ApiErrorKind::Permission,
+            "not_found_error" => ApiErrorKind::NotFound,
+            "api_error" => ApiErrorKind::Server,
+            _ => ApiErrorKind::Other,
+        };
+        ModelError::Api {
+            kind,
+            message: self.message,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct ApiUsage {
     input_tokens: u32,
@@ -186,6 +434,15 @@ pub struct AnthropicBackendBuilder {
     model: String,
     max_tokens: u32,
     system: Option<String>,
+    api_version: String,
+    beta: Vec<String>,
+    max_concurrency: Option<usize>,
+    extra_headers: HashMap<String, String>,
+    extra_body: serde_json::Map<String, Value>,
+    user_id: Option<String>,
+    client: Option<reqwest::Client>,
+    thinking_budget: Option<u32>,
+    oauth_system_prefix: bool,
 }
 
 impl AnthropicBackendBuilder {
@@ -195,7 +452,79 @@ impl AnthropicBackendBuilder {
             model: model.into(),
             max_tokens: 4096,
             system: None,
+            api_version: DEFAULT_API_VERSION.to_string(),
+            beta: Vec::new(),
+            max_concurrency: None,
+            extra_headers: HashMap::new(),
+            extra_body: serde_json::Map::new(),
+            user_id: None,
+            client: None,
+            thinking_budget: None,
+            oauth_system_prefix: true,
+        }
+    }
+
+    /// Set the `anthropic-version` header (default "2023-06-01").
+    pub fn api_version(mut self, version: impl Into<String>) -> Self {
+        self.api_version = version.into();
+        self
+    }
+
+    /// Opt into beta features, sent as a comma-separated `anthropic-beta`
+    /// header alongside any flags the auth mode requires.
+    pub fn beta(mut self, flags: Vec<String>) -> Self {
+        self.beta = flags;
+        self
+    }
+
+    /// Send these headers with every request, e.g. for an LLM gateway.
+    ///
+    /// They are added after the standard and auth headers. Headers the
+    /// backend sets itself (auth, `content-type`, `accept`, `anthropic-*`
+    /// and `idempotency-key`) are dropped with a warning rather than
+    /// sent twice; use [`Self::api_version`] and [`Self::beta`] for those.
+    pub fn extra_headers(mut self, headers: HashMap<String, String>) -> Self {
+        self.extra_headers = headers;
+        self
+    }
+
+    /// Add these top-level fields to every Messages request, for API
+    /// parameters the backend does not model yet (e.g. `service_tier`).
+    ///
+    /// Fields the backend manages (`model`, `messages`, `system`, `tools`,
+    /// `tool_choice`, `max_tokens`, `metadata` and `stream`) are dropped
+    /// with a warning, as is a value that is not a JSON object.
+    pub fn extra_body(mut self, fields: Value) -> Self {
+        match fields {
+            Value::Object(fields) => self.extra_body = fields,
+            other => tracing::warn!(body = %other, "ignoring extra body that is not an object"),
         }
+        self
+    }
+
+    /// Send `metadata.user_id` with every Messages request, so the provider
+    /// can correlate abuse reports with an end user of a multi-tenant
+    /// service. Use an opaque ID such as a hash, not a name or email.
+    pub fn user_id(mut self, user_id: impl Into<String>) -> Self {
+        self.user_id = Some(user_id.into());
+        self
+    }
+
+    /// Allow at most `limit` model calls in flight at once (minimum 1).
+    ///
+    /// Further calls wait their turn; [`AnthropicBackend::queue_depth`]
+    /// reports how many are waiting.
+    pub fn max_concurrency(mut self, limit: usize) -> Self {
+        self.max_concurrency = Some(limit.max(1));
+        self
+    }
+
+    /// Send requests through `client` instead of a default one, e.g. to
+    /// use a proxy, a custom CA bundle or timeouts, or to share one
+    /// connection pool across backends. Clones of a client share its pool.
+    pub fn client(mut self, client: reqwest::Client) -> Self {
+        self.client = Some(client);
+        self
     }
 
     pub fn max_tokens(mut self, max_tokens: u32) -> Self {
@@ -203,18 +532,71 @@ impl AnthropicBackendBuilder {
         self
     }
 
+    /// Enable extended thinking with up to `budget_tokens` of reasoning
+    /// per call (minimum 1024).
+    ///
+    /// The budget counts toward `max_tokens`, so a request whose
+    /// `max_tokens` does not exceed the budget is sent with the budget
+    /// added on top. Thinking blocks are kept in the conversation and sent
+    /// back as the API requires. With thinking on, the API only accepts a
+    /// tool choice of auto or none.
+    pub fn thinking_budget(mut self, budget_tokens: u32) -> Self {
+        self.thinking_budget = Some(budget_tokens.max(MIN_THINKING_BUDGET));
+        self
+    }
+
     pub fn system(mut self, system: impl Into<String>) -> Self {
         self.system = Some(system.into());
         self
     }
 
+    /// Whether OAuth requests start the system prompt with
+    /// [`AnthropicAuth::system_prefix`] (default true; no effect for API
+    /// keys).
+    ///
+    /// Only turn this off for endpoints known to accept OAuth requests
+    /// without it; the Anthropic API rejects them for most models.
+    pub fn oauth_system_prefix(mut self, enabled: bool) -> Self {
+        self.oauth_system_prefix = enabled;
+        self
+    }
+
     pub fn build(self) -> AnthropicBackend {
+        let extra_headers = self
+            .extra_headers
+            .into_iter()
+            .filter(|(name, _)| {
+                let reserved = RESERVED_HEADERS.contains(&name.to_ascii_lowercase().as_str());
+                if reserved {
+                    tracing::warn!(header = %name, "ignoring extra header the backend sets itself");
+                }
+                !reserved
+            })
+            .collect();
+        let mut extra_body = self.extra_body;
+        extra_body.retain(|name, _| {
+            let reserved = RESERVED_BODY_FIELDS.contains(&name.as_str());
+            if reserved {
+                tracing::warn!(field = %name, "ignoring extra body field the backend sets itself");
+            }
+            !reserved
+        });
+
         AnthropicBackend {
-            client: reqwest::Client::new(),
+            client: self.client.unwrap_or_default(),
             auth: self.auth,
             model: self.model,
             max_tokens: self.max_tokens,
             system: self.system,
+            api_version: self.api_version,
+            beta: self.beta,
+            token_counts: Mutex::default(),
+            limiter: self.max_concurrency.map(CallLimiter::new),
+            extra_headers,
+            extra_body,
+            user_id: self.user_id,
+            thinking_budget: self.thinking_budget,
+            oauth_system_prefix: self.oauth_system_prefix,
         }
     }
 }
@@ -226,6 +608,47 @@ pub struct AnthropicBackend {
     model: String,
     max_tokens: u32,
     system: Option<String>,
+    api_version: String,
+    beta: Vec<String>,
+    /// `count_tokens` results keyed by a hash of the request body.
+    token_counts: Mutex<HashMap<u64, u32>>,
+    limiter: Option<CallLimiter>,
+    extra_headers: Vec<(String, String)>,
+    extra_body: serde_json::Map<String, Value>,
+    user_id: Option<String>,
+    thinking_budget: Option<u32>,
+    oauth_system_prefix: bool,
+}
+
+/// Caps concurrent model calls, counting the ones left waiting.
+struct CallLimiter {
+    semaphore: Semaphore,
+    waiting: AtomicUsize,
+}
+
+impl CallLimiter {
+    fn new(limit: usize) -> Self {
+        Self {
+            semaphore: Semaphore::new(limit),
+            waiting: AtomicUsize::new(0),
+        }
+    }
+}
+
+/// Counts a caller as waiting until dropped, even if its call is cancelled.
+struct Waiting<'a>(&'a AtomicUsize);
+
+impl<'a> Waiting<'a> {
+    fn new(count: &'a AtomicUsize) -> Self {
+        count.fetch_add(1, Ordering::Relaxed);
+        Self(count)
+    }
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
 }
 
 impl AnthropicBackend {
@@ -233,9 +656,27 @@ impl AnthropicBackend {
         AnthropicBackendBuilder::new(auth, model)
     }
 
+    /// Model calls waiting for a slot under [`AnthropicBackendBuilder::max_concurrency`].
+    pub fn queue_depth(&self) -> usize {
+        self.limiter
+            .as_ref()
+            .map_or(0, |limiter| limiter.waiting.load(Ordering::Relaxed))
+    }
+
+    /// Wait for a call slot, if concurrency is limited.
+    async fn acquire(&self) -> Option<SemaphorePermit<'_>> {
+        let limiter = self.limiter.as_ref()?;
+        let _waiting = Waiting::new(&limiter.waiting);
+        // The semaphore is never closed.
+        limiter.semaphore.acquire().await.ok()
+    }
+
+    /// The Messages API only has user and assistant turns. Tool results
+    /// travel as user content blocks; system messages are normally sent
+    /// in the `system` field and filtered out before this is reached.
     fn role_to_api(role: Role) -> &'static str {
         match role {
-            Role::User | Role::System => "user",
+            Role::User | Role::Tool | Role::System => "user",
             Role::Assistant => "assistant",
         }
     }
@@ -262,6 +703,13 @@ impl AnthropicBackend {
                     name: call.name.clone(),
                     input: call.input.clone(),
                 },
+                Part::Thinking(Thinking::Text { text, signature }) => ApiContentBlock::Thinking {
+                    thinking: text.clone(),
+                    signature: signature.clone(),
+                },
+                Part::Thinking(Thinking::Redacted { data }) => {
+                    ApiContentBlock::RedactedThinking { data: data.clone() }
+                }
                 Part::ToolResult(result) => {
                     let (tool_use_id, content, is_error) = match result {
                         ToolResult::Success {
@@ -288,6 +736,28 @@ impl AnthropicBackend {
         }
     }
 
+    /// Map the canonical tool choice onto the wire format.
+    ///
+    /// The API rejects `tool_choice` without tools, so it is omitted then.
+    /// `disable_parallel_tool_use` is meaningless when tools are forbidden.
+    fn tool_choice_to_api(request: &ModelRequest<'_>, has_tools: bool) -> Option<ApiToolChoice> {
+        if !has_tools {
+            return None;
+        }
+        let kind = match &request.tool_choice {
+            ToolChoice::Auto => ApiToolChoiceKind::Auto,
+            ToolChoice::Any => ApiToolChoiceKind::Any,
+            ToolChoice::Tool { name } => ApiToolChoiceKind::Tool { name: name.clone() },
+            ToolChoice::None => ApiToolChoiceKind::None,
+        };
+        let disable_parallel_tool_use =
+            request.disable_parallel_tool_use && request.tool_choice != ToolChoice::None;
+        Some(ApiToolChoice {
+            kind,
+            disable_parallel_tool_use,
+        })
+    }
+
     fn tool_to_api(spec: &ToolSpec) -> ApiTool {
         ApiTool {
             name: spec.name.clone(),
@@ -304,6 +774,16 @@ impl AnthropicBackend {
                 ApiResponseBlock::ToolUse { id, name, input } => {
                     Some(Part::ToolCall(ToolCall { id, name, input }))
                 }
+                ApiResponseBlock::Thinking {
+                    thinking,
+                    signature,
+                } => Some(Part::Thinking(Thinking::Text {
+                    text: thinking,
+                    signature,
+                })),
+                ApiResponseBlock::RedactedThinking { data } => {
+                    Some(Part::Thinking(Thinking::Redacted { data }))
+                }
                 ApiResponseBlock::Unknown => None,
             })
             .collect();
@@ -313,16 +793,134 @@ impl AnthropicBackend {
             parts,
         }
     }
-}
 
-impl std::fmt::Display for AnthropicBackend {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "anthropic({}, auth={})", self.model, self.auth)
+    /// Like [`Backend::call`], but streams the response, passing text to
+    /// `on_text` as it is generated.
+    pub async fn call_streaming(
+        &self,
+        request: ModelRequest<'_>,
+        mut on_text: impl FnMut(&str) + Send,
+    ) -> Result<ModelResponse, ModelError> {
+        let _permit = self.acquire().await;
+        let mut response = self.send(self.http_request(&request, true)).await?;
+        let request_id = request_id(&response);
+
+        let mut decoder = SseDecoder::default();
+        let mut accumulator = SseAccumulator::default();
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| ModelError::Network(self.auth.redact(&e.to_string())))?
+        {
+            for data in decoder.feed(&chunk) {
+                let event: StreamEvent = serde_json::from_str(&data)
+                    .map_err(|e| ModelError::InvalidResponse(format!("stream event: {e}")))?;
+                if let Some(text) = accumulator.push(event)? {
+                    on_text(&text);
+                }
+            }
+        }
+
+        let mut response = accumulator.finish()?;
+        response.request_id = request_id;
+        Ok(response)
     }
-}
 
-impl Backend for AnthropicBackend {
-    async fn call(&self, request: ModelRequest<'_>) -> Result<ModelResponse, ModelError> {
+    /// Send a request, turning non-success statuses into errors.
+    async fn send(&self, request: RequestBuilder) -> Result<reqwest::Response, ModelError> {
+        let response = request
+            .send()
+            .await
+            .map_err(|e| ModelError::Network(self.auth.redact(&e.to_string())))?;
+
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            let body = self.auth.redact(&response.text().await.unwrap_or_default());
+            return Err(error_from_body(status, body));
+        }
+        Ok(response)
+    }
+
+    /// Build the full HTTP request for a model call.
+    fn http_request(&self, request: &ModelRequest<'_>, stream: bool) -> RequestBuilder {
+        let accept = if stream {
+            "text/event-stream"
+        } else {
+            "application/json"
+        };
+        let mut req = self.post(ANTHROPIC_API_URL, accept);
+
+        if let Some(key) = request.idempotency_key {
+            req = req.header("idempotency-key", key);
+        }
+
+        req.json(&self.request_body(request, stream))
+    }
+
+    /// The JSON body a Messages call for `request` would send, without
+    /// sending it, e.g. to inspect, diff or replay it against the API, or
+    /// to build test fixtures.
+    ///
+    /// Nothing is redacted: the body holds the full conversation and any
+    /// extra body fields. Credentials travel in headers, not here.
+    pub fn request_json(&self, request: &ModelRequest<'_>) -> Value {
+        self.request_body(request, false)
+    }
+
+    fn request_body(&self, request: &ModelRequest<'_>, stream: bool) -> Value {
+        let mut body = self.build_request(request);
+        body.stream = stream;
+        // Serializing plain data structs cannot fail.
+        let mut body = serde_json::to_value(body).unwrap_or_default();
+        if let Value::Object(fields) = &mut body {
+            fields.extend(self.extra_body.clone());
+        }
+        body
+    }
+
+    /// Body for [`Backend::health_check`]: a minimal token count that
+    /// skips the cache.
+    fn health_check_body(&self) -> ApiCountTokensRequest {
+        let request = ModelRequest::builder().message(Message {
+            role: Role::User,
+            parts: vec![Part::Text("ping".into())],
+        });
+        ApiCountTokensRequest::from(self.build_request(&request.build()))
+    }
+
+    /// Build the HTTP request for counting a model call's input tokens.
+    fn count_tokens_request(&self, body: &ApiCountTokensRequest) -> RequestBuilder {
+        self.post(COUNT_TOKENS_URL, "application/json").json(body)
+    }
+
+    /// Start a POST with the version, beta and auth headers every
+    /// endpoint needs.
+    fn post(&self, url: &str, accept: &str) -> RequestBuilder {
+        let mut req = self
+            .client
+            .post(url)
+            .header("anthropic-version", &self.api_version)
+            .header("content-type", "application/json")
+            .header("accept", accept);
+
+        let beta: Vec<&str> = self
+            .auth
+            .beta_flags()
+            .into_iter()
+            .chain(self.beta.iter().map(String::as_str))
+            .collect();
+        if !beta.is_empty() {
+            req = req.header("anthropic-beta", beta.join(","));
+        }
+
+        let mut req = self.auth.apply_headers(req);
+        for (name, value) in &self.extra_headers {
+            req = req.header(name, value);
+        }
+        req
+    }
+
+    fn build_request(&self, request: &ModelRequest<'_>) -> ApiRequest {
         let api_messages: Vec<ApiMessage> = request
             .messages
             .iter()
@@ -331,40 +929,100 @@ impl Backend for AnthropicBackend {
             .collect();
 
         let tools: Vec<ApiTool> = request.tools.iter().map(Self::tool_to_api).collect();
+        let tool_choice = Self::tool_choice_to_api(request, !tools.is_empty());
+
+        let mut max_tokens = request.max_tokens.unwrap_or(self.max_tokens);
+        if let Some(budget) = self.thinking_budget
+            && max_tokens <= budget
+        {
+            max_tokens = max_tokens.saturating_add(budget);
+        }
 
-        let api_request = ApiRequest {
+        ApiRequest {
             model: self.model.clone(),
-            max_tokens: self.max_tokens,
+            max_tokens,
             messages: api_messages,
-            system: self.auth.build_system(self.system.as_deref()),
+            system: self.auth.build_system(
+                request.system.or(self.system.as_deref()),
+                self.oauth_system_prefix,
+            ),
             tools,
-        };
+            tool_choice,
+            metadata: self.user_id.clone().map(|user_id| ApiMetadata { user_id }),
+            thinking: self
+                .thinking_budget
+                .map(|budget_tokens| ApiThinking::Enabled { budget_tokens }),
+            stream: false,
+        }
+    }
+}
 
-        let req = self
-            .client
-            .post(ANTHROPIC_API_URL)
-            .header("anthropic-version", "2023-06-01")
-            .header("content-type", "application/json")
-            .header("accept", "application/json");
+/// Parse an error response, keeping the raw body if it isn't the
+/// documented error shape.
+fn error_from_body(status: u16, body: String) -> ModelError {
+    match serde_json::from_str::<ApiErrorBody>(&body) {
+        Ok(parsed) => parsed.error.into_model_error(),
+        Err(_) => ModelError::Http { status, body },
+    }
+}
 
-        let req = self.auth.apply_headers(req);
+/// Cache key for a `count_tokens` body.
+fn token_count_key(body: &ApiCountTokensRequest) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    // Serializing plain data structs cannot fail.
+    serde_json::to_string(body)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    hasher.finish()
+}
 
-        let response = req
-            .json(&api_request)
-            .send()
-            .await
-            .map_err(|e| ModelError::Network(e.to_string()))?;
+/// The provider's ID for a request, from the `request-id` header.
+fn request_id(response: &reqwest::Response) -> Option<String> {
+    response
+        .headers()
+        .get("request-id")
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_owned)
+}
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            return Err(ModelError::Api(format!("{status}: {body}")));
-        }
+impl std::fmt::Debug for AnthropicBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AnthropicBackend")
+            .field("auth", &self.auth)
+            .field("model", &self.model)
+            .field("max_tokens", &self.max_tokens)
+            .field("system", &self.system)
+            .field("api_version", &self.api_version)
+            .field("beta", &self.beta)
+            // Gateway headers often carry credentials; show names only.
+            .field(
+                "extra_headers",
+                &self
+                    .extra_headers
+                    .iter()
+                    .map(|(name, _)| name)
+                    .collect::<Vec<_>>(),
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+impl std::fmt::Display for AnthropicBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "anthropic({}, auth={})", self.model, self.auth)
+    }
+}
+
+impl Backend for AnthropicBackend {
+    async fn call(&self, request: ModelRequest<'_>) -> Result<ModelResponse, ModelError> {
+        let _permit = self.acquire().await;
+        let response = self.send(self.http_request(&request, false)).await?;
+        let request_id = request_id(&response);
 
         let api_response: ApiResponse = response
             .json()
             .await
-            .map_err(|e| ModelError::InvalidResponse(e.to_string()))?;
+            .map_err(|e| ModelError::InvalidResponse(self.auth.redact(&e.to_string())))?;
 
         let message = Self::response_to_message(api_response.content);
         let usage = Usage {
@@ -372,13 +1030,80 @@ impl Backend for AnthropicBackend {
             output_tokens: api_response.usage.output_tokens,
         };
 
-        Ok(ModelResponse { message, usage })
+        Ok(ModelResponse {
+            message,
+            usage,
+            model: api_response.model,
+            request_id,
+            stop_reason: api_response
+                .stop_reason
+                .as_deref()
+                .map(StopReason::from_anthropic),
+        })
+    }
+
+    fn model_info(&self) -> ModelInfo {
+        lookup_model_info(&self.model)
+    }
+
+    fn name(&self) -> &str {
+        "anthropic"
+    }
+
+    /// Count tokens with the `count_tokens` endpoint.
+    ///
+    /// Counts are cached by request body, so asking again about an
+    /// unchanged conversation costs no round-trip.
+    async fn estimate_tokens(&self, request: &ModelRequest<'_>) -> Result<u32, ModelError> {
+        let body = ApiCountTokensRequest::from(self.build_request(request));
+        let key = token_count_key(&body);
+        if let Some(&count) = self.token_counts.lock().unwrap().get(&key) {
+            return Ok(count);
+        }
+
+        let response: ApiCountTokensResponse = self
+            .send(self.count_tokens_request(&body))
+            .await?
+            .json()
+            .await
+            .map_err(|e| ModelError::InvalidResponse(self.auth.redact(&e.to_string())))?;
+
+        let mut cache = self.token_counts.lock().unwrap();
+        if cache.len() >= TOKEN_COUNT_CACHE_SIZE {
+            cache.clear();
+        }
+        cache.insert(key, response.input_tokens);
+        Ok(response.input_tokens)
+    }
+
+    /// Count the tokens of a one-word message, which is free and fails
+    /// the same way a real call would on a bad key or unknown model.
+    async fn health_check(&self) -> Result<(), ModelError> {
+        let body = self.health_check_body();
+        self.send(self.count_tokens_request(&body)).await.map(drop)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde_json::json;
+
+    fn test_backend() -> AnthropicBackend {
+        AnthropicBackend::builder(AnthropicAuth::ApiKey("test".into()), "test-model").build()
+    }
+
+    fn test_tool() -> ToolSpec {
+        ToolSpec {
+            name: "extract".into(),
+            description: "Extract fields".into(),
+            schema: json!({ "type": "object" }),
+        }
+    }
+
+    fn request_json(backend: &AnthropicBackend, request: ModelRequest<'_>) -> Value {
+        backend.request_json(&request)
+    }
 
     #[test]
     fn auth_display() {
@@ -387,4 +1112,621 @@ mod tests {
         assert_eq!(api.to_string(), "api_key");
         assert_eq!(oauth.to_string(), "claude_code_oauth");
     }
+
+    #[test]
+    fn auth_debug_masks_secret() {
+        let api = AnthropicAuth::ApiKey("sk-ant-api-secret".into());
+        let oauth = AnthropicAuth::ClaudeCodeOauth("sk-ant-oat-secret".into());
+        assert_eq!(format!("{api:?}"), "ApiKey(****)");
+        assert_eq!(format!("{oauth:?}"), "ClaudeCodeOauth(****)");
+    }
+
+    #[test]
+    fn backend_debug_masks_secret() {
+        let backend =
+            AnthropicBackend::builder(AnthropicAuth::ApiKey("sk-ant-api-secret".into()), "m")
+                .build();
+        let debug = format!("{backend:?}");
+        assert!(!debug.contains("sk-ant-api-secret"));
+        assert!(debug.contains("ApiKey(****)"));
+    }
+
+    #[test]
+    fn auth_redact_masks_secret_in_text() {
+        let auth = AnthropicAuth::ApiKey("sk-ant-api-secret".into());
+        assert_eq!(
+            auth.redact("invalid key sk-ant-api-secret given"),
+            "invalid key **** given"
+        );
+    }
+
+    #[test]
+    fn tool_choice_tool_serializes_name() {
+        let backend = test_backend();
+        let tools = [test_tool()];
+        let body = request_json(
+            &backend,
+            ModelRequest {
+                messages: &[],
+                tools: &tools,
+                tool_choice: ToolChoice::Tool {
+                    name: "extract".into(),
+                },
+                disable_parallel_tool_use: false,
+                system: None,
+                idempotency_key: None,
+                max_tokens: None,
+            },
+        );
+        assert_eq!(
+            body["tool_choice"],
+            json!({ "type": "tool", "name": "extract" })
+        );
+    }
+
+    #[test]
+    fn tool_choice_auto_serializes_type() {
+        let backend = test_backend();
+        let tools = [test_tool()];
+        let body = request_json(
+            &backend,
+            ModelRequest {
+                messages: &[],
+                tools: &tools,
+                tool_choice: ToolChoice::Auto,
+                disable_parallel_tool_use: false,
+                system: None,
+                idempotency_key: None,
+                max_tokens: None,
+            },
+        );
+        assert_eq!(body["tool_choice"], json!({ "type": "auto" }));
+    }
+
+    #[test]
+    fn tool_choice_omitted_without_tools() {
+        let backend = test_backend();
+        let body = request_json(
+            &backend,
+            ModelRequest {
+                messages: &[],
+                tools: &[],
+                tool_choice: ToolChoice::Any,
+                disable_parallel_tool_use: false,
+                system: None,
+                idempotency_key: None,
+                max_tokens: None,
+            },
+        );
+        assert!(body.get("tool_choice").is_none());
+    }
+
+    #[test]
+    fn disable_parallel_tool_use_is_sent_in_tool_choice() {
+        let backend = test_backend();
+        let tools = [test_tool()];
+        let body = request_json(
+            &backend,
+            ModelRequest {
+                messages: &[],
+                tools: &tools,
+                tool_choice: ToolChoice::Auto,
+                disable_parallel_tool_use: true,
+                system: None,
+                idempotency_key: None,
+                max_tokens: None,
+            },
+        );
+        assert_eq!(
+            body["tool_choice"],
+            json!({ "type": "auto", "disable_parallel_tool_use": true })
+        );
+    }
+
+    #[test]
+    fn idempotency_key_is_sent_as_header() {
+        let backend = test_backend();
+        let request = ModelRequest {
+            messages: &[],
+            tools: &[],
+            tool_choice: ToolChoice::Auto,
+            disable_parallel_tool_use: false,
+            system: None,
+            idempotency_key: Some("key-1"),
+            max_tokens: None,
+        };
+        let http = backend.http_request(&request, false).build().unwrap();
+        assert_eq!(http.headers()["idempotency-key"], "key-1");
+
+        let request = ModelRequest {
+            idempotency_key: None,
+            max_tokens: None,
+            ..request
+        };
+        let http = backend.http_request(&request, false).build().unwrap();
+        assert!(http.headers().get("idempotency-key").is_none());
+    }
+
+    #[test]
+    fn streaming_request_asks_for_event_stream() {
+        let backend = test_backend();
+        let request = ModelRequest {
+            messages: &[],
+            tools: &[],
+            tool_choice: ToolChoice::Auto,
+            disable_parallel_tool_use: false,
+            system: None,
+            idempotency_key: None,
+            max_tokens: None,
+        };
+
+        let http = backend.http_request(&request, true).build().unwrap();
+        assert_eq!(http.headers()["accept"], "text/event-stream");
+        let body: Value = serde_json::from_slice(http.body().unwrap().as_bytes().unwrap()).unwrap();
+        assert_eq!(body["stream"], true);
+
+        let http = backend.http_request(&request, false).build().unwrap();
+        let body: Value = serde_json::from_slice(http.body().unwrap().as_bytes().unwrap()).unwrap();
+        assert!(body.get("stream").is_none());
+    }
+
+    #[test]
+    fn error_body_parses_into_kind() {
+        let body = r#"{"type":"error","error":{"type":"rate_limit_error","message":"Slow down"}}"#;
+        let err = error_from_body(429, body.into());
+        assert!(matches!(
+            &err,
+            ModelError::Api { kind: ApiErrorKind::RateLimit, message } if message == "Slow down"
+        ));
+        assert!(err.is_retryable());
+
+        let body = r#"{"type":"error","error":{"type":"billing_error","message":"Pay up"}}"#;
+        assert!(matches!(
+            error_from_body(400, body.into()),
+            ModelError::Api {
+                kind: ApiErrorKind::Other,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn unparseable_error_body_falls_back_to_http() {
+        let err = error_from_body(502, "<html>Bad Gateway</html>".into());
+        assert!(
+            matches!(err, ModelError::Http { status: 502, ref body } if body.contains("Bad Gateway"))
+        );
+        assert!(err.is_retryable());
+    }
+
+    fn empty_request() -> ModelRequest<'static> {
+        ModelRequest {
+            messages: &[],
+            tools: &[],
+            tool_choice: ToolChoice::Auto,
+            disable_parallel_tool_use: false,
+            system: None,
+            idempotency_key: None,
+            max_tokens: None,
+        }
+    }
+
+    #[test]
+    fn version_and_beta_headers_default() {
+        let http = test_backend()
+            .http_request(&empty_request(), false)
+            .build()
+            .unwrap();
+        assert_eq!(http.headers()["anthropic-version"], "2023-06-01");
+        assert!(http.headers().get("anthropic-beta").is_none());
+    }
+
+    #[test]
+    fn version_and_beta_headers_are_configurable() {
+        let backend = AnthropicBackend::builder(AnthropicAuth::ApiKey("test".into()), "test-model")
+            .api_version("2025-01-01")
+            .beta(vec!["feature-a".into(), "feature-b".into()])
+            .build();
+        let http = backend
+            .http_request(&empty_request(), false)
+            .build()
+            .unwrap();
+        assert_eq!(http.headers()["anthropic-version"], "2025-01-01");
+        assert_eq!(http.headers()["anthropic-beta"], "feature-a,feature-b");
+    }
+
+    #[test]
+    fn oauth_beta_flags_are_kept_with_custom_ones() {
+        let backend =
+            AnthropicBackend::builder(AnthropicAuth::ClaudeCodeOauth("test".into()), "test-model")
+                .beta(vec!["feature-a".into()])
+                .build();
+        let http = backend
+            .http_request(&empty_request(), false)
+            .build()
+            .unwrap();
+        assert_eq!(
+            http.headers()["anthropic-beta"],
+            format!("{OAUTH_BETA_HEADER},feature-a")
+        );
+    }
+
+    #[test]
+    fn tool_and_system_roles_are_sent_as_user() {
+        let backend = test_backend();
+        let messages = [
+            Message {
+                role: Role::System,
+                parts: vec![Part::Text("ignored".into())],
+            },
+            Message {
+                role: Role::Tool,
+                parts: vec![Part::ToolResult(ToolResult::Success {
+                    tool_call_id: "toolu_1".into(),
+                    output: json!("ok"),
+                })],
+            },
+        ];
+        let body = request_json(
+            &backend,
+            ModelRequest {
+                messages: &messages,
+                ..empty_request()
+            },
+        );
+
+        assert_eq!(
+            body["messages"],
+            json!([{
+                "role": "user",
+                "content": [{ "type": "tool_result", "tool_use_id": "toolu_1", "content": "\"ok\"" }],
+            }])
+        );
+        assert_eq!(AnthropicBackend::role_to_api(Role::System), "user");
+    }
+
+    #[test]
+    fn request_system_overrides_builder_default() {
+        let backend = AnthropicBackend::builder(AnthropicAuth::ApiKey("test".into()), "test-model")
+            .system("default")
+            .build();
+        let body = request_json(&backend, empty_request());
+        assert_eq!(body["system"], "default");
+
+        let request = ModelRequest {
+            system: Some("override"),
+            ..empty_request()
+        };
+        assert_eq!(request_json(&backend, request)["system"], "override");
+
+        let oauth =
+            AnthropicBackend::builder(AnthropicAuth::ClaudeCodeOauth("t".into()), "test-model")
+                .system("default")
+                .build();
+        let request = ModelRequest {
+            system: Some("override"),
+            ..empty_request()
+        };
+        let body = request_json(&oauth, request);
+        assert_eq!(body["system"][0]["text"], OAUTH_SYSTEM_PREFIX);
+        assert_eq!(body["system"][1]["text"], "override");
+    }
+
+    #[test]
+    fn oauth_always_prepends_the_system_prefix_by_default() {
+        assert_eq!(
+            AnthropicAuth::ClaudeCodeOauth("t".into()).system_prefix(),
+            Some(OAUTH_SYSTEM_PREFIX)
+        );
+        assert_eq!(AnthropicAuth::ApiKey("k".into()).system_prefix(), None);
+
+        // Even without a system prompt of its own.
+        let oauth =
+            AnthropicBackend::builder(AnthropicAuth::ClaudeCodeOauth("t".into()), "test-model")
+                .build();
+        let body = request_json(&oauth, empty_request());
+        assert_eq!(body["system"].as_array().unwrap().len(), 1);
+        assert_eq!(body["system"][0]["text"], OAUTH_SYSTEM_PREFIX);
+
+        let opted_out =
+            AnthropicBackend::builder(AnthropicAuth::ClaudeCodeOauth("t".into()), "test-model")
+                .oauth_system_prefix(false)
+                .build();
+        assert!(
+            request_json(&opted_out, empty_request())
+                .get("system")
+                .is_none()
+        );
+        let request = ModelRequest {
+            system: Some("mine"),
+            ..empty_request()
+        };
+        let body = request_json(&opted_out, request);
+        assert_eq!(body["system"][0]["text"], "mine");
+        assert_eq!(body["system"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn count_tokens_request_omits_generation_settings() {
+        let backend = test_backend();
+        let messages = [Message {
+            role: Role::User,
+            parts: vec![Part::Text("hi".into())],
+        }];
+        let request = ModelRequest {
+            messages: &messages,
+            idempotency_key: Some("key-1"),
+            max_tokens: None,
+            ..empty_request()
+        };
+        let body = ApiCountTokensRequest::from(backend.build_request(&request));
+
+        let http = backend.count_tokens_request(&body).build().unwrap();
+        assert_eq!(http.url().as_str(), COUNT_TOKENS_URL);
+        assert_eq!(http.headers()["anthropic-version"], "2023-06-01");
+        assert!(http.headers().get("idempotency-key").is_none());
+
+        let body: Value = serde_json::from_slice(http.body().unwrap().as_bytes().unwrap()).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "model": "test-model",
+                "messages": [{ "role": "user", "content": "hi" }],
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn cached_token_count_skips_the_request() {
+        let backend = test_backend();
+        let body = ApiCountTokensRequest::from(backend.build_request(&empty_request()));
+        backend
+            .token_counts
+            .lock()
+            .unwrap()
+            .insert(token_count_key(&body), 42);
+
+        assert_eq!(backend.estimate_tokens(&empty_request()).await.unwrap(), 42);
+    }
+
+    #[test]
+    fn extra_headers_are_sent_without_replacing_reserved_ones() {
+        let headers = HashMap::from([
+            ("Helicone-Auth".to_string(), "Bearer gateway".to_string()),
+            ("X-API-Key".to_string(), "other".to_string()),
+            ("content-type".to_string(), "text/plain".to_string()),
+        ]);
+        let backend = AnthropicBackend::builder(AnthropicAuth::ApiKey("test".into()), "test-model")
+            .extra_headers(headers)
+            .build();
+
+        let http = backend
+            .http_request(&empty_request(), false)
+            .build()
+            .unwrap();
+        assert_eq!(http.headers()["helicone-auth"], "Bearer gateway");
+        let api_keys: Vec<_> = http.headers().get_all("x-api-key").iter().collect();
+        assert_eq!(api_keys, ["test"]);
+        assert_eq!(http.headers()["content-type"], "application/json");
+
+        assert!(!format!("{backend:?}").contains("gateway"));
+
+        let headers = HashMap::from([
+            ("User-Agent".to_string(), "other/1.0".to_string()),
+            ("x-app".to_string(), "web".to_string()),
+        ]);
+        let oauth =
+            AnthropicBackend::builder(AnthropicAuth::ClaudeCodeOauth("token".into()), "test-model")
+                .extra_headers(headers)
+                .build();
+        let http = oauth.http_request(&empty_request(), false).build().unwrap();
+        let apps: Vec<_> = http.headers().get_all("x-app").iter().collect();
+        assert_eq!(apps, ["cli"]);
+        assert!(
+            http.headers()["user-agent"]
+                .to_str()
+                .unwrap()
+                .starts_with("claude-cli/")
+        );
+    }
+
+    #[test]
+    fn user_id_is_sent_as_metadata() {
+        let plain =
+            AnthropicBackend::builder(AnthropicAuth::ApiKey("test".into()), "test-model").build();
+        assert!(
+            request_json(&plain, empty_request())
+                .get("metadata")
+                .is_none()
+        );
+
+        let backend = AnthropicBackend::builder(AnthropicAuth::ApiKey("test".into()), "test-model")
+            .user_id("user-7f3a")
+            .build();
+        let body = request_json(&backend, empty_request());
+        assert_eq!(body["metadata"], json!({ "user_id": "user-7f3a" }));
+
+        let count = serde_json::to_value(ApiCountTokensRequest::from(
+            backend.build_request(&empty_request()),
+        ))
+        .unwrap();
+        assert!(count.get("metadata").is_none());
+    }
+
+    #[test]
+    fn thinking_budget_is_sent_and_raises_max_tokens() {
+        let plain = test_backend();
+        assert!(
+            request_json(&plain, empty_request())
+                .get("thinking")
+                .is_none()
+        );
+
+        let backend = AnthropicBackend::builder(AnthropicAuth::ApiKey("test".into()), "test-model")
+            .max_tokens(4096)
+            .thinking_budget(8000)
+            .build();
+        let body = request_json(&backend, empty_request());
+        assert_eq!(
+            body["thinking"],
+            json!({ "type": "enabled", "budget_tokens": 8000 })
+        );
+        assert_eq!(body["max_tokens"], 12096);
+
+        let request = ModelRequest {
+            max_tokens: Some(16000),
+            ..empty_request()
+        };
+        assert_eq!(request_json(&backend, request)["max_tokens"], 16000);
+
+        let small = AnthropicBackend::builder(AnthropicAuth::ApiKey("test".into()), "test-model")
+            .thinking_budget(10)
+            .build();
+        let body = request_json(&small, empty_request());
+        assert_eq!(body["thinking"]["budget_tokens"], MIN_THINKING_BUDGET);
+    }
+
+    #[test]
+    fn thinking_blocks_are_sent_back() {
+        let backend = test_backend();
+        let messages = [Message {
+            role: Role::Assistant,
+            parts: vec![
+                Part::Thinking(Thinking::Text {
+                    text: "Read it first.".into(),
+                    signature: "sig==".into(),
+                }),
+                Part::Thinking(Thinking::Redacted {
+                    data: "opaque".into(),
+                }),
+                Part::ToolCall(ToolCall {
+                    id: "toolu_1".into(),
+                    name: "read_file".into(),
+                    input: json!({}),
+                }),
+            ],
+        }];
+        let body = request_json(
+            &backend,
+            ModelRequest {
+                messages: &messages,
+                ..empty_request()
+            },
+        );
+
+        assert_eq!(
+            body["messages"][0]["content"],
+            json!([
+                { "type": "thinking", "thinking": "Read it first.", "signature": "sig==" },
+                { "type": "redacted_thinking", "data": "opaque" },
+                { "type": "tool_use", "id": "toolu_1", "name": "read_file", "input": {} },
+            ])
+        );
+
+        let response: ApiResponse = serde_json::from_value(json!({
+            "model": "test-model",
+            "content": [
+                { "type": "thinking", "thinking": "Hmm.", "signature": "s" },
+                { "type": "text", "text": "Hi." },
+            ],
+            "usage": { "input_tokens": 1, "output_tokens": 1 },
+        }))
+        .unwrap();
+        let message = AnthropicBackend::response_to_message(response.content);
+        assert!(matches!(
+            &message.parts[0],
+            Part::Thinking(Thinking::Text { text, signature }) if text == "Hmm." && signature == "s"
+        ));
+        assert_eq!(message.text(), "Hi.");
+    }
+
+    #[test]
+    fn health_check_counts_tokens_for_the_configured_model() {
+        let body = serde_json::to_value(test_backend().health_check_body()).unwrap();
+        assert_eq!(body["model"], "test-model");
+        assert_eq!(
+            body["messages"],
+            json!([{ "role": "user", "content": "ping" }])
+        );
+        assert!(body.get("max_tokens").is_none());
+    }
+
+    #[test]
+    fn extra_body_adds_fields_except_reserved_ones() {
+        let backend = AnthropicBackend::builder(AnthropicAuth::ApiKey("test".into()), "test-model")
+            .extra_body(json!({
+                "service_tier": "auto",
+                "model": "other",
+                "stream": true,
+                "thinking": { "type": "enabled", "budget_tokens": 50_000 },
+            }))
+            .build();
+
+        let http = backend
+            .http_request(&empty_request(), false)
+            .build()
+            .unwrap();
+        let body: Value = serde_json::from_slice(http.body().unwrap().as_bytes().unwrap()).unwrap();
+        assert_eq!(body, backend.request_json(&empty_request()));
+        assert_eq!(body["service_tier"], "auto");
+        assert_eq!(body["model"], "test-model");
+        // Reserved even though a non-streaming body leaves it out.
+        assert!(body.get("stream").is_none());
+        assert!(body.get("thinking").is_none());
+
+        let thinking =
+            AnthropicBackend::builder(AnthropicAuth::ApiKey("test".into()), "test-model")
+                .thinking_budget(2048)
+                .extra_body(json!({ "thinking": { "type": "disabled" } }))
+                .build();
+        let body = thinking.request_json(&empty_request());
+        assert_eq!(
+            body["thinking"],
+            json!({ "type": "enabled", "budget_tokens": 2048 })
+        );
+
+        let ignored = AnthropicBackend::builder(AnthropicAuth::ApiKey("test".into()), "test-model")
+            .extra_body(json!(["not", "an", "object"]))
+            .build();
+        assert!(ignored.extra_body.is_empty());
+    }
+
+    #[tokio::test]
+    async fn max_concurrency_queues_extra_calls() {
+        let backend = AnthropicBackend::builder(AnthropicAuth::ApiKey("test".into()), "test-model")
+            .max_concurrency(1)
+            .build();
+        let first = backend.acquire().await;
+        assert!(first.is_some());
+        assert_eq!(backend.queue_depth(), 0);
+
+        let second = backend.acquire();
+        tokio::pin!(second);
+        let waited = tokio::time::timeout(std::time::Duration::from_millis(10), &mut second).await;
+        assert!(waited.is_err());
+        assert_eq!(backend.queue_depth(), 1);
+
+        drop(first);
+        assert!(second.await.is_some());
+        assert_eq!(backend.queue_depth(), 0);
+    }
+
+    #[tokio::test]
+    async fn unlimited_backend_never_queues() {
+        let backend = test_backend();
+        assert!(backend.acquire().await.is_none());
+        assert_eq!(backend.queue_depth(), 0);
+    }
+
+    #[test]
+    fn model_info_matches_by_prefix() {
+        let info = lookup_model_info("claude-sonnet-4-20250514");
+        assert_eq!(info.context_window, 200_000);
+        assert_eq!(info.max_output_tokens, 64_000);
+    }
+
+    #[test]
+    fn model_info_unknown_model_uses_fallback() {
+        assert_eq!(lookup_model_info("some-future-model"), FALLBACK_MODEL_INFO);
+    }
 }
@@ -0,0 +1,451 @@
+//! Server-sent event parsing for streamed Messages API responses.
+
+use crate::model::{Message, ModelError, ModelResponse, Part, Role, StopReason, Thinking, Usage};
+use crate::tools::ToolCall;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// One event of a streamed response, decoded from an SSE `data:` payload.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(super) enum StreamEvent {
+    MessageStart {
+        message: StreamMessage,
+    },
+    ContentBlockStart {
+        index: usize,
+        content_block: StreamBlock,
+    },
+    ContentBlockDelta {
+        index: usize,
+        delta: StreamDelta,
+    },
+    ContentBlockStop {
+        index: usize,
+    },
+    MessageDelta {
+        #[serde(default)]
+        delta: StreamMessageDelta,
+        usage: StreamUsage,
+    },
+    MessageStop,
+    Ping,
+    Error {
+        error: super::ApiErrorDetail,
+    },
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct StreamMessage {
+    model: String,
+    usage: super::ApiUsage,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(super) enum StreamBlock {
+    Text {
+        text: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+    },
+    Thinking {
+        #[serde(default)]
+        thinking: String,
+    },
+    RedactedThinking {
+        data: String,
+    },
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(super) enum StreamDelta {
+    TextDelta {
+        text: String,
+    },
+    InputJsonDelta {
+        partial_json: String,
+    },
+    ThinkingDelta {
+        thinking: String,
+    },
+    SignatureDelta {
+        signature: String,
+    },
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(super) struct StreamMessageDelta {
+    stop_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct StreamUsage {
+    output_tokens: u32,
+}
+
+/// Splits a byte stream into SSE events, yielding each event's data.
+///
+/// Only `data:` fields matter for this API; `event:` repeats the `type`
+/// already inside the data, and comments are keep-alives.
+#[derive(Debug, Default)]
+pub(super) struct SseDecoder {
+    buffer: Vec<u8>,
+    data: String,
+}
+
+impl SseDecoder {
+    /// Feed raw bytes, returning the data of every event they complete.
+    pub(super) fn feed(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(bytes);
+
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            // Whole lines never split a UTF-8 sequence.
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            if line.is_empty() {
+                if !self.data.is_empty() {
+                    events.push(std::mem::take(&mut self.data));
+                }
+            } else if let Some(value) = line.strip_prefix("data:") {
+                if !self.data.is_empty() {
+                    self.data.push('\n');
+                }
+                self.data.push_str(value.strip_prefix(' ').unwrap_or(value));
+            }
+        }
+        events
+    }
+}
+
+/// A content block that has started but not yet stopped.
+#[derive(Debug)]
+enum OpenBlock {
+    Text(String),
+    ToolUse {
+        id: String,
+        name: String,
+        json: String,
+    },
+    Thinking {
+        text: String,
+        signature: String,
+    },
+    RedactedThinking(String),
+    /// A block type we don't use.
+    Ignored,
+}
+
+/// Assembles streamed events into a complete response.
+///
+/// Tool arguments arrive as fragments of JSON text, so a tool call only
+/// becomes a [`Part::ToolCall`] once its block stops and the joined
+/// fragments can be parsed.
+#[derive(Debug, Default)]
+pub(super) struct SseAccumulator {
+    model: String,
+    usage: Usage,
+    open: HashMap<usize, OpenBlock>,
+    parts: BTreeMap<usize, Part>,
+    stop_reason: Option<String>,
+    stopped: bool,
+}
+
+impl SseAccumulator {
+    /// Apply one event, returning any newly streamed text.
+    pub(super) fn push(&mut self, event: StreamEvent) -> Result<Option<String>, ModelError> {
+        match event {
+            StreamEvent::MessageStart { message } => {
+                self.model = message.model;
+                self.usage = Usage {
+                    input_tokens: message.usage.input_tokens,
+                    output_tokens: message.usage.output_tokens,
+                };
+            }
+            StreamEvent::ContentBlockStart {
+                index,
+                content_block,
+            } => {
+                let block = match content_block {
+                    StreamBlock::Text { text } => OpenBlock::Text(text),
+                    StreamBlock::ToolUse { id, name } => OpenBlock::ToolUse {
+                        id,
+                        name,
+                        json: String::new(),
+                    },
+                    StreamBlock::Thinking { thinking } => OpenBlock::Thinking {
+                        text: thinking,
+                        signature: String::new(),
+                    },
+                    StreamBlock::RedactedThinking { data } => OpenBlock::RedactedThinking(data),
+                    StreamBlock::Unknown => OpenBlock::Ignored,
+                };
+                self.open.insert(index, block);
+            }
+            StreamEvent::ContentBlockDelta { index, delta } => {
+                let block = self.open.get_mut(&index).ok_or_else(|| {
+                    ModelError::InvalidResponse(format!("delta for unknown block {index}"))
+                })?;
+                match (block, delta) {
+                    (OpenBlock::Text(text), StreamDelta::TextDelta { text: delta }) => {
+                        text.push_str(&delta);
+                        return Ok(Some(delta));
+                    }
+                    (
+                        OpenBlock::ToolUse { json, .. },
+                        StreamDelta::InputJsonDelta { partial_json },
+                    ) => {
+                        json.push_str(&partial_json);
+                    }
+                    (OpenBlock::Thinking { text, .. }, StreamDelta::ThinkingDelta { thinking }) => {
+                        text.push_str(&thinking);
+                    }
+                    (
+                        OpenBlock::Thinking { signature, .. },
+                        StreamDelta::SignatureDelta { signature: delta },
+                    ) => {
+                        signature.push_str(&delta);
+                    }
+                    _ => {}
+                }
+            }
+            StreamEvent::ContentBlockStop { index } => {
+                let block = self.open.remove(&index).ok_or_else(|| {
+                    ModelError::InvalidResponse(format!("stop for unknown block {index}"))
+                })?;
+                if let Some(part) = close_block(block)? {
+                    self.parts.insert(index, part);
+                }
+            }
+            StreamEvent::MessageDelta { delta, usage } => {
+                // The count is cumulative, not an increment.
+                self.usage.output_tokens = usage.output_tokens;
+                if delta.stop_reason.is_some() {
+                    self.stop_reason = delta.stop_reason;
+                }
+            }
+            StreamEvent::MessageStop => self.stopped = true,
+            StreamEvent::Error { error } => return Err(error.into_model_error()),
+            StreamEvent::Ping | StreamEvent::Unknown => {}
+        }
+        Ok(None)
+    }
+
+    /// Build the response once the stream has ended.
+    pub(super) fn finish(self) -> Result<ModelResponse, ModelError> {
+        if !self.stopped {
+            return Err(ModelError::InvalidResponse(
+                "stream ended before message_stop".into(),
+            ));
+        }
+
+        Ok(ModelResponse {
+            message: Message {
+                role: Role::Assistant,
+                parts: self.parts.into_values().collect(),
+            },
+            usage: self.usage,
+            model: self.model,
+            request_id: None,
+            stop_reason: self.stop_reason.as_deref().map(StopReason::from_anthropic),
+        })
+    }
+}
+
+fn close_block(block: OpenBlock) -> Result<Option<Part>, ModelError> {
+    match block {
+        OpenBlock::Text(text) => Ok(Some(Part::Text(text))),
+        OpenBlock::ToolUse { id, name, json } => {
+            // A tool without parameters may stream no fragments at all.
+            let input = if json.trim().is_empty() {
+                Value::Object(Default::default())
+            } else {
+                serde_json::from_str(&json).map_err(|e| {
+                    ModelError::InvalidResponse(format!("tool input for {name}: {e}"))
+                })?
+            };
+            Ok(Some(Part::ToolCall(ToolCall { id, name, input })))
+        }
+        OpenBlock::Thinking { text, signature } => {
+            Ok(Some(Part::Thinking(Thinking::Text { text, signature })))
+        }
+        OpenBlock::RedactedThinking(data) => Ok(Some(Part::Thinking(Thinking::Redacted { data }))),
+        OpenBlock::Ignored => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ApiErrorKind;
+    use serde_json::json;
+
+    fn accumulate(stream: &str) -> (Result<ModelResponse, ModelError>, String) {
+        let mut decoder = SseDecoder::default();
+        let mut accumulator = SseAccumulator::default();
+        let mut streamed = String::new();
+
+        for data in decoder.feed(stream.as_bytes()) {
+            let event: StreamEvent = serde_json::from_str(&data).unwrap();
+            match accumulator.push(event) {
+                Ok(Some(text)) => streamed.push_str(&text),
+                Ok(None) => {}
+                Err(e) => return (Err(e), streamed),
+            }
+        }
+        (accumulator.finish(), streamed)
+    }
+
+    fn sse(events: &[Value]) -> String {
+        events
+            .iter()
+            .map(|event| {
+                format!(
+                    "event: {}\ndata: {event}\n\n",
+                    event["type"].as_str().unwrap()
+                )
+            })
+            .collect()
+    }
+
+    fn message_start() -> Value {
+        json!({
+            "type": "message_start",
+            "message": { "model": "claude-test", "usage": { "input_tokens": 10, "output_tokens": 1 } },
+        })
+    }
+
+    #[test]
+    fn decoder_handles_split_lines_and_crlf() {
+        let mut decoder = SseDecoder::default();
+        assert!(decoder.feed(b"event: ping\r\nda").is_empty());
+        assert!(decoder.feed(b"ta: {\"type\":").is_empty());
+        assert_eq!(
+            decoder.feed(b" \"ping\"}\r\n\r\n: keep-alive\n\n"),
+            vec!["{\"type\": \"ping\"}"]
+        );
+    }
+
+    #[test]
+    fn assembles_text_and_partial_tool_json() {
+        let stream = sse(&[
+            message_start(),
+            json!({ "type": "content_block_start", "index": 0, "content_block": { "type": "text", "text": "" } }),
+            json!({ "type": "ping" }),
+            json!({ "type": "content_block_delta", "index": 0, "delta": { "type": "text_delta", "text": "Let me " } }),
+            json!({ "type": "content_block_delta", "index": 0, "delta": { "type": "text_delta", "text": "check." } }),
+            json!({ "type": "content_block_stop", "index": 0 }),
+            json!({ "type": "content_block_start", "index": 1, "content_block": { "type": "tool_use", "id": "toolu_1", "name": "read_file", "input": {} } }),
+            json!({ "type": "content_block_delta", "index": 1, "delta": { "type": "input_json_delta", "partial_json": "{\"pa" } }),
+            json!({ "type": "content_block_delta", "index": 1, "delta": { "type": "input_json_delta", "partial_json": "th\": \"a.txt\"}" } }),
+            json!({ "type": "content_block_stop", "index": 1 }),
+            json!({ "type": "message_delta", "delta": { "stop_reason": "tool_use" }, "usage": { "output_tokens": 42 } }),
+            json!({ "type": "message_stop" }),
+        ]);
+
+        let (response, streamed) = accumulate(&stream);
+        let response = response.unwrap();
+
+        assert_eq!(streamed, "Let me check.");
+        assert_eq!(response.model, "claude-test");
+        assert_eq!(response.usage.input_tokens, 10);
+        assert_eq!(response.usage.output_tokens, 42);
+        assert_eq!(response.stop_reason, Some(StopReason::ToolUse));
+        assert_eq!(response.message.text(), "Let me check.");
+
+        let calls = response.message.tool_calls();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].name, "read_file");
+        assert_eq!(calls[0].input, json!({ "path": "a.txt" }));
+    }
+
+    #[test]
+    fn keeps_thinking_blocks_with_their_signature() {
+        let stream = sse(&[
+            message_start(),
+            json!({ "type": "content_block_start", "index": 0, "content_block": { "type": "thinking", "thinking": "" } }),
+            json!({ "type": "content_block_delta", "index": 0, "delta": { "type": "thinking_delta", "thinking": "The user " } }),
+            json!({ "type": "content_block_delta", "index": 0, "delta": { "type": "thinking_delta", "thinking": "wants a file." } }),
+            json!({ "type": "content_block_delta", "index": 0, "delta": { "type": "signature_delta", "signature": "sig==" } }),
+            json!({ "type": "content_block_stop", "index": 0 }),
+            json!({ "type": "content_block_start", "index": 1, "content_block": { "type": "redacted_thinking", "data": "opaque" } }),
+            json!({ "type": "content_block_stop", "index": 1 }),
+            json!({ "type": "content_block_start", "index": 2, "content_block": { "type": "text", "text": "" } }),
+            json!({ "type": "content_block_delta", "index": 2, "delta": { "type": "text_delta", "text": "Done." } }),
+            json!({ "type": "content_block_stop", "index": 2 }),
+            json!({ "type": "message_stop" }),
+        ]);
+
+        let (response, streamed) = accumulate(&stream);
+        let response = response.unwrap();
+
+        assert_eq!(streamed, "Done.");
+        let parts = &response.message.parts;
+        assert_eq!(parts.len(), 3);
+        assert!(matches!(
+            &parts[0],
+            Part::Thinking(Thinking::Text { text, signature })
+                if text == "The user wants a file." && signature == "sig=="
+        ));
+        assert!(matches!(
+            &parts[1],
+            Part::Thinking(Thinking::Redacted { data }) if data == "opaque"
+        ));
+        assert_eq!(response.message.text(), "Done.");
+    }
+
+    #[test]
+    fn tool_without_fragments_gets_empty_object() {
+        let stream = sse(&[
+            message_start(),
+            json!({ "type": "content_block_start", "index": 0, "content_block": { "type": "tool_use", "id": "toolu_1", "name": "list", "input": {} } }),
+            json!({ "type": "content_block_stop", "index": 0 }),
+            json!({ "type": "message_stop" }),
+        ]);
+
+        let response = accumulate(&stream).0.unwrap();
+        assert_eq!(response.message.tool_calls()[0].input, json!({}));
+    }
+
+    #[test]
+    fn error_event_fails_the_call() {
+        let stream = sse(&[
+            message_start(),
+            json!({ "type": "error", "error": { "type": "overloaded_error", "message": "Overloaded" } }),
+        ]);
+
+        let err = accumulate(&stream).0.unwrap_err();
+        assert!(
+            matches!(
+                &err,
+                ModelError::Api { kind: ApiErrorKind::Overloaded, message } if message == "Overloaded"
+            ),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn truncated_stream_is_invalid() {
+        let stream = sse(&[
+            message_start(),
+            json!({ "type": "content_block_start", "index": 0, "content_block": { "type": "tool_use", "id": "toolu_1", "name": "x", "input": {} } }),
+            json!({ "type": "content_block_delta", "index": 0, "delta": { "type": "input_json_delta", "partial_json": "{\"a\": " } }),
+        ]);
+
+        let err = accumulate(&stream).0.unwrap_err();
+        assert!(matches!(err, ModelError::InvalidResponse(_)), "{err:?}");
+    }
+}
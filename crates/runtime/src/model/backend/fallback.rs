@@ -0,0 +1,171 @@
+//! Backend that falls back to a secondary provider.
+
+use crate::model::{Backend, ModelError, ModelInfo, ModelRequest, ModelResponse};
+
+/// Tries `primary` first and `secondary` if the primary fails.
+///
+/// Only retryable errors (see [`ModelError::is_retryable`]) trigger the
+/// fallback; a bad request fails immediately. Nest wrappers to chain more
+/// than two providers.
+pub struct FallbackBackend<P, S> {
+    primary: P,
+    secondary: S,
+}
+
+impl<P: Backend, S: Backend> FallbackBackend<P, S> {
+    /// Create a fallback chain of two backends.
+    pub fn new(primary: P, secondary: S) -> Self {
+        Self { primary, secondary }
+    }
+}
+
+impl<P: Backend, S: Backend> Backend for FallbackBackend<P, S> {
+    async fn call(&self, request: ModelRequest<'_>) -> Result<ModelResponse, ModelError> {
+        match self.primary.call(request.clone()).await {
+            Err(e) if e.is_retryable() => self.secondary.call(request).await,
+            result => result,
+        }
+    }
+
+    /// Limits of the primary model; callers should size requests for it.
+    fn model_info(&self) -> ModelInfo {
+        self.primary.model_info()
+    }
+
+    fn name(&self) -> &str {
+        self.primary.name()
+    }
+
+    fn supports_tools(&self) -> bool {
+        self.primary.supports_tools() && self.secondary.supports_tools()
+    }
+
+    async fn estimate_tokens(&self, request: &ModelRequest<'_>) -> Result<u32, ModelError> {
+        match self.primary.estimate_tokens(request).await {
+            Err(e) if e.is_retryable() => self.secondary.estimate_tokens(request).await,
+            result => result,
+        }
+    }
+
+    /// Both backends must pass, so a broken fallback is found before it
+    /// is needed.
+    async fn health_check(&self) -> Result<(), ModelError> {
+        self.primary.health_check().await?;
+        self.secondary.health_check().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Message, Role, ToolChoice, Usage};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Backend that fails with a fixed HTTP status, or succeeds if `None`.
+    struct StubBackend {
+        status: Option<u16>,
+        calls: AtomicU32,
+    }
+
+    impl StubBackend {
+        fn new(status: Option<u16>) -> Self {
+            Self {
+                status,
+                calls: AtomicU32::new(0),
+            }
+        }
+    }
+
+    impl Backend for StubBackend {
+        async fn call(&self, _request: ModelRequest<'_>) -> Result<ModelResponse, ModelError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            match self.status {
+                Some(status) => Err(ModelError::Http {
+                    status,
+                    body: String::new(),
+                }),
+                None => Ok(ModelResponse {
+                    message: Message {
+                        role: Role::Assistant,
+                        parts: Vec::new(),
+                    },
+                    usage: Usage::default(),
+                    model: "stub".into(),
+                    request_id: None,
+                    stop_reason: None,
+                }),
+            }
+        }
+
+        fn model_info(&self) -> ModelInfo {
+            ModelInfo {
+                context_window: 1_000,
+                max_output_tokens: 100,
+                supports_tools: true,
+                supports_vision: false,
+            }
+        }
+
+        fn name(&self) -> &str {
+            "stub"
+        }
+    }
+
+    fn request() -> ModelRequest<'static> {
+        ModelRequest {
+            messages: &[],
+            tools: &[],
+            tool_choice: ToolChoice::Auto,
+            disable_parallel_tool_use: false,
+            system: None,
+            idempotency_key: None,
+            max_tokens: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn call_falls_back_on_retryable_error() {
+        let backend = FallbackBackend::new(StubBackend::new(Some(529)), StubBackend::new(None));
+
+        assert!(backend.call(request()).await.is_ok());
+        assert_eq!(backend.secondary.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn call_does_not_fall_back_on_client_error() {
+        let backend = FallbackBackend::new(StubBackend::new(Some(400)), StubBackend::new(None));
+
+        let err = backend.call(request()).await.unwrap_err();
+        assert!(matches!(err, ModelError::Http { status: 400, .. }));
+        assert_eq!(backend.secondary.calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn call_returns_last_error_when_all_fail() {
+        let backend =
+            FallbackBackend::new(StubBackend::new(Some(503)), StubBackend::new(Some(500)));
+
+        let err = backend.call(request()).await.unwrap_err();
+        assert!(matches!(err, ModelError::Http { status: 500, .. }));
+    }
+
+    #[tokio::test]
+    async fn health_check_needs_both_backends() {
+        let healthy = FallbackBackend::new(StubBackend::new(None), StubBackend::new(None));
+        healthy.health_check().await.unwrap();
+        assert_eq!(healthy.primary.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(healthy.secondary.calls.load(Ordering::SeqCst), 1);
+
+        let broken = FallbackBackend::new(StubBackend::new(None), StubBackend::new(Some(401)));
+        let err = broken.health_check().await.unwrap_err();
+        assert!(matches!(err, ModelError::Http { status: 401, .. }));
+    }
+
+    #[tokio::test]
+    async fn call_skips_secondary_when_primary_succeeds() {
+        let backend = FallbackBackend::new(StubBackend::new(None), StubBackend::new(None));
+
+        assert!(backend.call(request()).await.is_ok());
+        assert_eq!(backend.secondary.calls.load(Ordering::SeqCst), 0);
+    }
+}
@@ -0,0 +1,103 @@
+//! Backend wrapper that logs requests and responses.
+
+use crate::model::{Backend, ModelError, ModelInfo, ModelRequest, ModelResponse};
+use serde_json::json;
+use std::time::Instant;
+use tracing::{Instrument, Level, Span, field};
+
+/// Logs every call to the wrapped backend via `tracing`.
+///
+/// Each call runs in a `model_call` span carrying the backend name, the
+/// model that answered, token counts, and latency. The request and
+/// response bodies are logged at `debug` level. Credentials live in the
+/// backend, not the request, so nothing logged here contains them.
+pub struct LoggingBackend<B> {
+    inner: B,
+}
+
+impl<B: Backend> LoggingBackend<B> {
+    /// Wrap a backend with logging.
+    pub fn new(inner: B) -> Self {
+        Self { inner }
+    }
+
+    /// Unwrap the inner backend.
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+impl<B: Backend> Backend for LoggingBackend<B> {
+    async fn call(&self, request: ModelRequest<'_>) -> Result<ModelResponse, ModelError> {
+        let span = tracing::debug_span!(
+            "model_call",
+            backend = self.inner.name(),
+            messages = request.messages.len(),
+            tools = request.tools.len(),
+            idempotency_key = request.idempotency_key,
+            request_id = field::Empty,
+            model = field::Empty,
+            input_tokens = field::Empty,
+            output_tokens = field::Empty,
+            latency_ms = field::Empty,
+        );
+
+        async move {
+            if tracing::enabled!(Level::DEBUG) {
+                let body = json!({
+                    "messages": request.messages,
+                    "tools": request.tools,
+                    "tool_choice": request.tool_choice,
+                    "disable_parallel_tool_use": request.disable_parallel_tool_use,
+                });
+                tracing::debug!(request = %body, "model request");
+            }
+
+            let started = Instant::now();
+            let result = self.inner.call(request).await;
+
+            let span = Span::current();
+            span.record("latency_ms", started.elapsed().as_millis() as u64);
+
+            match &result {
+                Ok(response) => {
+                    span.record("model", response.model.as_str());
+                    span.record("input_tokens", response.usage.input_tokens);
+                    span.record("output_tokens", response.usage.output_tokens);
+                    if let Some(request_id) = &response.request_id {
+                        span.record("request_id", request_id.as_str());
+                    }
+                    if tracing::enabled!(Level::DEBUG) {
+                        let body = json!({ "message": response.message, "usage": response.usage });
+                        tracing::debug!(response = %body, "model response");
+                    }
+                }
+                Err(error) => tracing::debug!(%error, "model call failed"),
+            }
+
+            result
+        }
+        .instrument(span)
+        .await
+    }
+
+    fn model_info(&self) -> ModelInfo {
+        self.inner.model_info()
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn supports_tools(&self) -> bool {
+        self.inner.supports_tools()
+    }
+
+    async fn estimate_tokens(&self, request: &ModelRequest<'_>) -> Result<u32, ModelError> {
+        self.inner.estimate_tokens(request).await
+    }
+
+    async fn health_check(&self) -> Result<(), ModelError> {
+        self.inner.health_check().await
+    }
+}
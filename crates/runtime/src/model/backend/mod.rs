@@ -1,5 +1,11 @@
 //! LLM provider backends.
 
+#[cfg(feature = "anthropic")]
 mod anthropic;
+mod fallback;
+mod logging;
 
+#[cfg(feature = "anthropic")]
 pub use anthropic::{AnthropicAuth, AnthropicBackend, AnthropicBackendBuilder};
+pub use fallback::FallbackBackend;
+pub use logging::LoggingBackend;
@@ -1,5 +1,58 @@
+use std::fmt;
 use thiserror::Error;
 
+/// Category of an error reported by the provider.
+///
+/// Lets callers decide on retries and user-facing messages without
+/// parsing provider-specific text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ApiErrorKind {
+    /// Too many requests; slow down and retry.
+    RateLimit,
+    /// The provider is temporarily overloaded.
+    Overloaded,
+    /// The request was malformed or unsupported.
+    InvalidRequest,
+    /// The credentials are missing or invalid.
+    Authentication,
+    /// The credentials lack access to the resource.
+    Permission,
+    /// The requested resource (e.g. model) does not exist.
+    NotFound,
+    /// An unexpected error inside the provider.
+    Server,
+    /// A category not covered above.
+    Other,
+}
+
+impl ApiErrorKind {
+    /// Returns the canonical name of this error kind.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::RateLimit => "rate_limit",
+            Self::Overloaded => "overloaded",
+            Self::InvalidRequest => "invalid_request",
+            Self::Authentication => "authentication",
+            Self::Permission => "permission",
+            Self::NotFound => "not_found",
+            Self::Server => "server",
+            Self::Other => "other",
+        }
+    }
+
+    /// Whether a request failing this way may succeed if tried again.
+    pub fn is_retryable(self) -> bool {
+        matches!(self, Self::RateLimit | Self::Overloaded | Self::Server)
+    }
+}
+
+impl fmt::Display for ApiErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
 /// Errors from LLM provider calls.
 ///
 /// This enum is marked `#[non_exhaustive]` to allow adding new variants
@@ -11,11 +64,71 @@ pub enum ModelError {
     #[error("network: {0}")]
     Network(String),
 
-    /// The LLM provider returned an error response.
-    #[error("provider api: {0}")]
-    Api(String),
+    /// The LLM provider returned a structured error.
+    #[error("provider api: {kind}: {message}")]
+    Api { kind: ApiErrorKind, message: String },
+
+    /// The provider answered with an HTTP error status and a body that
+    /// could not be parsed as a structured error.
+    #[error("provider api: {status}: {body}")]
+    Http { status: u16, body: String },
 
     /// The provider response could not be parsed.
     #[error("invalid provider response: {0}")]
     InvalidResponse(String),
 }
+
+impl ModelError {
+    /// Whether retrying (or trying another provider) could succeed.
+    ///
+    /// Network failures, timeouts, rate limits, and server-side errors
+    /// (including 529 overloaded) are retryable; other 4xx errors mean the
+    /// request itself is bad and would fail again.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Network(_) => true,
+            Self::Http { status, .. } => matches!(status, 408 | 429 | 500..=599),
+            Self::Api { kind, .. } => kind.is_retryable(),
+            Self::InvalidResponse(_) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn http(status: u16) -> ModelError {
+        ModelError::Http {
+            status,
+            body: String::new(),
+        }
+    }
+
+    #[test]
+    fn is_retryable_server_and_rate_limit_errors() {
+        assert!(ModelError::Network("reset".into()).is_retryable());
+        assert!(http(429).is_retryable());
+        assert!(http(500).is_retryable());
+        assert!(http(529).is_retryable());
+    }
+
+    #[test]
+    fn is_retryable_follows_api_error_kind() {
+        let api = |kind| ModelError::Api {
+            kind,
+            message: String::new(),
+        };
+        assert!(api(ApiErrorKind::RateLimit).is_retryable());
+        assert!(api(ApiErrorKind::Overloaded).is_retryable());
+        assert!(!api(ApiErrorKind::InvalidRequest).is_retryable());
+        assert!(!api(ApiErrorKind::Authentication).is_retryable());
+    }
+
+    #[test]
+    fn is_retryable_client_errors_are_not() {
+        assert!(!http(400).is_retryable());
+        assert!(!http(401).is_retryable());
+        assert!(!ModelError::InvalidResponse("bad json".into()).is_retryable());
+    }
+}
@@ -4,6 +4,12 @@ pub mod backend;
 pub mod errors;
 pub mod types;
 
+#[cfg(feature = "anthropic")]
 pub use backend::{AnthropicAuth, AnthropicBackend, AnthropicBackendBuilder};
-pub use errors::ModelError;
-pub use types::{Backend, Message, ModelRequest, ModelResponse, Part, Role, Usage};
+pub use backend::{FallbackBackend, LoggingBackend};
+pub use errors::{ApiErrorKind, ModelError};
+pub(crate) use types::rough_token_count;
+pub use types::{
+    Backend, Message, ModelInfo, ModelRequest, ModelRequestBuilder, ModelResponse, Part, Role,
+    StopReason, Thinking, ToolChoice, Usage,
+};
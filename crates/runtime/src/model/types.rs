@@ -1,15 +1,21 @@
 use super::errors::ModelError;
 use crate::tools::{ToolCall, ToolResult, ToolSpec};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::fmt;
 use std::future::Future;
 
 /// The role of a message sender.
+///
+/// Providers may have fewer roles than this; backends map `Tool` (and
+/// `System`, where it isn't sent separately) onto their user role.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Role {
     System,
     User,
     Assistant,
+    /// Tool results sent back to the model.
+    Tool,
 }
 
 impl Role {
@@ -25,12 +31,14 @@ impl Role {
     /// assert_eq!(Role::User.name(), "user");
     /// assert_eq!(Role::Assistant.name(), "assistant");
     /// assert_eq!(Role::System.name(), "system");
+    /// assert_eq!(Role::Tool.name(), "tool");
     /// ```
     pub fn name(self) -> &'static str {
         match self {
             Self::System => "system",
             Self::User => "user",
             Self::Assistant => "assistant",
+            Self::Tool => "tool",
         }
     }
 }
@@ -47,6 +55,19 @@ pub enum Part {
     Text(String),
     ToolCall(ToolCall),
     ToolResult(ToolResult),
+    Thinking(Thinking),
+}
+
+/// Reasoning from a model with extended thinking enabled.
+///
+/// It is kept in the conversation because the provider requires it to be
+/// sent back, unchanged, with the tool results that follow it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Thinking {
+    /// Readable reasoning and the provider's signature over it.
+    Text { text: String, signature: String },
+    /// Reasoning the provider encrypted.
+    Redacted { data: String },
 }
 
 /// A message, consisting of a role and one or more parts.
@@ -88,11 +109,120 @@ pub struct Usage {
     pub output_tokens: u32,
 }
 
+/// How the model may use the tools offered in a request.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ToolChoice {
+    /// The model decides whether to call a tool.
+    #[default]
+    Auto,
+    /// The model must call at least one tool.
+    Any,
+    /// The model must call the named tool.
+    Tool { name: String },
+    /// The model must not call any tool.
+    None,
+}
+
 /// Everything needed for a model request.
 #[derive(Debug, Clone)]
 pub struct ModelRequest<'a> {
     pub messages: &'a [Message],
     pub tools: &'a [ToolSpec],
+    pub tool_choice: ToolChoice,
+    /// Ask the model for at most one tool call per response.
+    pub disable_parallel_tool_use: bool,
+    /// System prompt for this request, replacing the backend's default.
+    pub system: Option<&'a str>,
+    /// Identifies this logical request to the provider.
+    ///
+    /// Retries of the same request send the same key, so a provider that
+    /// deduplicates will not bill or generate twice.
+    pub idempotency_key: Option<&'a str>,
+    /// Cap on output tokens for this request, replacing the backend's
+    /// default.
+    pub max_tokens: Option<u32>,
+}
+
+impl ModelRequest<'_> {
+    /// Start a request whose collections may be owned or borrowed.
+    pub fn builder<'b>() -> ModelRequestBuilder<'b> {
+        ModelRequestBuilder::default()
+    }
+}
+
+/// Assembles a [`ModelRequest`] from owned or borrowed parts.
+///
+/// Messages and tools are held as [`Cow`]s, so a caller can borrow a
+/// session's history and still append to it; only the first change
+/// copies. [`Self::build`] lends the parts out as a request.
+#[derive(Debug, Clone, Default)]
+pub struct ModelRequestBuilder<'a> {
+    messages: Cow<'a, [Message]>,
+    tools: Cow<'a, [ToolSpec]>,
+    tool_choice: ToolChoice,
+    disable_parallel_tool_use: bool,
+    system: Option<Cow<'a, str>>,
+    idempotency_key: Option<Cow<'a, str>>,
+    max_tokens: Option<u32>,
+}
+
+impl<'a> ModelRequestBuilder<'a> {
+    /// Set the conversation, e.g. a `Vec` or a borrowed slice.
+    pub fn messages(mut self, messages: impl Into<Cow<'a, [Message]>>) -> Self {
+        self.messages = messages.into();
+        self
+    }
+
+    /// Append one message to the conversation.
+    pub fn message(mut self, message: Message) -> Self {
+        self.messages.to_mut().push(message);
+        self
+    }
+
+    /// Set the tools offered to the model.
+    pub fn tools(mut self, tools: impl Into<Cow<'a, [ToolSpec]>>) -> Self {
+        self.tools = tools.into();
+        self
+    }
+
+    pub fn tool_choice(mut self, tool_choice: ToolChoice) -> Self {
+        self.tool_choice = tool_choice;
+        self
+    }
+
+    pub fn disable_parallel_tool_use(mut self, disabled: bool) -> Self {
+        self.disable_parallel_tool_use = disabled;
+        self
+    }
+
+    pub fn system(mut self, system: impl Into<Cow<'a, str>>) -> Self {
+        self.system = Some(system.into());
+        self
+    }
+
+    pub fn idempotency_key(mut self, key: impl Into<Cow<'a, str>>) -> Self {
+        self.idempotency_key = Some(key.into());
+        self
+    }
+
+    pub fn max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Borrow the assembled parts as a request.
+    pub fn build(&self) -> ModelRequest<'_> {
+        ModelRequest {
+            messages: &self.messages,
+            tools: &self.tools,
+            tool_choice: self.tool_choice.clone(),
+            disable_parallel_tool_use: self.disable_parallel_tool_use,
+            system: self.system.as_deref(),
+            idempotency_key: self.idempotency_key.as_deref(),
+            max_tokens: self.max_tokens,
+        }
+    }
 }
 
 /// The response from a model.
@@ -100,6 +230,77 @@ pub struct ModelRequest<'a> {
 pub struct ModelResponse {
     pub message: Message,
     pub usage: Usage,
+    /// The model that produced the response, as reported by the provider.
+    pub model: String,
+    /// The provider's ID for the request, for matching against its logs.
+    pub request_id: Option<String>,
+    /// Why the model stopped, if the provider reported it.
+    pub stop_reason: Option<StopReason>,
+}
+
+/// Why a model stopped generating.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StopReason {
+    /// The model finished its answer.
+    EndTurn,
+    /// The output hit the token limit.
+    MaxTokens,
+    /// A configured stop sequence was generated.
+    StopSequence,
+    /// The model wants tool results before it goes on.
+    ToolUse,
+    /// The provider paused a long turn; sending the response back
+    /// unchanged lets the model continue.
+    PauseTurn,
+    /// The model declined to answer, e.g. for safety reasons.
+    Refusal,
+    /// A reason not listed above, as the provider named it.
+    Unknown(String),
+}
+
+impl StopReason {
+    /// Parse a stop reason from the Anthropic API.
+    pub fn from_anthropic(reason: &str) -> Self {
+        match reason {
+            "end_turn" => Self::EndTurn,
+            "max_tokens" => Self::MaxTokens,
+            "stop_sequence" => Self::StopSequence,
+            "tool_use" => Self::ToolUse,
+            "pause_turn" => Self::PauseTurn,
+            "refusal" => Self::Refusal,
+            other => Self::Unknown(other.to_string()),
+        }
+    }
+
+    /// Returns the snake_case name of this stop reason.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::EndTurn => "end_turn",
+            Self::MaxTokens => "max_tokens",
+            Self::StopSequence => "stop_sequence",
+            Self::ToolUse => "tool_use",
+            Self::PauseTurn => "pause_turn",
+            Self::Refusal => "refusal",
+            Self::Unknown(reason) => reason,
+        }
+    }
+}
+
+impl fmt::Display for StopReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Static capabilities and limits of a model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelInfo {
+    /// Maximum input + output tokens per request.
+    pub context_window: u32,
+    /// Maximum tokens the model may generate in one response.
+    pub max_output_tokens: u32,
+    pub supports_tools: bool,
+    pub supports_vision: bool,
 }
 
 /// Trait for LLM provider backends.
@@ -108,6 +309,78 @@ pub trait Backend: Send + Sync {
         &self,
         request: ModelRequest<'_>,
     ) -> impl Future<Output = Result<ModelResponse, ModelError>> + Send;
+
+    /// Capabilities and limits of the model this backend talks to.
+    fn model_info(&self) -> ModelInfo;
+
+    /// Short provider name for logging and routing (e.g., "anthropic").
+    fn name(&self) -> &str;
+
+    /// Whether this backend can make use of tool specs in a request.
+    fn supports_tools(&self) -> bool {
+        true
+    }
+
+    /// Roughly how many input tokens `request` would use.
+    ///
+    /// The default guesses one token per four characters of text, tool
+    /// arguments and tool schemas; backends with a counting endpoint
+    /// should override it.
+    fn estimate_tokens(
+        &self,
+        request: &ModelRequest<'_>,
+    ) -> impl Future<Output = Result<u32, ModelError>> + Send {
+        let estimate = rough_token_count(request);
+        async move { Ok(estimate) }
+    }
+
+    /// Check that the provider is reachable and accepts the credentials
+    /// and model, so a bad key fails before the first real turn.
+    ///
+    /// The default sends a one-token request; backends with a cheaper
+    /// check should override it.
+    fn health_check(&self) -> impl Future<Output = Result<(), ModelError>> + Send {
+        async move {
+            let request = ModelRequest::builder()
+                .message(Message {
+                    role: Role::User,
+                    parts: vec![Part::Text("ping".into())],
+                })
+                .max_tokens(1);
+            self.call(request.build()).await.map(drop)
+        }
+    }
+}
+
+/// Characters per token assumed by [`rough_token_count`].
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Estimate input tokens from the size of everything sent to the model.
+pub(crate) fn rough_token_count(request: &ModelRequest<'_>) -> u32 {
+    let json_len = |value: &serde_json::Value| value.to_string().len();
+
+    let messages: usize = request
+        .messages
+        .iter()
+        .flat_map(|message| &message.parts)
+        .map(|part| match part {
+            Part::Text(text) => text.len(),
+            Part::ToolCall(call) => call.name.len() + json_len(&call.input),
+            Part::ToolResult(ToolResult::Success { output, .. }) => json_len(output),
+            Part::ToolResult(ToolResult::Failure { error, .. }) => error.to_string().len(),
+            Part::Thinking(Thinking::Text { text, .. }) => text.len(),
+            Part::Thinking(Thinking::Redacted { data }) => data.len(),
+        })
+        .sum();
+    let tools: usize = request
+        .tools
+        .iter()
+        .map(|spec| spec.name.len() + spec.description.len() + json_len(&spec.schema))
+        .sum();
+
+    let system = request.system.map_or(0, str::len);
+
+    u32::try_from((messages + tools + system).div_ceil(CHARS_PER_TOKEN)).unwrap_or(u32::MAX)
 }
 
 #[cfg(test)]
@@ -115,6 +388,27 @@ mod tests {
     use super::*;
     use serde_json::Value;
 
+    #[test]
+    fn stop_reasons_parse_from_anthropic_names() {
+        for name in [
+            "end_turn",
+            "max_tokens",
+            "stop_sequence",
+            "tool_use",
+            "pause_turn",
+            "refusal",
+        ] {
+            let reason = StopReason::from_anthropic(name);
+            assert!(!matches!(reason, StopReason::Unknown(_)), "{name}");
+            assert_eq!(reason.to_string(), name);
+        }
+        assert_eq!(StopReason::from_anthropic("refusal"), StopReason::Refusal);
+        assert_eq!(
+            StopReason::from_anthropic("model_context_window_exceeded"),
+            StopReason::Unknown("model_context_window_exceeded".into())
+        );
+    }
+
     #[test]
     fn message_text_extraction() {
         let msg = Message {
@@ -161,12 +455,88 @@ mod tests {
         assert_eq!(Role::User.name(), "user");
         assert_eq!(Role::Assistant.name(), "assistant");
         assert_eq!(Role::System.name(), "system");
+        assert_eq!(Role::Tool.name(), "tool");
+    }
+
+    #[test]
+    fn rough_token_count_covers_text_tools_and_results() {
+        let messages = [
+            Message {
+                role: Role::User,
+                parts: vec![Part::Text("abcdefgh".into())],
+            },
+            Message {
+                role: Role::Assistant,
+                parts: vec![Part::ToolCall(ToolCall {
+                    id: "1".into(),
+                    name: "echo".into(),
+                    input: serde_json::json!({}),
+                })],
+            },
+            Message {
+                role: Role::User,
+                parts: vec![Part::ToolResult(ToolResult::Success {
+                    tool_call_id: "1".into(),
+                    output: serde_json::json!("ok"),
+                })],
+            },
+        ];
+        let tools = [ToolSpec {
+            name: "echo".into(),
+            description: "".into(),
+            schema: serde_json::json!({}),
+        }];
+        let request = ModelRequest {
+            messages: &messages,
+            tools: &tools,
+            tool_choice: ToolChoice::Auto,
+            disable_parallel_tool_use: false,
+            system: None,
+            idempotency_key: None,
+            max_tokens: None,
+        };
+
+        // 8 text + (4 + 2) call + 4 result + (4 + 2) spec = 24 chars.
+        assert_eq!(rough_token_count(&request), 6);
+
+        let request = ModelRequest {
+            tools: &[],
+            ..request
+        };
+        // 18 chars rounds up.
+        assert_eq!(rough_token_count(&request), 5);
+    }
+
+    #[test]
+    fn request_builder_mixes_borrowed_and_owned_parts() {
+        let history = vec![Message {
+            role: Role::User,
+            parts: vec![Part::Text("hi".into())],
+        }];
+        let builder = ModelRequest::builder()
+            .messages(&history)
+            .message(Message {
+                role: Role::User,
+                parts: vec![Part::Text("again".into())],
+            })
+            .tool_choice(ToolChoice::None)
+            .system("Be brief.")
+            .idempotency_key(String::from("key-1"));
+
+        let request = builder.build();
+        assert_eq!(request.messages.len(), 2);
+        assert_eq!(history.len(), 1);
+        assert!(request.tools.is_empty());
+        assert_eq!(request.tool_choice, ToolChoice::None);
+        assert_eq!(request.system, Some("Be brief."));
+        assert_eq!(request.idempotency_key, Some("key-1"));
+        assert!(!request.disable_parallel_tool_use);
     }
 
     #[test]
     fn role_display_uses_name() {
         // Verify Display produces the same output as name()
-        for role in [Role::User, Role::Assistant, Role::System] {
+        for role in [Role::User, Role::Assistant, Role::System, Role::Tool] {
             assert_eq!(role.to_string(), role.name());
         }
     }
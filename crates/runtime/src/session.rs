@@ -1,29 +1,158 @@
 //! Session management.
 
-use crate::model::{Backend, Message, ModelRequest, Part, Role, Usage};
-use crate::tools::{EmptyToolHost, ToolCall, ToolHost, ToolResult};
+use crate::model::{
+    Backend, Message, ModelRequest, ModelResponse, Part, Role, StopReason, ToolChoice, Usage,
+    rough_token_count,
+};
+use crate::sink::EventSink;
+use crate::summarize::SummarizingTrimmer;
+use crate::tool_cache::ToolCache;
+use crate::tool_timeouts::ToolTimeouts;
+use crate::tools::{EmptyToolHost, ToolCall, ToolError, ToolHost, ToolResult, ToolSpec};
 use crate::{Error, Result};
-use policy::{CapabilityRequest, Decision, Policy};
-use serde_json::json;
+use policy::{CapabilityKind, CapabilityRequest, Decision, Policy};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use storage::{Event, EventKind, EventStore, Role as StorageRole, SessionId};
+use tracing::{Instrument, Span, field};
+use uuid::Uuid;
 
 const MAX_TOOL_STEPS: usize = 8;
 
+/// Longest title [`Session::auto_title`] stores.
+const MAX_AUTO_TITLE_CHARS: usize = 60;
+
+/// Output cap for the model call that writes a title.
+const TITLE_MAX_TOKENS: u32 = 32;
+
+/// Longest transcript excerpt sent when asking for a title.
+const TITLE_TRANSCRIPT_CHARS: usize = 4_000;
+
+const TITLE_PROMPT: &str = "Write a short title (at most eight words) for the conversation the \
+    user sends. Reply with the title only, without quotes or trailing punctuation.";
+
+/// Default cap on a tool result as seen by the model.
+const DEFAULT_MAX_TOOL_RESULT_BYTES: usize = 32 * 1024;
+
+/// How a session without a title gets one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AutoTitle {
+    /// Only [`Session::set_title`] sets a title.
+    #[default]
+    Off,
+    /// Use the first line of the first user message.
+    FirstMessage,
+    /// After the first response, ask the model for a short title. This
+    /// costs one small extra call; if it fails, the first message is used.
+    Model,
+}
+
+/// A decision on a tool call, returned by the hook set with
+/// [`Session::on_tool_call`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ToolApproval {
+    /// Run the call as the model asked.
+    Allow,
+    /// Skip the call; the model receives a failure carrying this reason.
+    Deny(String),
+    /// Run the call with these arguments instead.
+    Modify(Value),
+}
+
+/// Hook consulted before each tool call is executed.
+type ToolApprover = Box<dyn Fn(&ToolCall) -> ToolApproval + Send + Sync>;
+
+/// How much of each model request a session records in its event log.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RequestLog {
+    /// Record nothing beyond the conversation itself.
+    #[default]
+    Off,
+    /// Record the system prompt, message count and tool names.
+    Summary,
+    /// Also record the full messages and tool specs sent, with tool
+    /// outputs as [`ToolHost::log_output`] returns them.
+    Full,
+}
+
+/// Final report returned when a session ends.
+#[derive(Debug, Clone, Copy)]
+pub struct SessionOutcome {
+    pub id: SessionId,
+    /// Tokens used across every model call, including summaries.
+    pub usage: Usage,
+    /// Turns that ended with an answer from the model.
+    pub turns: u32,
+    /// Time since the session was created or resumed.
+    pub duration: Duration,
+}
+
 /// A conversation session.
-pub struct Session<B: Backend> {
+///
+/// `T` is the tool host attached with [`Session::with_tool_host`]; by
+/// default there is none.
+///
+/// A session is `Send`, so it can move between tasks, but it runs one
+/// turn at a time: every turn takes `&mut self`. Token usage and turn
+/// counts belong to the session alone. Distinct sessions are independent
+/// and may run concurrently, including on clones of one [`EventStore`].
+pub struct Session<B: Backend, T = EmptyToolHost> {
     pub id: SessionId,
     store: EventStore,
     backend: B,
     policy: Policy,
     messages: Vec<Message>,
     usage: Usage,
+    capability_calls: HashMap<CapabilityKind, u32>,
+    dry_run: bool,
+    disable_parallel_tool_use: bool,
+    trimmer: Option<SummarizingTrimmer>,
+    tool_cache: Option<ToolCache>,
+    tool_timeouts: ToolTimeouts,
+    /// Tool outputs as the tool host logs them, by tool call ID, where
+    /// [`ToolHost::log_output`] masked something.
+    logged_outputs: HashMap<String, Value>,
+    /// Context size of the latest model call, as reported by the provider.
+    context_tokens: u32,
+    max_tool_result_bytes: usize,
+    checkpoint: bool,
+    system_prompt: Option<String>,
+    request_log: RequestLog,
+    started: Instant,
+    turns: u32,
+    title: Option<String>,
+    tags: Vec<String>,
+    auto_title: AutoTitle,
+    approver: Option<ToolApprover>,
+    /// Whether the backend's lack of tool support has been reported.
+    warned_no_tools: bool,
+    sinks: Vec<Box<dyn EventSink>>,
+    /// Shared so a turn can borrow it while the session is mutated.
+    tool_host: Arc<T>,
 }
 
 impl<B: Backend> Session<B> {
     /// Create a new session.
     pub fn new(store: EventStore, backend: B, policy: Policy) -> Result<Self> {
-        let id = SessionId::new();
-        store.append(&Event::new(id, EventKind::SessionStart))?;
+        Self::new_with_id(store, backend, policy, SessionId::new())
+    }
+
+    /// Create a session with a caller-chosen ID.
+    ///
+    /// `SessionStart` is only recorded if the store has no events for `id`
+    /// yet, so the same ID can be reused without duplicating it. Earlier
+    /// messages are not loaded into the conversation.
+    pub fn new_with_id(
+        store: EventStore,
+        backend: B,
+        policy: Policy,
+        id: SessionId,
+    ) -> Result<Self> {
+        if !store.session_exists(id)? {
+            store.append(&Event::new(id, EventKind::SessionStart))?;
+        }
 
         Ok(Self {
             id,
@@ -32,30 +161,317 @@ impl<B: Backend> Session<B> {
             policy,
             messages: Vec::new(),
             usage: Usage::default(),
+            capability_calls: HashMap::new(),
+            dry_run: false,
+            disable_parallel_tool_use: false,
+            trimmer: None,
+            tool_cache: None,
+            tool_timeouts: ToolTimeouts::default(),
+            logged_outputs: HashMap::new(),
+            context_tokens: 0,
+            max_tool_result_bytes: DEFAULT_MAX_TOOL_RESULT_BYTES,
+            checkpoint: false,
+            system_prompt: None,
+            request_log: RequestLog::Off,
+            started: Instant::now(),
+            turns: 0,
+            title: None,
+            tags: Vec::new(),
+            auto_title: AutoTitle::Off,
+            approver: None,
+            warned_no_tools: false,
+            sinks: Vec::new(),
+            tool_host: Arc::new(EmptyToolHost),
         })
     }
 
+    /// Reopen a session from its latest checkpoint.
+    ///
+    /// The conversation is restored as it was when the checkpoint was
+    /// written and checkpointing stays on. If the run stopped mid-turn,
+    /// [`Self::has_pending_turn`] is true and [`Self::resume_turn`] picks
+    /// the tool loop back up. Token usage starts again from zero.
+    pub fn resume(store: EventStore, backend: B, policy: Policy, id: SessionId) -> Result<Self> {
+        if !store.session_exists(id)? {
+            return Err(Error::SessionNotFound(id.to_string()));
+        }
+
+        let mut latest = None;
+        let mut meta = None;
+        store.for_each_event(id, |event| {
+            match event.kind {
+                EventKind::Checkpoint { messages, .. } => latest = Some(messages),
+                EventKind::SessionMeta { title, tags } => meta = Some((title, tags)),
+                _ => {}
+            }
+            Ok(())
+        })?;
+        let messages =
+            latest.ok_or_else(|| Error::InvalidState(format!("session {id} has no checkpoint")))?;
+        let messages = serde_json::from_value(messages)
+            .map_err(|e| Error::InvalidState(format!("unreadable checkpoint: {e}")))?;
+
+        let mut session = Self::new_with_id(store, backend, policy, id)?.checkpoint(true);
+        session.messages = messages;
+        if let Some((title, tags)) = meta {
+            session.title = title;
+            session.tags = tags;
+        }
+        Ok(session)
+    }
+}
+
+impl<B: Backend, T: ToolHost> Session<B, T> {
+    /// Attach a tool host, so [`Self::chat`], [`Self::regenerate_last`] and
+    /// [`Self::edit_last_user`] offer its tools without it being passed on
+    /// every call. The `_with_tools` variants still use the host given.
+    ///
+    /// End the session with [`Self::end_and_shutdown`] so the host is
+    /// shut down.
+    pub fn with_tool_host<H: ToolHost>(self, tool_host: H) -> Session<B, H> {
+        Session {
+            id: self.id,
+            store: self.store,
+            backend: self.backend,
+            policy: self.policy,
+            messages: self.messages,
+            usage: self.usage,
+            capability_calls: self.capability_calls,
+            dry_run: self.dry_run,
+            disable_parallel_tool_use: self.disable_parallel_tool_use,
+            trimmer: self.trimmer,
+            tool_cache: self.tool_cache,
+            tool_timeouts: self.tool_timeouts,
+            logged_outputs: self.logged_outputs,
+            context_tokens: self.context_tokens,
+            max_tool_result_bytes: self.max_tool_result_bytes,
+            checkpoint: self.checkpoint,
+            system_prompt: self.system_prompt,
+            request_log: self.request_log,
+            started: self.started,
+            turns: self.turns,
+            title: self.title,
+            tags: self.tags,
+            auto_title: self.auto_title,
+            approver: self.approver,
+            warned_no_tools: self.warned_no_tools,
+            sinks: self.sinks,
+            tool_host: Arc::new(tool_host),
+        }
+    }
+
+    /// The attached tool host.
+    pub fn tool_host(&self) -> &T {
+        &self.tool_host
+    }
+
+    /// Tools the attached host offers the model, e.g. for a UI listing the
+    /// agent's capabilities. Empty when no host is attached.
+    pub fn tool_specs(&self) -> &[ToolSpec] {
+        self.tool_host.specs()
+    }
+
+    /// Enable or disable dry-run mode.
+    ///
+    /// In dry-run mode tool calls are logged but never executed; the model
+    /// receives a synthetic `{"dry_run": true}` result instead.
+    pub fn dry_run(mut self, enabled: bool) -> Self {
+        self.dry_run = enabled;
+        self
+    }
+
+    /// Consult `approve` before executing each tool call.
+    ///
+    /// A denied call is not run; the model receives a failure with the
+    /// reason given. A modified call runs with the new arguments, and the
+    /// event log records those in place of the model's.
+    pub fn on_tool_call<F>(mut self, approve: F) -> Self
+    where
+        F: Fn(&ToolCall) -> ToolApproval + Send + Sync + 'static,
+    {
+        self.approver = Some(Box::new(approve));
+        self
+    }
+
+    /// Whether dry-run mode is enabled.
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Ask the model for one tool call at a time.
+    ///
+    /// Use this for tools that mutate shared state and must not be batched.
+    /// Calls are always executed in order, one after another.
+    pub fn disable_parallel_tool_use(mut self, disabled: bool) -> Self {
+        self.disable_parallel_tool_use = disabled;
+        self
+    }
+
+    /// Cap the size of a tool result added to the conversation (default 32 KiB).
+    ///
+    /// Larger outputs are cut short with a `...[truncated N of M bytes]`
+    /// marker; the event log still records them in full.
+    pub fn max_tool_result_bytes(mut self, bytes: usize) -> Self {
+        self.max_tool_result_bytes = bytes;
+        self
+    }
+
+    /// Send this system prompt with every model call, in place of the
+    /// backend's default.
+    pub fn system_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.system_prompt = Some(prompt.into());
+        self
+    }
+
+    /// Replace the system prompt for subsequent turns.
+    pub fn set_system_prompt(&mut self, prompt: Option<String>) {
+        self.system_prompt = prompt;
+    }
+
+    /// Choose how the session titles itself while it has no title.
+    pub fn auto_title(mut self, mode: AutoTitle) -> Self {
+        self.auto_title = mode;
+        self
+    }
+
+    /// The session's title, if one has been set.
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// The session's tags.
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// Set the title shown when listing sessions.
+    pub fn set_title(&mut self, title: impl Into<String>) -> Result<()> {
+        self.title = Some(title.into());
+        self.log_meta()
+    }
+
+    /// Replace the session's tags.
+    pub fn set_tags(&mut self, tags: Vec<String>) -> Result<()> {
+        self.tags = tags;
+        self.log_meta()
+    }
+
+    /// Record the current title and tags; the latest event wins.
+    fn log_meta(&self) -> Result<()> {
+        self.append(&Event::new(
+            self.id,
+            EventKind::SessionMeta {
+                title: self.title.clone(),
+                tags: self.tags.clone(),
+            },
+        ))?;
+        Ok(())
+    }
+
+    /// Record a `model_request` event before every model call.
+    ///
+    /// Off by default, since [`RequestLog::Full`] copies the whole
+    /// conversation into the log on each call.
+    pub fn log_requests(mut self, mode: RequestLog) -> Self {
+        self.request_log = mode;
+        self
+    }
+
+    /// Enable or disable checkpointing.
+    ///
+    /// When enabled, the full conversation is written to the event log
+    /// after every model reply and every round of tool results, so that
+    /// [`Self::resume`] can continue a long tool loop after a crash.
+    /// Tool outputs are stored as [`ToolHost::log_output`] returns them,
+    /// so a resumed conversation sees masked secrets as masked.
+    pub fn checkpoint(mut self, enabled: bool) -> Self {
+        self.checkpoint = enabled;
+        self
+    }
+
+    /// Also hand every event recorded from now on to `sink`, after it is
+    /// stored. Events from before, such as `session_start`, are not
+    /// replayed. Sinks run in the order they were added.
+    pub fn event_sink(mut self, sink: impl EventSink + 'static) -> Self {
+        self.sinks.push(Box::new(sink));
+        self
+    }
+
+    /// Answer repeated calls to the cache's tools from earlier results.
+    ///
+    /// Cache hits are logged like any other tool call and result.
+    pub fn cache_tool_results(mut self, cache: ToolCache) -> Self {
+        self.tool_cache = Some(cache);
+        self
+    }
+
+    /// Fail tool calls that run longer than their limit in `timeouts`.
+    ///
+    /// The model gets a timeout error as the call's result and can carry
+    /// on. Without this, tools run as long as they take. Timed-out calls
+    /// to MCP servers may keep running on the server; see [`ToolTimeouts`].
+    pub fn tool_timeouts(mut self, timeouts: ToolTimeouts) -> Self {
+        self.tool_timeouts = timeouts;
+        self
+    }
+
+    /// Summarize old turns when the context grows past the trimmer's threshold.
+    pub fn summarize_with(mut self, trimmer: SummarizingTrimmer) -> Self {
+        self.trimmer = Some(trimmer);
+        self
+    }
+
+    /// The in-memory conversation, oldest message first.
+    pub fn messages(&self) -> &[Message] {
+        &self.messages
+    }
+
+    /// Render the conversation as plain text, one line per part.
+    ///
+    /// Intended for display and snapshot tests, not for sending to a model.
+    pub fn transcript(&self) -> String {
+        render_transcript(&self.messages)
+    }
+
     /// Get cumulative token usage.
     pub fn usage(&self) -> Usage {
         self.usage
     }
 
-    /// Check if a capability is allowed.
+    /// Check if a capability is allowed, including any remaining call limit.
+    ///
+    /// This does not count as a use of the capability.
     pub fn check_capability(&self, request: &CapabilityRequest) -> Decision {
-        self.policy.check(request)
+        match self.policy.check(request) {
+            Decision::Allow => self
+                .policy
+                .check_limit(request.kind, self.capability_calls(request.kind)),
+            deny => deny,
+        }
     }
 
     /// Require a capability, error if denied.
-    pub fn require_capability(&self, request: &CapabilityRequest) -> Result<()> {
-        match self.policy.check(request) {
-            Decision::Allow => Ok(()),
+    ///
+    /// Each granted request counts towards the kind's call limit.
+    pub fn require_capability(&mut self, request: &CapabilityRequest) -> Result<()> {
+        match self.check_capability(request) {
+            Decision::Allow => {
+                *self.capability_calls.entry(request.kind).or_default() += 1;
+                Ok(())
+            }
             Decision::Deny { reason } => Err(Error::CapabilityDenied(reason)),
         }
     }
 
-    /// Chat without tools.
+    /// Number of granted calls for a capability kind in this session.
+    pub fn capability_calls(&self, kind: CapabilityKind) -> u32 {
+        self.capability_calls.get(&kind).copied().unwrap_or(0)
+    }
+
+    /// Chat using the attached tool host, if any.
     pub async fn chat(&mut self, user_input: &str) -> Result<(String, Usage)> {
-        self.chat_with_tools(user_input, &EmptyToolHost).await
+        let tool_host = Arc::clone(&self.tool_host);
+        self.chat_with_tools(user_input, &*tool_host).await
     }
 
     /// Chat with tool support.
@@ -64,30 +480,408 @@ impl<B: Backend> Session<B> {
         user_input: &str,
         tool_host: &H,
     ) -> Result<(String, Usage)> {
+        self.summarize_if_needed(tool_host.specs())
+            .await
+            .map_err(|e| self.record_error(e))?;
+
         self.messages.push(Message {
             role: Role::User,
             parts: vec![Part::Text(user_input.into())],
         });
         self.log_message(StorageRole::User, user_input)?;
+        if self.auto_title == AutoTitle::FirstMessage
+            && self.title.is_none()
+            && let Some(title) = title_from(user_input)
+        {
+            self.set_title(title)?;
+        }
+
+        let reply = self.run_turn(tool_host).await?;
+        if self.auto_title == AutoTitle::Model && self.title.is_none() {
+            // The turn itself succeeded; a missing title is not worth
+            // failing it over.
+            if let Err(e) = self.title_from_model(user_input).await {
+                tracing::warn!(error = %e, "failed to record a session title");
+            }
+        }
+        Ok(reply)
+    }
+
+    /// Ask the model for a title for the conversation so far, falling
+    /// back to `first_input` if the call fails.
+    async fn title_from_model(&mut self, first_input: &str) -> Result<()> {
+        let transcript: String = render_transcript(&self.messages)
+            .chars()
+            .take(TITLE_TRANSCRIPT_CHARS)
+            .collect();
+        let request = ModelRequest::builder()
+            .message(Message {
+                role: Role::User,
+                parts: vec![Part::Text(transcript)],
+            })
+            .system(TITLE_PROMPT)
+            .max_tokens(TITLE_MAX_TOKENS)
+            .idempotency_key(Uuid::new_v4().to_string());
+        let request = request.build();
+        self.log_request(&request)?;
+
+        let title = match self.backend.call(request).await {
+            Ok(response) => {
+                self.usage.input_tokens += response.usage.input_tokens;
+                self.usage.output_tokens += response.usage.output_tokens;
+                self.log_usage(&response)?;
+                let text = response.message.text();
+                title_from(text.trim().trim_matches(['"', '\'']))
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "title request failed; using the first message");
+                None
+            }
+        };
+        match title.or_else(|| title_from(first_input)) {
+            Some(title) => self.set_title(title),
+            None => Ok(()),
+        }
+    }
+
+    /// Discard the last response and ask the model again, using the
+    /// attached tool host.
+    pub async fn regenerate_last(&mut self) -> Result<(String, Usage)> {
+        let tool_host = Arc::clone(&self.tool_host);
+        self.regenerate_last_with_tools(&*tool_host).await
+    }
+
+    /// Discard the last response and ask the model again.
+    ///
+    /// Everything after the last user turn, including any tool exchanges,
+    /// is dropped from the in-memory conversation. The event log keeps the
+    /// original response and records the regeneration.
+    pub async fn regenerate_last_with_tools<H: ToolHost>(
+        &mut self,
+        tool_host: &H,
+    ) -> Result<(String, Usage)> {
+        let index = self.last_user_turn()?;
+        self.messages.truncate(index + 1);
+        self.append(&Event::new(self.id, EventKind::Regenerated))?;
+
+        self.run_turn(tool_host).await
+    }
+
+    /// Replace the last user turn and regenerate the response, using the
+    /// attached tool host.
+    pub async fn edit_last_user(&mut self, new_text: &str) -> Result<(String, Usage)> {
+        let tool_host = Arc::clone(&self.tool_host);
+        self.edit_last_user_with_tools(new_text, &*tool_host).await
+    }
+
+    /// Replace the last user turn and regenerate the response.
+    pub async fn edit_last_user_with_tools<H: ToolHost>(
+        &mut self,
+        new_text: &str,
+        tool_host: &H,
+    ) -> Result<(String, Usage)> {
+        let index = self.last_user_turn()?;
+        self.messages.truncate(index + 1);
+        self.messages[index].parts = vec![Part::Text(new_text.into())];
+        self.append(&Event::new(
+            self.id,
+            EventKind::MessageEdited {
+                content: new_text.into(),
+            },
+        ))?;
+
+        self.run_turn(tool_host).await
+    }
+
+    /// Forget the conversation so the next message starts a new one, while
+    /// keeping the session, its usage totals and its event log.
+    pub fn clear(&mut self) -> Result<()> {
+        self.messages.clear();
+        self.logged_outputs.clear();
+        self.context_tokens = 0;
+        self.append(&Event::new(self.id, EventKind::Cleared))?;
+        self.save_checkpoint(true)
+    }
+
+    /// Whether the conversation stopped before the model's final answer,
+    /// e.g. on a session restored by [`Self::resume`].
+    pub fn has_pending_turn(&self) -> bool {
+        self.messages
+            .last()
+            .is_some_and(|m| m.role != Role::Assistant || !m.tool_calls().is_empty())
+    }
+
+    /// Finish a turn that was interrupted mid-loop.
+    ///
+    /// Tool calls the model made without receiving results are run first,
+    /// so a tool that was executing when the process died runs again.
+    pub async fn resume_turn<H: ToolHost>(&mut self, tool_host: &H) -> Result<(String, Usage)> {
+        if !self.has_pending_turn() {
+            return Err(Error::InvalidState("no turn in progress".into()));
+        }
+        self.run_turn(tool_host).await
+    }
+
+    /// Append one pre-built message without calling the backend, e.g. a
+    /// canned greeting or injected context, logged as [`import_messages`]
+    /// would.
+    ///
+    /// Two messages in a row with the same role are allowed but warned
+    /// about, since some providers reject them.
+    ///
+    /// [`import_messages`]: Self::import_messages
+    pub fn push_message(&mut self, message: Message) -> Result<()> {
+        if let Some(last) = self.messages.last()
+            && last.role == message.role
+        {
+            tracing::warn!(
+                role = ?message.role,
+                "two consecutive messages with the same role; some providers reject this"
+            );
+        }
+        self.import_messages(vec![message])
+    }
+
+    /// Append pre-built messages to the conversation without calling the
+    /// backend, e.g. to replay a saved transcript.
+    ///
+    /// Each message is recorded as the events a live turn would have
+    /// produced: text as `message`, tool calls as `tool_call` and tool
+    /// results as `tool_result`, named after their matching call. Text in
+    /// tool messages is not logged, so a replay never mistakes it for
+    /// something the user typed.
+    pub fn import_messages(&mut self, messages: Vec<Message>) -> Result<()> {
+        let mut tool_names: HashMap<String, String> = self
+            .messages
+            .iter()
+            .flat_map(Message::tool_calls)
+            .map(|call| (call.id, call.name))
+            .collect();
+
+        let mut events = Vec::new();
+        for message in &messages {
+            let role = match message.role {
+                Role::Assistant => Some(StorageRole::Assistant),
+                Role::System => Some(StorageRole::System),
+                Role::User => Some(StorageRole::User),
+                Role::Tool => None,
+            };
+            let text = message.text();
+            if let Some(role) = role
+                && !text.is_empty()
+            {
+                events.push(Event::message(self.id, role, text));
+            }
+
+            for part in &message.parts {
+                match part {
+                    Part::Text(_) | Part::Thinking(_) => {}
+                    Part::ToolCall(call) => {
+                        tool_names.insert(call.id.clone(), call.name.clone());
+                        events.push(Event::new(
+                            self.id,
+                            EventKind::ToolCall {
+                                name: call.name.clone(),
+                                input: call.input.clone(),
+                            },
+                        ));
+                    }
+                    Part::ToolResult(result) => {
+                        let (id, output) = match result {
+                            ToolResult::Success {
+                                tool_call_id,
+                                output,
+                            } => (tool_call_id, output.clone()),
+                            ToolResult::Failure {
+                                tool_call_id,
+                                error,
+                            } => (tool_call_id, json!({ "error": error.to_string() })),
+                        };
+                        let name = tool_names.get(id).cloned().ok_or_else(|| {
+                            Error::InvalidState(format!("tool result for unknown call {id}"))
+                        })?;
+                        events.push(Event::new(self.id, EventKind::ToolResult { name, output }));
+                    }
+                }
+            }
+        }
+
+        self.append_batch(&events)?;
+        self.messages.extend(messages);
+        Ok(())
+    }
+
+    /// Replace old turns with a model-written summary if over budget.
+    ///
+    /// The count reported for the last response settles it when already
+    /// over, since it is exact for what it covered. Otherwise the backend
+    /// estimates what the next request will cost, falling back to a rough
+    /// local count if that fails.
+    async fn summarize_if_needed(&mut self, tools: &[ToolSpec]) -> Result<()> {
+        let Some(trimmer) = &self.trimmer else {
+            return Ok(());
+        };
+        let over_budget = trimmer.should_summarize(self.context_tokens) || {
+            let request = ModelRequest {
+                messages: &self.messages,
+                tools,
+                tool_choice: ToolChoice::Auto,
+                disable_parallel_tool_use: self.disable_parallel_tool_use,
+                system: self.system_prompt.as_deref(),
+                idempotency_key: None,
+                max_tokens: None,
+            };
+            let estimate = match self.backend.estimate_tokens(&request).await {
+                Ok(estimate) => estimate,
+                Err(error) => {
+                    tracing::warn!(%error, "token estimate failed; using a rough count");
+                    rough_token_count(&request)
+                }
+            };
+            trimmer.should_summarize(estimate)
+        };
+        if !over_budget {
+            return Ok(());
+        }
+        let Some(split) = trimmer.split_point(&self.messages) else {
+            return Ok(());
+        };
+
+        let request = ModelRequest::builder()
+            .messages(trimmer.summary_request(&self.messages[..split]))
+            .idempotency_key(Uuid::new_v4().to_string());
+        let request = request.build();
+        self.log_request(&request)?;
+        let response = self.backend.call(request).await?;
+
+        self.usage.input_tokens += response.usage.input_tokens;
+        self.usage.output_tokens += response.usage.output_tokens;
+        self.log_usage(&response)?;
+
+        let summary = SummarizingTrimmer::summary_message(&response.message.text());
+        self.messages.splice(..split, [summary]);
+        self.context_tokens = 0;
+        Ok(())
+    }
+
+    /// Index of the last message typed by the user.
+    ///
+    /// Live tool results use [`Role::Tool`], but imported conversations may
+    /// still carry them in user messages, as some providers' formats do,
+    /// so only messages with text count as user turns.
+    fn last_user_turn(&self) -> Result<usize> {
+        self.messages
+            .iter()
+            .rposition(|m| {
+                m.role == Role::User && m.parts.iter().any(|p| matches!(p, Part::Text(_)))
+            })
+            .ok_or_else(|| Error::InvalidState("no user message to regenerate".into()))
+    }
+
+    /// Run the tool loop, recording a failure in the event log.
+    ///
+    /// The turn runs in a `chat_turn` span carrying the session ID, model,
+    /// token counts, tool call count and duration, for metrics layers.
+    async fn run_turn<H: ToolHost>(&mut self, tool_host: &H) -> Result<(String, Usage)> {
+        let span = tracing::info_span!(
+            "chat_turn",
+            session_id = %self.id,
+            model = field::Empty,
+            input_tokens = field::Empty,
+            output_tokens = field::Empty,
+            tool_calls = 0,
+            duration_ms = field::Empty,
+        );
+        let started = Instant::now();
+        let result = match self.save_checkpoint(false) {
+            Ok(()) => self.tool_loop(tool_host).instrument(span.clone()).await,
+            Err(e) => Err(e),
+        };
+
+        span.record("duration_ms", started.elapsed().as_millis() as u64);
+        if let Ok((_, usage)) = &result {
+            self.turns += 1;
+            span.record("input_tokens", usage.input_tokens);
+            span.record("output_tokens", usage.output_tokens);
+        }
+        result.map_err(|e| self.record_error(e))
+    }
+
+    /// Append an error event and hand the error back for propagation.
+    ///
+    /// If the store itself is failing, the original error matters more than
+    /// the failure to log it, so that secondary error is dropped.
+    fn record_error(&self, error: Error) -> Error {
+        let _ = self.append(&Event::new(
+            self.id,
+            EventKind::Error {
+                source: error.category().into(),
+                message: error.to_string(),
+            },
+        ));
+        error
+    }
 
+    /// Call the model until it answers without tool calls.
+    async fn tool_loop<H: ToolHost>(&mut self, tool_host: &H) -> Result<(String, Usage)> {
         let mut turn_usage = Usage::default();
-        let tools = tool_host.specs();
+        let mut turn_tool_calls = 0;
+        let mut tools = tool_host.specs();
+        if !tools.is_empty() && !self.backend.supports_tools() {
+            if !self.warned_no_tools {
+                tracing::warn!(
+                    backend = self.backend.name(),
+                    tools = tools.len(),
+                    "backend does not support tools; they will not be offered to the model"
+                );
+                self.warned_no_tools = true;
+            }
+            tools = &[];
+        }
+
+        let unanswered = self.unanswered_tool_calls();
+        if !unanswered.is_empty() {
+            turn_tool_calls += unanswered.len();
+            Span::current().record("tool_calls", turn_tool_calls);
+            self.run_tools(&unanswered, tool_host).await?;
+        }
 
         for _ in 0..MAX_TOOL_STEPS {
-            let response = self
-                .backend
-                .call(ModelRequest {
-                    messages: &self.messages,
-                    tools,
-                })
-                .await
-                .map_err(|e| Error::Api(e.to_string()))?;
+            let idempotency_key = Uuid::new_v4().to_string();
+            let request = ModelRequest {
+                messages: &self.messages,
+                tools,
+                tool_choice: ToolChoice::Auto,
+                disable_parallel_tool_use: self.disable_parallel_tool_use,
+                system: self.system_prompt.as_deref(),
+                idempotency_key: Some(&idempotency_key),
+                max_tokens: None,
+            };
+            self.log_request(&request)?;
+            let response = self.backend.call(request).await?;
 
+            // Count billed tokens right away, so they stay in the session
+            // total even if a later step fails the turn.
             turn_usage.input_tokens += response.usage.input_tokens;
             turn_usage.output_tokens += response.usage.output_tokens;
+            self.usage.input_tokens += response.usage.input_tokens;
+            self.usage.output_tokens += response.usage.output_tokens;
+            self.context_tokens = response.usage.input_tokens + response.usage.output_tokens;
+            self.log_usage(&response)?;
+            Span::current().record("model", response.model.as_str());
 
             let text = response.message.text();
             let tool_calls = response.message.tool_calls();
+            if response.stop_reason == Some(StopReason::Refusal) {
+                return Err(Error::Refused { text });
+            }
+            // Keep it out of the conversation: the API rejects empty
+            // assistant turns.
+            if text.is_empty() && tool_calls.is_empty() {
+                return Err(Error::EmptyResponse {
+                    stop_reason: response.stop_reason,
+                });
+            }
 
             self.messages.push(response.message);
 
@@ -96,55 +890,215 @@ impl<B: Backend> Session<B> {
             }
 
             if tool_calls.is_empty() {
-                self.usage.input_tokens += turn_usage.input_tokens;
-                self.usage.output_tokens += turn_usage.output_tokens;
+                self.save_checkpoint(true)?;
                 return Ok((text, turn_usage));
             }
+            self.save_checkpoint(false)?;
 
-            let results = self.execute_tools(&tool_calls, tool_host).await?;
-            self.messages.push(Message {
-                role: Role::User,
-                parts: results,
-            });
+            turn_tool_calls += tool_calls.len();
+            Span::current().record("tool_calls", turn_tool_calls);
+            self.run_tools(&tool_calls, tool_host).await?;
         }
 
         Err(Error::InvalidState("max tool steps exceeded".into()))
     }
 
-    async fn execute_tools<H: ToolHost>(&self, calls: &[ToolCall], host: &H) -> Result<Vec<Part>> {
+    /// Tool calls in the last message if it is an assistant message, which
+    /// means their results have not been added yet.
+    fn unanswered_tool_calls(&self) -> Vec<ToolCall> {
+        match self.messages.last() {
+            Some(message) if message.role == Role::Assistant => message.tool_calls(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Execute one round of tool calls and add their results.
+    async fn run_tools<H: ToolHost>(&mut self, calls: &[ToolCall], host: &H) -> Result<()> {
+        let results = self.execute_tools(calls, host).await?;
+        self.messages.push(Message {
+            role: Role::Tool,
+            parts: results,
+        });
+        self.save_checkpoint(false)
+    }
+
+    /// Write the conversation to the log if checkpointing is enabled.
+    fn save_checkpoint(&self, turn_complete: bool) -> Result<()> {
+        if !self.checkpoint {
+            return Ok(());
+        }
+        let messages = serde_json::to_value(self.redacted(&self.messages))
+            .map_err(|e| Error::InvalidState(format!("cannot checkpoint messages: {e}")))?;
+        self.append(&Event::new(
+            self.id,
+            EventKind::Checkpoint {
+                messages,
+                turn_complete,
+            },
+        ))?;
+        Ok(())
+    }
+
+    /// `messages` with tool outputs replaced by what the tool host logs
+    /// for them, so secrets masked in `tool_result` events stay masked.
+    fn redacted(&self, messages: &[Message]) -> Vec<Message> {
+        let mut messages = messages.to_vec();
+        if self.logged_outputs.is_empty() {
+            return messages;
+        }
+        for part in messages.iter_mut().flat_map(|m| m.parts.iter_mut()) {
+            if let Part::ToolResult(ToolResult::Success {
+                tool_call_id,
+                output,
+            }) = part
+                && let Some(logged) = self.logged_outputs.get(tool_call_id)
+            {
+                *output = logged.clone();
+            }
+        }
+        messages
+    }
+
+    async fn execute_tools<H: ToolHost>(
+        &mut self,
+        calls: &[ToolCall],
+        host: &H,
+    ) -> Result<Vec<Part>> {
         let mut results = Vec::with_capacity(calls.len());
+        let mut events = Vec::with_capacity(calls.len() * 2);
 
         for call in calls {
-            self.store.append(&Event::new(
+            let approval = match &self.approver {
+                Some(approve) => approve(call),
+                None => ToolApproval::Allow,
+            };
+            let modified;
+            let call = match approval {
+                ToolApproval::Allow => call,
+                ToolApproval::Deny(reason) => {
+                    tracing::info!(tool = %call.name, %reason, "tool call denied");
+                    let error = ToolError::Rejected(reason);
+                    host.answered(call, &Err(error.clone()));
+                    events.push(Event::new(
+                        self.id,
+                        EventKind::ToolCall {
+                            name: call.name.clone(),
+                            input: call.input.clone(),
+                        },
+                    ));
+                    events.push(Event::new(
+                        self.id,
+                        EventKind::ToolResult {
+                            name: call.name.clone(),
+                            output: json!({ "error": error.to_string() }),
+                        },
+                    ));
+                    results.push(Part::ToolResult(ToolResult::Failure {
+                        tool_call_id: call.id.clone(),
+                        error,
+                    }));
+                    continue;
+                }
+                ToolApproval::Modify(input) => {
+                    tracing::info!(tool = %call.name, "tool call arguments modified");
+                    modified = ToolCall {
+                        input,
+                        ..call.clone()
+                    };
+                    &modified
+                }
+            };
+
+            events.push(Event::new(
                 self.id,
                 EventKind::ToolCall {
                     name: call.name.clone(),
                     input: call.input.clone(),
                 },
-            ))?;
+            ));
+
+            if self.dry_run {
+                let output = json!({ "dry_run": true });
+                host.answered(call, &Ok(output.clone()));
+                events.push(Event::new(
+                    self.id,
+                    EventKind::ToolResult {
+                        name: call.name.clone(),
+                        output: output.clone(),
+                    },
+                ));
+                results.push(Part::ToolResult(ToolResult::Success {
+                    tool_call_id: call.id.clone(),
+                    output,
+                }));
+                continue;
+            }
+
+            let cached = self.tool_cache.as_mut().and_then(|cache| cache.get(call));
+            let outcome = if let Some(output) = cached {
+                tracing::info!(tool = %call.name, outcome = "cached", "tool executed");
+                host.answered(call, &Ok(output.clone()));
+                Ok(output)
+            } else {
+                // Record the call before running it, so a crash, hang or
+                // dropped future still leaves a trace of what was attempted.
+                self.append_batch(&events)?;
+                events.clear();
+                let started = Instant::now();
+                let outcome = match self.tool_timeouts.get(&call.name) {
+                    Some(limit) => tokio::time::timeout(limit, host.execute(call))
+                        .await
+                        .unwrap_or(Err(ToolError::Timeout(limit.as_millis() as u64))),
+                    None => host.execute(call).await,
+                };
+                let duration_ms = started.elapsed().as_millis() as u64;
+                match &outcome {
+                    Ok(_) => tracing::info!(
+                        tool = %call.name,
+                        outcome = "ok",
+                        duration_ms,
+                        "tool executed"
+                    ),
+                    Err(error) => tracing::info!(
+                        tool = %call.name,
+                        outcome = "error",
+                        %error,
+                        duration_ms,
+                        "tool executed"
+                    ),
+                }
+                if let Some(cache) = &mut self.tool_cache {
+                    cache.record(call, outcome.as_ref().ok());
+                }
+                outcome
+            };
 
-            let part = match host.execute(call).await {
+            let part = match outcome {
                 Ok(output) => {
-                    self.store.append(&Event::new(
+                    let logged = host.log_output(call, &output);
+                    if logged != output {
+                        self.logged_outputs.insert(call.id.clone(), logged.clone());
+                    }
+                    events.push(Event::new(
                         self.id,
                         EventKind::ToolResult {
                             name: call.name.clone(),
-                            output: output.clone(),
+                            output: logged,
                         },
-                    ))?;
+                    ));
                     Part::ToolResult(ToolResult::Success {
                         tool_call_id: call.id.clone(),
-                        output,
+                        output: truncate_output(output, self.max_tool_result_bytes),
                     })
                 }
                 Err(error) => {
-                    self.store.append(&Event::new(
+                    events.push(Event::new(
                         self.id,
                         EventKind::ToolResult {
                             name: call.name.clone(),
                             output: json!({ "error": error.to_string() }),
                         },
-                    ))?;
+                    ));
                     Part::ToolResult(ToolResult::Failure {
                         tool_call_id: call.id.clone(),
                         error,
@@ -155,18 +1109,1823 @@ impl<B: Backend> Session<B> {
             results.push(part);
         }
 
+        self.append_batch(&events)?;
         Ok(results)
     }
 
+    /// Store an event, then pass it to every sink.
+    fn append(&self, event: &Event) -> Result<()> {
+        self.store.append(event)?;
+        for sink in &self.sinks {
+            sink.record(event);
+        }
+        Ok(())
+    }
+
+    fn append_batch(&self, events: &[Event]) -> Result<()> {
+        self.store.append_batch(events)?;
+        for event in events {
+            for sink in &self.sinks {
+                sink.record(event);
+            }
+        }
+        Ok(())
+    }
+
     fn log_message(&self, role: StorageRole, content: &str) -> Result<()> {
-        self.store.append(&Event::message(self.id, role, content))?;
+        self.append(&Event::message(self.id, role, content))?;
         Ok(())
     }
 
-    /// End the session.
-    pub fn end(self) -> Result<()> {
-        self.store
-            .append(&Event::new(self.id, EventKind::SessionEnd))?;
+    fn log_request(&self, request: &ModelRequest<'_>) -> Result<()> {
+        if self.request_log == RequestLog::Off {
+            return Ok(());
+        }
+        let full = (self.request_log == RequestLog::Full).then(|| {
+            json!({
+                "messages": self.redacted(request.messages),
+                "tools": request.tools,
+                "tool_choice": request.tool_choice,
+            })
+        });
+        self.append(&Event::new(
+            self.id,
+            EventKind::ModelRequest {
+                system: request.system.map(str::to_string),
+                message_count: u32::try_from(request.messages.len()).unwrap_or(u32::MAX),
+                tool_names: request.tools.iter().map(|spec| spec.name.clone()).collect(),
+                request: full,
+            },
+        ))?;
+        Ok(())
+    }
+
+    fn log_usage(&self, response: &ModelResponse) -> Result<()> {
+        self.append(&Event::new(
+            self.id,
+            EventKind::Usage {
+                model: response.model.clone(),
+                input_tokens: response.usage.input_tokens,
+                output_tokens: response.usage.output_tokens,
+            },
+        ))?;
         Ok(())
     }
+
+    /// End the session, returning its totals.
+    ///
+    /// An attached tool host is dropped without being shut down, which
+    /// leaves MCP server processes running. A session with an attached
+    /// host must end with [`Self::end_and_shutdown`] instead; dropping it
+    /// has the same problem as this method.
+    pub fn end(self) -> Result<SessionOutcome> {
+        self.append(&Event::new(self.id, EventKind::SessionEnd))?;
+        Ok(SessionOutcome {
+            id: self.id,
+            usage: self.usage,
+            turns: self.turns,
+            duration: self.started.elapsed(),
+        })
+    }
+
+    /// End the session and shut down the tool host it has been using.
+    ///
+    /// The session only borrows its tool host per call, so it cannot do
+    /// this on drop; callers that own the host hand it over here.
+    pub async fn end_with_tools<H: ToolHost>(self, tool_host: H) -> Result<SessionOutcome> {
+        tool_host.shutdown().await;
+        self.end()
+    }
+
+    /// End the session and shut down its attached tool host.
+    pub async fn end_and_shutdown(self) -> Result<SessionOutcome> {
+        let tool_host = Arc::clone(&self.tool_host);
+        let outcome = self.end();
+        // The session held the only other reference, and it is gone.
+        if let Ok(tool_host) = Arc::try_unwrap(tool_host) {
+            tool_host.shutdown().await;
+        }
+        outcome
+    }
+}
+
+/// A title for a session that opened with `text`: its first non-blank
+/// line, cut to [`MAX_AUTO_TITLE_CHARS`].
+fn title_from(text: &str) -> Option<String> {
+    let line = text.lines().map(str::trim).find(|line| !line.is_empty())?;
+    match line.char_indices().nth(MAX_AUTO_TITLE_CHARS) {
+        Some((cut, _)) => Some(format!("{}…", line[..cut].trim_end())),
+        None => Some(line.to_string()),
+    }
+}
+
+/// Render messages as plain text, one line per part.
+pub(crate) fn render_transcript(messages: &[Message]) -> String {
+    let mut out = String::new();
+    for message in messages {
+        let role = message.role;
+        for part in &message.parts {
+            let line = match part {
+                Part::Text(text) => format!("{role}: {text}"),
+                Part::ToolCall(call) => {
+                    let name = &call.name;
+                    let input = &call.input;
+                    format!("{role}: [tool call {name}] {input}")
+                }
+                Part::ToolResult(ToolResult::Success { output, .. }) => {
+                    format!("{role}: [tool result] {output}")
+                }
+                Part::ToolResult(ToolResult::Failure { error, .. }) => {
+                    format!("{role}: [tool error] {error}")
+                }
+                Part::Thinking(_) => continue,
+            };
+            out.push_str(&line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Shorten a tool output to at most `max_bytes` of text plus a marker.
+///
+/// Strings are cut directly; other values are cut as serialized JSON,
+/// which leaves a string since the JSON is no longer valid.
+fn truncate_output(output: Value, max_bytes: usize) -> Value {
+    let text = match output {
+        Value::String(text) => text,
+        other => {
+            let json = other.to_string();
+            if json.len() <= max_bytes {
+                return other;
+            }
+            json
+        }
+    };
+    if text.len() <= max_bytes {
+        return Value::String(text);
+    }
+
+    let kept = text.floor_char_boundary(max_bytes);
+    let total = text.len();
+    Value::String(format!(
+        "{}...[truncated {} of {total} bytes]",
+        &text[..kept],
+        total - kept
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ApiErrorKind, ModelError, ModelInfo};
+    use crate::tools::ToolError;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    /// Backend that replays canned responses in order.
+    struct ScriptedBackend {
+        responses: Mutex<Vec<ModelResponse>>,
+        /// System prompt of each request received.
+        systems: Mutex<Vec<Option<String>>>,
+        /// Names of the tools offered in each request received.
+        offered_tools: Mutex<Vec<Vec<String>>>,
+        /// Conversation sent with each request received.
+        sent_messages: Mutex<Vec<Vec<Message>>>,
+    }
+
+    impl ScriptedBackend {
+        fn new(mut responses: Vec<ModelResponse>) -> Self {
+            responses.reverse();
+            Self {
+                responses: Mutex::new(responses),
+                systems: Mutex::default(),
+                offered_tools: Mutex::default(),
+                sent_messages: Mutex::default(),
+            }
+        }
+    }
+
+    impl Backend for ScriptedBackend {
+        async fn call(
+            &self,
+            request: ModelRequest<'_>,
+        ) -> std::result::Result<ModelResponse, ModelError> {
+            self.systems
+                .lock()
+                .unwrap()
+                .push(request.system.map(str::to_string));
+            self.offered_tools
+                .lock()
+                .unwrap()
+                .push(request.tools.iter().map(|spec| spec.name.clone()).collect());
+            self.sent_messages
+                .lock()
+                .unwrap()
+                .push(request.messages.to_vec());
+            self.responses
+                .lock()
+                .unwrap()
+                .pop()
+                .ok_or_else(|| ModelError::Api {
+                    kind: ApiErrorKind::Other,
+                    message: "no scripted response left".into(),
+                })
+        }
+
+        fn model_info(&self) -> ModelInfo {
+            ModelInfo {
+                context_window: 200_000,
+                max_output_tokens: 4_096,
+                supports_tools: true,
+                supports_vision: false,
+            }
+        }
+
+        fn name(&self) -> &str {
+            "scripted"
+        }
+    }
+
+    /// A [`ScriptedBackend`] that claims not to support tools.
+    struct TextOnlyBackend(ScriptedBackend);
+
+    impl Backend for TextOnlyBackend {
+        async fn call(
+            &self,
+            request: ModelRequest<'_>,
+        ) -> std::result::Result<ModelResponse, ModelError> {
+            self.0.call(request).await
+        }
+
+        fn model_info(&self) -> ModelInfo {
+            self.0.model_info()
+        }
+
+        fn name(&self) -> &str {
+            "text-only"
+        }
+
+        fn supports_tools(&self) -> bool {
+            false
+        }
+    }
+
+    /// Tool host that fails the test if any tool is actually executed.
+    struct PanickingToolHost;
+
+    impl ToolHost for PanickingToolHost {
+        fn specs(&self) -> &[ToolSpec] {
+            &[]
+        }
+
+        async fn execute(&self, call: &ToolCall) -> std::result::Result<Value, ToolError> {
+            panic!("tool {} executed", call.name);
+        }
+    }
+
+    /// Tool host offering tools with the given names, answering every call
+    /// with its input and recording its shutdown.
+    #[derive(Default)]
+    struct EchoToolHost {
+        specs: Vec<ToolSpec>,
+        shut_down: Arc<AtomicBool>,
+    }
+
+    impl EchoToolHost {
+        fn offering(names: &[&str]) -> Self {
+            let specs = names
+                .iter()
+                .map(|name| ToolSpec {
+                    name: name.to_string(),
+                    description: String::new(),
+                    schema: json!({ "type": "object" }),
+                })
+                .collect();
+            Self {
+                specs,
+                ..Self::default()
+            }
+        }
+    }
+
+    impl ToolHost for EchoToolHost {
+        fn specs(&self) -> &[ToolSpec] {
+            &self.specs
+        }
+
+        async fn execute(&self, call: &ToolCall) -> std::result::Result<Value, ToolError> {
+            Ok(call.input.clone())
+        }
+
+        async fn shutdown(self) {
+            self.shut_down.store(true, Ordering::SeqCst);
+        }
+    }
+
+    /// Tool host that answers every call with the closure's future.
+    struct FnToolHost<F>(F);
+
+    impl<F, Fut> ToolHost for FnToolHost<F>
+    where
+        F: Fn(ToolCall) -> Fut + Send + Sync,
+        Fut: Future<Output = std::result::Result<Value, ToolError>> + Send,
+    {
+        fn specs(&self) -> &[ToolSpec] {
+            &[]
+        }
+
+        fn execute(
+            &self,
+            call: &ToolCall,
+        ) -> impl Future<Output = std::result::Result<Value, ToolError>> + Send {
+            (self.0)(call.clone())
+        }
+    }
+
+    /// Tool host whose every tool returns the same output.
+    struct FixedToolHost(Value);
+
+    impl ToolHost for FixedToolHost {
+        fn specs(&self) -> &[ToolSpec] {
+            &[]
+        }
+
+        async fn execute(&self, _call: &ToolCall) -> std::result::Result<Value, ToolError> {
+            Ok(self.0.clone())
+        }
+    }
+
+    /// Tool host that hands out a secret and masks it in the event log.
+    struct SecretToolHost;
+
+    impl ToolHost for SecretToolHost {
+        fn specs(&self) -> &[ToolSpec] {
+            &[]
+        }
+
+        async fn execute(&self, _call: &ToolCall) -> std::result::Result<Value, ToolError> {
+            Ok(json!({ "value": "hunter2" }))
+        }
+
+        fn log_output(&self, _call: &ToolCall, _output: &Value) -> Value {
+            json!({ "value": "****" })
+        }
+    }
+
+    fn text_response(text: &str) -> ModelResponse {
+        ModelResponse {
+            message: Message {
+                role: Role::Assistant,
+                parts: vec![Part::Text(text.into())],
+            },
+            usage: Usage::default(),
+            model: "scripted-model".into(),
+            request_id: None,
+            stop_reason: None,
+        }
+    }
+
+    fn tool_call_response(id: &str, name: &str, input: Value) -> ModelResponse {
+        ModelResponse {
+            message: Message {
+                role: Role::Assistant,
+                parts: vec![Part::ToolCall(ToolCall {
+                    id: id.into(),
+                    name: name.into(),
+                    input,
+                })],
+            },
+            usage: Usage::default(),
+            model: "scripted-model".into(),
+            request_id: None,
+            stop_reason: None,
+        }
+    }
+
+    fn session_with_policy(policy: Policy) -> Session<ScriptedBackend> {
+        let store = EventStore::in_memory().unwrap();
+        Session::new(store, ScriptedBackend::new(Vec::new()), policy).unwrap()
+    }
+
+    #[test]
+    fn new_with_id_starts_each_id_once() {
+        let store = EventStore::in_memory().unwrap();
+        let id = SessionId::from_u128(1);
+
+        let session = Session::new_with_id(
+            store.clone(),
+            ScriptedBackend::new(Vec::new()),
+            Policy::default(),
+            id,
+        )
+        .unwrap();
+        assert_eq!(session.id, id);
+
+        Session::new_with_id(
+            store.clone(),
+            ScriptedBackend::new(Vec::new()),
+            Policy::default(),
+            id,
+        )
+        .unwrap();
+        let starts = store.load_events(id, Some("session_start")).unwrap();
+        assert_eq!(starts.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn end_with_tools_shuts_down_host_and_ends_session() {
+        let session = session_with_policy(Policy::default());
+        let store = session.store.clone();
+        let id = session.id;
+        let host = EchoToolHost::default();
+        let closed = host.shut_down.clone();
+
+        session.end_with_tools(host).await.unwrap();
+
+        assert!(closed.load(Ordering::SeqCst));
+        assert_eq!(store.load_events(id, Some("session_end")).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn require_capability_enforces_call_limit() {
+        let policy = Policy::parse(
+            r#"
+[allow]
+exec = ["git"]
+
+[limits]
+exec = { max_calls = 2 }
+"#,
+        )
+        .unwrap();
+        let mut session = session_with_policy(policy);
+        let request = CapabilityRequest::exec("git status");
+
+        assert!(session.require_capability(&request).is_ok());
+        assert!(session.require_capability(&request).is_ok());
+        let err = session.require_capability(&request).unwrap_err();
+
+        assert!(matches!(err, Error::CapabilityDenied(reason) if reason.contains("limit")));
+        assert_eq!(session.capability_calls(CapabilityKind::Exec), 2);
+    }
+
+    #[cfg(feature = "local-tools")]
+    #[tokio::test]
+    async fn tool_loop_enforces_call_limits() {
+        let policy = Policy::parse(
+            r#"
+[allow]
+exec = ["echo"]
+
+[limits]
+exec = { max_calls = 1 }
+"#,
+        )
+        .unwrap();
+        let echo =
+            |id| tool_call_response(id, "exec", json!({ "command": "echo", "args": ["hi"] }));
+        let backend =
+            ScriptedBackend::new(vec![echo("call_1"), echo("call_2"), text_response("done")]);
+        let store = EventStore::in_memory().unwrap();
+        let mut session = Session::new(store, backend, policy.clone()).unwrap();
+        let host = crate::tools::LocalToolHost::new(policy);
+
+        session.chat_with_tools("echo twice", &host).await.unwrap();
+
+        let results: Vec<_> = session
+            .messages
+            .iter()
+            .filter_map(|m| match m.parts.first() {
+                Some(Part::ToolResult(result)) => Some(result),
+                _ => None,
+            })
+            .collect();
+        assert!(matches!(results[0], ToolResult::Success { .. }));
+        assert!(matches!(
+            results[1],
+            ToolResult::Failure {
+                error: ToolError::CapabilityDenied(reason),
+                ..
+            } if reason.contains("limit")
+        ));
+        assert_eq!(host.capability_calls(CapabilityKind::Exec), 1);
+    }
+
+    #[test]
+    fn require_capability_denied_does_not_count() {
+        let policy = Policy::parse(
+            r#"
+[limits]
+exec = { max_calls = 1 }
+"#,
+        )
+        .unwrap();
+        let mut session = session_with_policy(policy);
+
+        assert!(
+            session
+                .require_capability(&CapabilityRequest::exec("ls"))
+                .is_err()
+        );
+        assert_eq!(session.capability_calls(CapabilityKind::Exec), 0);
+    }
+
+    #[tokio::test]
+    async fn dry_run_logs_tool_calls_without_executing() {
+        let backend = ScriptedBackend::new(vec![
+            tool_call_response("call_1", "delete_file", json!({ "path": "a.txt" })),
+            text_response("done"),
+        ]);
+        let store = EventStore::in_memory().unwrap();
+        let mut session = Session::new(store, backend, Policy::default())
+            .unwrap()
+            .dry_run(true);
+
+        let (text, _) = session
+            .chat_with_tools("clean up", &PanickingToolHost)
+            .await
+            .unwrap();
+        assert_eq!(text, "done");
+
+        let events = session.store.load_session(session.id).unwrap();
+        let logged: Vec<_> = events
+            .iter()
+            .filter_map(|e| match &e.kind {
+                EventKind::ToolCall { name, input } => Some(("call", name, input.clone())),
+                EventKind::ToolResult { name, output } => Some(("result", name, output.clone())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(logged.len(), 2);
+        assert_eq!(logged[0].0, "call");
+        assert_eq!(logged[0].2, json!({ "path": "a.txt" }));
+        assert_eq!(logged[1].0, "result");
+        assert_eq!(logged[1].2, json!({ "dry_run": true }));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_sessions_share_one_store() {
+        const SESSIONS: u32 = 8;
+        let used = |mut response: ModelResponse| {
+            response.usage = Usage {
+                input_tokens: 10,
+                output_tokens: 1,
+            };
+            response
+        };
+        let store = EventStore::in_memory().unwrap();
+
+        let mut tasks = Vec::new();
+        for n in 0..SESSIONS {
+            let backend = ScriptedBackend::new(vec![
+                used(tool_call_response("call_1", "lookup", json!({ "n": n }))),
+                used(text_response(&format!("answer {n}"))),
+                used(text_response("bye")),
+            ]);
+            let mut session = Session::new(store.clone(), backend, Policy::default()).unwrap();
+            tasks.push(tokio::spawn(async move {
+                let host = FixedToolHost(json!(n));
+                let (text, _) = session.chat_with_tools("look it up", &host).await.unwrap();
+                assert_eq!(text, format!("answer {n}"));
+                session.chat("thanks").await.unwrap();
+                session.end().unwrap()
+            }));
+        }
+
+        for task in tasks {
+            let outcome = task.await.unwrap();
+            assert_eq!(outcome.turns, 2);
+            assert_eq!(outcome.usage.input_tokens, 30);
+            let events = store.load_session(outcome.id).unwrap();
+            let messages = events
+                .iter()
+                .filter(|e| matches!(e.kind, EventKind::Message { .. }))
+                .count();
+            assert_eq!(messages, 4);
+        }
+        let stats = store.usage_stats(None).unwrap();
+        assert_eq!(stats.sessions, u64::from(SESSIONS));
+        assert_eq!(stats.input_tokens(), u64::from(SESSIONS) * 30);
+    }
+
+    #[tokio::test]
+    async fn event_sinks_see_every_new_event() {
+        struct Collect(Mutex<Vec<&'static str>>);
+
+        impl EventSink for Collect {
+            fn record(&self, event: &Event) {
+                self.0.lock().unwrap().push(event.kind.name());
+            }
+        }
+
+        let sink = Arc::new(Collect(Mutex::default()));
+        let backend = ScriptedBackend::new(vec![
+            tool_call_response("call_1", "search", json!({})),
+            text_response("done"),
+        ]);
+        let store = EventStore::in_memory().unwrap();
+        let mut session = Session::new(store.clone(), backend, Policy::default())
+            .unwrap()
+            .event_sink(Arc::clone(&sink));
+
+        session
+            .chat_with_tools("look", &FixedToolHost(json!("found")))
+            .await
+            .unwrap();
+        let id = session.id;
+        session.end().unwrap();
+
+        let stored: Vec<_> = store
+            .load_session(id)
+            .unwrap()
+            .iter()
+            .map(|event| event.kind.name())
+            .collect();
+        // Everything but the session_start written before the sink was added.
+        assert_eq!(stored[0], "session_start");
+        assert_eq!(*sink.0.lock().unwrap(), stored[1..]);
+    }
+
+    #[tokio::test]
+    async fn refusals_are_an_error() {
+        let mut refusal = text_response("I can't help with that.");
+        refusal.stop_reason = Some(StopReason::Refusal);
+        let backend = ScriptedBackend::new(vec![refusal]);
+        let store = EventStore::in_memory().unwrap();
+        let mut session = Session::new(store, backend, Policy::default()).unwrap();
+
+        let err = session.chat("do something bad").await.unwrap_err();
+        assert!(
+            matches!(&err, Error::Refused { text } if text == "I can't help with that."),
+            "{err:?}"
+        );
+        assert_eq!(err.category(), "refused");
+        assert_eq!(session.messages.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn empty_responses_are_an_error() {
+        // What the backend returns when every block was filtered out.
+        let mut thinking_only = text_response("");
+        thinking_only.message.parts.clear();
+        thinking_only.stop_reason = Some(StopReason::EndTurn);
+        let backend = ScriptedBackend::new(vec![thinking_only]);
+        let store = EventStore::in_memory().unwrap();
+        let mut session = Session::new(store, backend, Policy::default()).unwrap();
+
+        let err = session.chat("hello").await.unwrap_err();
+        assert!(
+            matches!(
+                &err,
+                Error::EmptyResponse {
+                    stop_reason: Some(StopReason::EndTurn)
+                }
+            ),
+            "{err:?}"
+        );
+        assert_eq!(
+            err.to_string(),
+            "model returned an empty response (stop reason: end_turn)"
+        );
+        // The empty turn is not kept in the conversation.
+        assert_eq!(session.messages.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn text_only_backends_are_not_offered_tools() {
+        let backend = TextOnlyBackend(ScriptedBackend::new(vec![text_response("hi")]));
+        let store = EventStore::in_memory().unwrap();
+        let mut session = Session::new(store, backend, Policy::default()).unwrap();
+        let host = EchoToolHost::offering(&["search"]);
+
+        session.chat_with_tools("hello", &host).await.unwrap();
+        assert_eq!(
+            *session.backend.0.offered_tools.lock().unwrap(),
+            [Vec::<String>::new()]
+        );
+        assert!(session.warned_no_tools);
+    }
+
+    #[tokio::test]
+    async fn denied_tool_calls_fail_without_executing() {
+        let backend = ScriptedBackend::new(vec![
+            tool_call_response("call_1", "delete_file", json!({ "path": "a.txt" })),
+            text_response("ok, I won't"),
+        ]);
+        let store = EventStore::in_memory().unwrap();
+        let mut session = Session::new(store, backend, Policy::default())
+            .unwrap()
+            .on_tool_call(|call| ToolApproval::Deny(format!("{} not allowed", call.name)));
+
+        let (text, _) = session
+            .chat_with_tools("clean up", &PanickingToolHost)
+            .await
+            .unwrap();
+        assert_eq!(text, "ok, I won't");
+
+        let tool_message = &session.messages[2];
+        assert_eq!(tool_message.role, Role::Tool);
+        assert!(matches!(
+            &tool_message.parts[0],
+            Part::ToolResult(ToolResult::Failure {
+                error: ToolError::Rejected(reason),
+                ..
+            }) if reason == "delete_file not allowed"
+        ));
+    }
+
+    #[tokio::test]
+    async fn mixed_text_and_tool_calls_are_sent_back_whole() {
+        let mixed = Message {
+            role: Role::Assistant,
+            parts: vec![
+                Part::Text("Checking both files.".into()),
+                Part::ToolCall(ToolCall {
+                    id: "call_1".into(),
+                    name: "read_file".into(),
+                    input: json!({ "path": "a.txt" }),
+                }),
+                Part::ToolCall(ToolCall {
+                    id: "call_2".into(),
+                    name: "read_file".into(),
+                    input: json!({ "path": "b.txt" }),
+                }),
+            ],
+        };
+        let backend = ScriptedBackend::new(vec![
+            ModelResponse {
+                message: mixed.clone(),
+                ..text_response("")
+            },
+            text_response("Both are empty."),
+        ]);
+        let store = EventStore::in_memory().unwrap();
+        let mut session = Session::new(store, backend, Policy::default()).unwrap();
+
+        session
+            .chat_with_tools("read a and b", &FixedToolHost(json!("")))
+            .await
+            .unwrap();
+
+        let sent = session.backend.sent_messages.lock().unwrap();
+        let second = &sent[1];
+        assert_eq!(second.len(), 3);
+        assert_eq!(
+            serde_json::to_value(&second[1]).unwrap(),
+            serde_json::to_value(&mixed).unwrap()
+        );
+        let result_ids: Vec<_> = second[2]
+            .parts
+            .iter()
+            .map(|part| match part {
+                Part::ToolResult(
+                    ToolResult::Success { tool_call_id, .. }
+                    | ToolResult::Failure { tool_call_id, .. },
+                ) => tool_call_id.as_str(),
+                _ => panic!("expected only tool results"),
+            })
+            .collect();
+        assert_eq!(result_ids, ["call_1", "call_2"]);
+    }
+
+    #[tokio::test]
+    async fn modified_tool_calls_run_with_new_arguments() {
+        let backend = ScriptedBackend::new(vec![
+            tool_call_response("call_1", "read_file", json!({ "path": "/etc/passwd" })),
+            text_response("done"),
+        ]);
+        let store = EventStore::in_memory().unwrap();
+        let mut session = Session::new(store, backend, Policy::default())
+            .unwrap()
+            .on_tool_call(|_| ToolApproval::Modify(json!({ "path": "a.txt" })));
+
+        session
+            .chat_with_tools("read it", &EchoToolHost::default())
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            &session.messages[2].parts[0],
+            Part::ToolResult(ToolResult::Success { output, .. }) if *output == json!({ "path": "a.txt" })
+        ));
+        let events = session.store.load_session(session.id).unwrap();
+        let logged_input = events.iter().find_map(|e| match &e.kind {
+            EventKind::ToolCall { input, .. } => Some(input.clone()),
+            _ => None,
+        });
+        assert_eq!(logged_input, Some(json!({ "path": "a.txt" })));
+    }
+
+    #[tokio::test]
+    async fn cached_tool_results_skip_execution() {
+        let read = || tool_call_response("call_1", "read_file", json!({ "path": "a.txt" }));
+        let backend = ScriptedBackend::new(vec![
+            read(),
+            read(),
+            tool_call_response("call_2", "write_file", json!({ "path": "a.txt" })),
+            read(),
+            text_response("done"),
+        ]);
+        let store = EventStore::in_memory().unwrap();
+        let mut session = Session::new(store, backend, Policy::default())
+            .unwrap()
+            .cache_tool_results(ToolCache::new(["read_file"]));
+        let runs = AtomicUsize::new(0);
+        let host = FnToolHost(|_| {
+            let run = runs.fetch_add(1, Ordering::SeqCst);
+            async move { Ok(json!(run)) }
+        });
+
+        session.chat_with_tools("read it", &host).await.unwrap();
+
+        let outputs: Vec<_> = session
+            .messages
+            .iter()
+            .filter_map(|m| match m.parts.first() {
+                Some(Part::ToolResult(ToolResult::Success { output, .. })) => Some(output.clone()),
+                _ => None,
+            })
+            .collect();
+        // The second read is cached; the write clears the cache.
+        assert_eq!(outputs, [json!(0), json!(0), json!(1), json!(2)]);
+    }
+
+    #[tokio::test]
+    async fn slow_tools_time_out_at_their_own_limit() {
+        let backend = ScriptedBackend::new(vec![
+            tool_call_response("call_1", "read_file", json!({ "path": "a.txt" })),
+            tool_call_response("call_2", "run_tests", json!({})),
+            text_response("done"),
+        ]);
+        let store = EventStore::in_memory().unwrap();
+        let mut session = Session::new(store, backend, Policy::default())
+            .unwrap()
+            .tool_timeouts(
+                ToolTimeouts::new()
+                    .default_timeout(Duration::from_millis(10))
+                    .tool("run_*", Duration::from_secs(10)),
+            );
+
+        let host = FnToolHost(|call: ToolCall| async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Ok(json!(call.name))
+        });
+        session.chat_with_tools("test it", &host).await.unwrap();
+
+        let results: Vec<_> = session
+            .messages
+            .iter()
+            .filter_map(|m| match m.parts.first() {
+                Some(Part::ToolResult(result)) => Some(result),
+                _ => None,
+            })
+            .collect();
+        assert!(matches!(
+            results[0],
+            ToolResult::Failure {
+                error: ToolError::Timeout(10),
+                ..
+            }
+        ));
+        assert!(matches!(
+            results[1],
+            ToolResult::Success { output, .. } if *output == json!("run_tests")
+        ));
+    }
+
+    #[tokio::test]
+    async fn hosts_see_calls_the_session_answers() {
+        #[derive(Default)]
+        struct WatchingToolHost {
+            executed: Mutex<Vec<String>>,
+            answered: Mutex<Vec<(String, bool)>>,
+        }
+
+        impl ToolHost for WatchingToolHost {
+            fn specs(&self) -> &[ToolSpec] {
+                &[]
+            }
+
+            async fn execute(&self, call: &ToolCall) -> std::result::Result<Value, ToolError> {
+                self.executed.lock().unwrap().push(call.name.clone());
+                Ok(json!("ran"))
+            }
+
+            fn answered(&self, call: &ToolCall, result: &std::result::Result<Value, ToolError>) {
+                self.answered
+                    .lock()
+                    .unwrap()
+                    .push((call.name.clone(), result.is_ok()));
+            }
+        }
+
+        let read = || tool_call_response("call_1", "read_file", json!({ "path": "a.txt" }));
+        let backend = ScriptedBackend::new(vec![
+            read(),
+            read(),
+            tool_call_response("call_2", "rm", json!({})),
+            text_response("done"),
+        ]);
+        let store = EventStore::in_memory().unwrap();
+        let mut session = Session::new(store.clone(), backend, Policy::default())
+            .unwrap()
+            .cache_tool_results(ToolCache::new(["read_file"]))
+            .on_tool_call(|call| match call.name.as_str() {
+                "rm" => ToolApproval::Deny("no".into()),
+                _ => ToolApproval::Allow,
+            });
+        let host = WatchingToolHost::default();
+        session.chat_with_tools("go", &host).await.unwrap();
+
+        assert_eq!(*host.executed.lock().unwrap(), ["read_file"]);
+        assert_eq!(
+            *host.answered.lock().unwrap(),
+            [("read_file".to_string(), true), ("rm".to_string(), false)]
+        );
+
+        let backend = ScriptedBackend::new(vec![read(), text_response("done")]);
+        let mut session = Session::new(store, backend, Policy::default())
+            .unwrap()
+            .dry_run(true);
+        let host = WatchingToolHost::default();
+        session.chat_with_tools("go", &host).await.unwrap();
+
+        assert!(host.executed.lock().unwrap().is_empty());
+        assert_eq!(
+            *host.answered.lock().unwrap(),
+            [("read_file".to_string(), true)]
+        );
+    }
+
+    #[tokio::test]
+    async fn tool_calls_are_logged_before_they_run() {
+        let backend = ScriptedBackend::new(vec![
+            tool_call_response("call_1", "exec", json!({})),
+            tool_call_response("call_2", "write_file", json!({})),
+            text_response("done"),
+        ]);
+        let store = EventStore::in_memory().unwrap();
+        let mut session = Session::new(store.clone(), backend, Policy::default()).unwrap();
+        let id = session.id;
+        let host = FnToolHost(|_| {
+            let logged = store.load_events(id, Some("tool_call")).unwrap();
+            async move { Ok(json!({ "logged_calls": logged.len() })) }
+        });
+
+        session.chat_with_tools("go", &host).await.unwrap();
+
+        let outputs: Vec<_> = session
+            .messages
+            .iter()
+            .filter_map(|m| match m.parts.first() {
+                Some(Part::ToolResult(ToolResult::Success { output, .. })) => {
+                    Some(output["logged_calls"].clone())
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(outputs, [json!(1), json!(2)]);
+    }
+
+    #[tokio::test]
+    async fn transcript_renders_text_and_tool_parts() {
+        let backend = ScriptedBackend::new(vec![
+            tool_call_response("call_1", "search", json!({ "q": "rust" })),
+            text_response("found it"),
+        ]);
+        let store = EventStore::in_memory().unwrap();
+        let mut session = Session::new(store, backend, Policy::default())
+            .unwrap()
+            .dry_run(true);
+
+        session
+            .chat_with_tools("look it up", &PanickingToolHost)
+            .await
+            .unwrap();
+
+        assert_eq!(session.messages().len(), 4);
+        assert_eq!(
+            session.transcript(),
+            "user: look it up\n\
+             assistant: [tool call search] {\"q\":\"rust\"}\n\
+             tool: [tool result] {\"dry_run\":true}\n\
+             assistant: found it\n"
+        );
+    }
+
+    #[test]
+    fn truncate_output_marks_cut_bytes() {
+        assert_eq!(truncate_output(json!("short"), 8), json!("short"));
+        assert_eq!(
+            truncate_output(json!("abcdefghij"), 4),
+            json!("abcd...[truncated 6 of 10 bytes]")
+        );
+        // A cut never splits a character.
+        assert_eq!(
+            truncate_output(json!("aé"), 2),
+            json!("a...[truncated 2 of 3 bytes]")
+        );
+        assert_eq!(truncate_output(json!({ "a": 1 }), 8), json!({ "a": 1 }));
+        assert_eq!(
+            truncate_output(json!({ "a": 12345 }), 5),
+            json!("{\"a\":...[truncated 6 of 11 bytes]")
+        );
+    }
+
+    #[tokio::test]
+    async fn oversized_tool_result_is_truncated_but_logged_in_full() {
+        let backend = ScriptedBackend::new(vec![
+            tool_call_response("call_1", "read_file", json!({})),
+            text_response("done"),
+        ]);
+        let store = EventStore::in_memory().unwrap();
+        let mut session = Session::new(store, backend, Policy::default())
+            .unwrap()
+            .max_tool_result_bytes(10);
+        let output = json!("x".repeat(100));
+
+        session
+            .chat_with_tools("read it", &FixedToolHost(output.clone()))
+            .await
+            .unwrap();
+
+        let Part::ToolResult(ToolResult::Success { output: seen, .. }) =
+            &session.messages()[2].parts[0]
+        else {
+            panic!("expected a tool result");
+        };
+        assert_eq!(
+            *seen,
+            json!(format!("{}...[truncated 90 of 100 bytes]", "x".repeat(10)))
+        );
+
+        let results = session
+            .store
+            .load_events(session.id, Some("tool_result"))
+            .unwrap();
+        assert!(matches!(
+            &results[0].kind,
+            EventKind::ToolResult { output: logged, .. } if *logged == output
+        ));
+    }
+
+    #[tokio::test]
+    async fn tool_specs_are_offered_natively_on_every_step() {
+        let host = EchoToolHost::offering(&["echo"]);
+        let backend = ScriptedBackend::new(vec![
+            tool_call_response("call_1", "echo", json!({ "x": 1 })),
+            text_response("done"),
+        ]);
+        let store = EventStore::in_memory().unwrap();
+        let mut session = Session::new(store, backend, Policy::default()).unwrap();
+
+        session.chat_with_tools("go", &host).await.unwrap();
+
+        assert_eq!(
+            *session.backend.offered_tools.lock().unwrap(),
+            [vec!["echo".to_string()], vec!["echo".to_string()]]
+        );
+        let Part::ToolResult(ToolResult::Success { output, .. }) = &session.messages()[2].parts[0]
+        else {
+            panic!("expected a tool result");
+        };
+        assert_eq!(*output, json!({ "x": 1 }));
+    }
+
+    #[tokio::test]
+    async fn attached_tool_host_is_used_by_chat_and_shut_down() {
+        let host = EchoToolHost::offering(&["search"]);
+        let shut_down = host.shut_down.clone();
+        let backend = ScriptedBackend::new(vec![
+            tool_call_response("call_1", "search", json!({})),
+            text_response("done"),
+        ]);
+        let store = EventStore::in_memory().unwrap();
+        let session = Session::new(store, backend, Policy::default()).unwrap();
+        assert!(session.tool_specs().is_empty());
+        let mut session = session.with_tool_host(host);
+        assert_eq!(session.tool_specs()[0].name, "search");
+
+        let (reply, _) = session.chat("look it up").await.unwrap();
+        assert_eq!(reply, "done");
+        assert_eq!(session.tool_host().specs()[0].name, "search");
+        assert_eq!(
+            *session.backend.offered_tools.lock().unwrap(),
+            [vec!["search".to_string()], vec!["search".to_string()]]
+        );
+
+        let outcome = session.end_and_shutdown().await.unwrap();
+        assert_eq!(outcome.turns, 1);
+        assert!(shut_down.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn title_and_tags_are_recorded_and_restored() {
+        let backend = ScriptedBackend::new(vec![text_response("one"), text_response("two")]);
+        let store = EventStore::in_memory().unwrap();
+        let mut session = Session::new(store.clone(), backend, Policy::default())
+            .unwrap()
+            .auto_title(AutoTitle::FirstMessage)
+            .checkpoint(true);
+
+        session
+            .chat("\n  Why does the parser reject tabs?\nMore detail")
+            .await
+            .unwrap();
+        assert_eq!(session.title(), Some("Why does the parser reject tabs?"));
+        session.chat("and spaces?").await.unwrap();
+        assert_eq!(session.title(), Some("Why does the parser reject tabs?"));
+        session.set_tags(vec!["parser".into()]).unwrap();
+
+        let summary = &store.list_sessions().unwrap()[0];
+        assert_eq!(
+            summary.title.as_deref(),
+            Some("Why does the parser reject tabs?")
+        );
+        assert_eq!(summary.tags, ["parser"]);
+
+        let resumed = Session::resume(
+            store,
+            ScriptedBackend::new(Vec::new()),
+            Policy::default(),
+            session.id,
+        )
+        .unwrap();
+        assert_eq!(resumed.title(), session.title());
+        assert_eq!(resumed.tags(), ["parser"]);
+    }
+
+    #[tokio::test]
+    async fn model_title_is_requested_once_with_fallback() {
+        let backend = ScriptedBackend::new(vec![
+            text_response("Tabs are rejected on purpose."),
+            text_response("\"Parser tab handling\""),
+            text_response("Spaces are fine."),
+        ]);
+        let store = EventStore::in_memory().unwrap();
+        let mut session = Session::new(store, backend, Policy::default())
+            .unwrap()
+            .auto_title(AutoTitle::Model);
+
+        session
+            .chat("Why does the parser reject tabs?")
+            .await
+            .unwrap();
+        assert_eq!(session.title(), Some("Parser tab handling"));
+        session.chat("and spaces?").await.unwrap();
+        let systems = session.backend.systems.lock().unwrap().clone();
+        assert_eq!(systems, [None, Some(TITLE_PROMPT.to_string()), None]);
+
+        // With no response left for the title call, the first message is used.
+        let backend = ScriptedBackend::new(vec![text_response("ok")]);
+        let store = EventStore::in_memory().unwrap();
+        let mut session = Session::new(store, backend, Policy::default())
+            .unwrap()
+            .auto_title(AutoTitle::Model);
+        session.chat("Hello there").await.unwrap();
+        assert_eq!(session.title(), Some("Hello there"));
+    }
+
+    #[test]
+    fn title_from_cuts_long_lines() {
+        assert_eq!(title_from("  \n "), None);
+        let long = "word ".repeat(20);
+        let title = title_from(&long).unwrap();
+        assert_eq!(title.chars().count(), MAX_AUTO_TITLE_CHARS);
+        assert!(title.ends_with("word…"));
+    }
+
+    #[tokio::test]
+    async fn request_log_records_what_was_sent() {
+        let backend = ScriptedBackend::new(vec![
+            tool_call_response("call_1", "search", json!({})),
+            text_response("done"),
+        ]);
+        let store = EventStore::in_memory().unwrap();
+        let mut session = Session::new(store, backend, Policy::default())
+            .unwrap()
+            .system_prompt("Be brief.")
+            .log_requests(RequestLog::Summary);
+
+        session
+            .chat_with_tools("go", &FixedToolHost(json!("hit")))
+            .await
+            .unwrap();
+
+        let requests: Vec<_> = session
+            .store
+            .load_events(session.id, Some("model_request"))
+            .unwrap()
+            .into_iter()
+            .map(|event| match event.kind {
+                EventKind::ModelRequest {
+                    system,
+                    message_count,
+                    request,
+                    ..
+                } => (system, message_count, request.is_some()),
+                other => panic!("unexpected {other:?}"),
+            })
+            .collect();
+        let system = Some("Be brief.".to_string());
+        assert_eq!(requests, [(system.clone(), 1, false), (system, 3, false)]);
+
+        session = session.log_requests(RequestLog::Full);
+        session.chat("again").await.unwrap_err();
+        let events = session
+            .store
+            .load_events(session.id, Some("model_request"))
+            .unwrap();
+        let EventKind::ModelRequest {
+            request: Some(request),
+            ..
+        } = &events[2].kind
+        else {
+            panic!("expected a full request");
+        };
+        assert_eq!(request["messages"].as_array().unwrap().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn full_request_log_keeps_secrets_masked() {
+        let backend = ScriptedBackend::new(vec![
+            tool_call_response("call_1", "read_secret", json!({ "name": "TOKEN" })),
+            text_response("done"),
+        ]);
+        let store = EventStore::in_memory().unwrap();
+        let mut session = Session::new(store, backend, Policy::default())
+            .unwrap()
+            .log_requests(RequestLog::Full);
+
+        session
+            .chat_with_tools("read it", &SecretToolHost)
+            .await
+            .unwrap();
+
+        let requests = session
+            .store
+            .load_events(session.id, Some("model_request"))
+            .unwrap();
+        // The second request carries the tool result.
+        assert_eq!(requests.len(), 2);
+        for event in requests {
+            let json = serde_json::to_string(&event).unwrap();
+            assert!(!json.contains("hunter2"), "{json}");
+        }
+    }
+
+    #[tokio::test]
+    async fn system_prompt_is_sent_with_each_call() {
+        let backend = ScriptedBackend::new(vec![text_response("one"), text_response("two")]);
+        let store = EventStore::in_memory().unwrap();
+        let mut session = Session::new(store, backend, Policy::default())
+            .unwrap()
+            .system_prompt("Be brief.");
+
+        session.chat("hi").await.unwrap();
+        session.set_system_prompt(None);
+        session.chat("again").await.unwrap();
+
+        assert_eq!(
+            *session.backend.systems.lock().unwrap(),
+            [Some("Be brief.".to_string()), None]
+        );
+    }
+
+    #[tokio::test]
+    async fn checkpoints_mark_turn_progress() {
+        let backend = ScriptedBackend::new(vec![
+            tool_call_response("call_1", "search", json!({})),
+            text_response("done"),
+        ]);
+        let store = EventStore::in_memory().unwrap();
+        let mut session = Session::new(store, backend, Policy::default())
+            .unwrap()
+            .checkpoint(true);
+
+        session
+            .chat_with_tools("go", &FixedToolHost(json!("hit")))
+            .await
+            .unwrap();
+
+        let checkpoints: Vec<_> = session
+            .store
+            .load_events(session.id, Some("checkpoint"))
+            .unwrap()
+            .into_iter()
+            .map(|event| match event.kind {
+                EventKind::Checkpoint {
+                    messages,
+                    turn_complete,
+                } => (messages.as_array().unwrap().len(), turn_complete),
+                other => panic!("unexpected {other:?}"),
+            })
+            .collect();
+        // User message, tool call, tool result, final answer.
+        assert_eq!(checkpoints, [(1, false), (2, false), (3, false), (4, true)]);
+    }
+
+    #[tokio::test]
+    async fn checkpoints_keep_secrets_masked() {
+        let backend = ScriptedBackend::new(vec![
+            tool_call_response("call_1", "read_secret", json!({ "name": "TOKEN" })),
+            text_response("done"),
+        ]);
+        let store = EventStore::in_memory().unwrap();
+        let mut session = Session::new(store, backend, Policy::default())
+            .unwrap()
+            .checkpoint(true);
+
+        session
+            .chat_with_tools("read it", &SecretToolHost)
+            .await
+            .unwrap();
+
+        let checkpoints = session
+            .store
+            .load_events(session.id, Some("checkpoint"))
+            .unwrap();
+        assert!(!checkpoints.is_empty());
+        for event in checkpoints {
+            let json = serde_json::to_string(&event).unwrap();
+            assert!(!json.contains("hunter2"), "{json}");
+        }
+        // The model itself still got the value.
+        assert!(matches!(
+            session.messages[2].parts.first(),
+            Some(Part::ToolResult(ToolResult::Success { output, .. }))
+                if *output == json!({ "value": "hunter2" })
+        ));
+    }
+
+    #[tokio::test]
+    async fn resume_runs_unanswered_tool_calls() {
+        // A run that died while its tool call was executing.
+        let store = EventStore::in_memory().unwrap();
+        let mut crashed = Session::new(
+            store.clone(),
+            ScriptedBackend::new(Vec::new()),
+            Policy::default(),
+        )
+        .unwrap()
+        .checkpoint(true);
+        let id = crashed.id;
+        crashed.messages.push(Message {
+            role: Role::User,
+            parts: vec![Part::Text("go".into())],
+        });
+        crashed
+            .messages
+            .push(tool_call_response("call_1", "search", json!({})).message);
+        crashed.save_checkpoint(false).unwrap();
+
+        let backend = ScriptedBackend::new(vec![text_response("done")]);
+        let mut session = Session::resume(store, backend, Policy::default(), id).unwrap();
+        assert!(session.has_pending_turn());
+
+        let (text, _) = session
+            .resume_turn(&FixedToolHost(json!("hit")))
+            .await
+            .unwrap();
+        assert_eq!(text, "done");
+        assert!(!session.has_pending_turn());
+        assert_eq!(
+            session.transcript(),
+            "user: go\n\
+             assistant: [tool call search] {}\n\
+             tool: [tool result] \"hit\"\n\
+             assistant: done\n"
+        );
+        assert!(matches!(
+            session.resume_turn(&EmptyToolHost).await,
+            Err(Error::InvalidState(_))
+        ));
+    }
+
+    #[test]
+    fn resume_requires_a_checkpoint() {
+        let store = EventStore::in_memory().unwrap();
+        let session = session_with_policy(Policy::default());
+        assert!(matches!(
+            Session::resume(
+                store.clone(),
+                ScriptedBackend::new(Vec::new()),
+                Policy::default(),
+                session.id
+            ),
+            Err(Error::SessionNotFound(_))
+        ));
+
+        let id = Session::new(
+            store.clone(),
+            ScriptedBackend::new(Vec::new()),
+            Policy::default(),
+        )
+        .unwrap()
+        .id;
+        assert!(matches!(
+            Session::resume(
+                store,
+                ScriptedBackend::new(Vec::new()),
+                Policy::default(),
+                id
+            ),
+            Err(Error::InvalidState(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn import_messages_seeds_history_and_events() {
+        let backend = ScriptedBackend::new(vec![text_response("continued")]);
+        let store = EventStore::in_memory().unwrap();
+        let mut session = Session::new(store, backend, Policy::default()).unwrap();
+
+        let mut imported = vec![Message {
+            role: Role::User,
+            parts: vec![Part::Text("look it up".into())],
+        }];
+        imported.extend([
+            tool_call_response("call_1", "search", json!({ "q": "rust" })).message,
+            Message {
+                role: Role::Tool,
+                parts: vec![
+                    Part::ToolResult(ToolResult::Success {
+                        tool_call_id: "call_1".into(),
+                        output: json!("found"),
+                    }),
+                    Part::Text("search finished".into()),
+                ],
+            },
+            text_response("here it is").message,
+        ]);
+        session.import_messages(imported).unwrap();
+        session.chat("thanks").await.unwrap();
+
+        assert_eq!(
+            session.transcript(),
+            "user: look it up\n\
+             assistant: [tool call search] {\"q\":\"rust\"}\n\
+             tool: [tool result] \"found\"\n\
+             tool: search finished\n\
+             assistant: here it is\n\
+             user: thanks\n\
+             assistant: continued\n"
+        );
+        let kinds: Vec<_> = session
+            .store
+            .load_session(session.id)
+            .unwrap()
+            .iter()
+            .map(|event| event.kind.name())
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                "session_start",
+                "message",
+                "tool_call",
+                "tool_result",
+                "message",
+                "message",
+                "usage",
+                "message",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn push_message_adds_history_without_calling_the_backend() {
+        let backend = ScriptedBackend::new(vec![text_response("sure")]);
+        let store = EventStore::in_memory().unwrap();
+        let mut session = Session::new(store, backend, Policy::default()).unwrap();
+
+        session
+            .push_message(Message {
+                role: Role::Assistant,
+                parts: vec![Part::Text("Hi! What can I do?".into())],
+            })
+            .unwrap();
+        assert!(session.backend.systems.lock().unwrap().is_empty());
+
+        session.chat("fix the build").await.unwrap();
+        assert_eq!(
+            session.transcript(),
+            "assistant: Hi! What can I do?\n\
+             user: fix the build\n\
+             assistant: sure\n"
+        );
+        let events = session.store.load_session(session.id).unwrap();
+        assert!(matches!(
+            &events[1].kind,
+            EventKind::Message { role: StorageRole::Assistant, content } if content == "Hi! What can I do?"
+        ));
+
+        // Same-role messages only warn.
+        session
+            .push_message(Message {
+                role: Role::Assistant,
+                parts: vec![Part::Text("Anything else?".into())],
+            })
+            .unwrap();
+        assert_eq!(session.messages().len(), 4);
+    }
+
+    #[test]
+    fn import_messages_rejects_unmatched_tool_result() {
+        let mut session = session_with_policy(Policy::default());
+        let err = session
+            .import_messages(vec![Message {
+                role: Role::Tool,
+                parts: vec![Part::ToolResult(ToolResult::Success {
+                    tool_call_id: "missing".into(),
+                    output: Value::Null,
+                })],
+            }])
+            .unwrap_err();
+
+        assert!(matches!(err, Error::InvalidState(_)));
+        assert!(session.messages().is_empty());
+    }
+
+    #[tokio::test]
+    async fn clear_starts_a_new_conversation() {
+        let backend = ScriptedBackend::new(vec![text_response("one"), text_response("two")]);
+        let store = EventStore::in_memory().unwrap();
+        let mut session = Session::new(store, backend, Policy::default())
+            .unwrap()
+            .checkpoint(true);
+
+        session.chat("first").await.unwrap();
+        session.clear().unwrap();
+        assert!(session.messages().is_empty());
+        assert!(!session.has_pending_turn());
+
+        session.chat("second").await.unwrap();
+        assert_eq!(session.transcript(), "user: second\nassistant: two\n");
+        assert_eq!(session.backend.sent_messages.lock().unwrap()[1].len(), 1);
+        let cleared = session
+            .store
+            .load_events(session.id, Some("cleared"))
+            .unwrap();
+        assert_eq!(cleared.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn regenerate_last_drops_previous_response() {
+        let backend = ScriptedBackend::new(vec![text_response("first"), text_response("second")]);
+        let store = EventStore::in_memory().unwrap();
+        let mut session = Session::new(store, backend, Policy::default()).unwrap();
+
+        session.chat("hi").await.unwrap();
+        let (text, _) = session.regenerate_last().await.unwrap();
+
+        assert_eq!(text, "second");
+        assert_eq!(session.transcript(), "user: hi\nassistant: second\n");
+        let regenerated = session
+            .store
+            .load_events(session.id, Some("regenerated"))
+            .unwrap();
+        assert_eq!(regenerated.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn edit_last_user_replaces_turn_and_tool_exchange() {
+        let backend = ScriptedBackend::new(vec![
+            tool_call_response("call_1", "search", json!({})),
+            text_response("first"),
+            text_response("edited answer"),
+        ]);
+        let store = EventStore::in_memory().unwrap();
+        let mut session = Session::new(store, backend, Policy::default())
+            .unwrap()
+            .dry_run(true);
+
+        session
+            .chat_with_tools("hi", &PanickingToolHost)
+            .await
+            .unwrap();
+        let (text, _) = session.edit_last_user("hello").await.unwrap();
+
+        assert_eq!(text, "edited answer");
+        assert_eq!(
+            session.transcript(),
+            "user: hello\nassistant: edited answer\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn regenerate_last_without_user_turn_returns_error() {
+        let mut session = session_with_policy(Policy::default());
+        let err = session.regenerate_last().await.unwrap_err();
+        assert!(matches!(err, Error::InvalidState(_)));
+    }
+
+    #[tokio::test]
+    async fn summarize_replaces_old_turns_over_threshold() {
+        let mut big = text_response("second answer");
+        big.usage.input_tokens = 1_000;
+        let backend = ScriptedBackend::new(vec![
+            text_response("first answer"),
+            big,
+            text_response("short summary"),
+            text_response("third answer"),
+        ]);
+        let store = EventStore::in_memory().unwrap();
+        let mut session = Session::new(store, backend, Policy::default())
+            .unwrap()
+            .summarize_with(SummarizingTrimmer::new(500).keep_recent(2));
+
+        session.chat("one").await.unwrap();
+        session.chat("two").await.unwrap();
+        session.chat("three").await.unwrap();
+
+        assert_eq!(
+            session.transcript(),
+            "user: [Summary of earlier conversation]\nshort summary\n\
+             user: two\n\
+             assistant: second answer\n\
+             user: three\n\
+             assistant: third answer\n"
+        );
+        // The event log still holds every original message.
+        let messages = session
+            .store
+            .load_events(session.id, Some("message"))
+            .unwrap();
+        assert_eq!(messages.len(), 6);
+    }
+
+    #[tokio::test]
+    async fn summarize_uses_backend_estimate_without_reported_usage() {
+        let long_answer = "x".repeat(200);
+        let backend = ScriptedBackend::new(vec![
+            text_response("first answer"),
+            text_response(&long_answer),
+            text_response("short summary"),
+            text_response("third answer"),
+        ]);
+        let store = EventStore::in_memory().unwrap();
+        let mut session = Session::new(store, backend, Policy::default())
+            .unwrap()
+            .summarize_with(SummarizingTrimmer::new(20).keep_recent(2));
+
+        session.chat("one").await.unwrap();
+        session.chat("two").await.unwrap();
+        session.chat("three").await.unwrap();
+
+        assert!(
+            session
+                .transcript()
+                .starts_with("user: [Summary of earlier conversation]\nshort summary\n"),
+            "{}",
+            session.transcript()
+        );
+    }
+
+    #[tokio::test]
+    async fn summarize_survives_a_failed_estimate() {
+        struct UncountedBackend(ScriptedBackend);
+
+        impl Backend for UncountedBackend {
+            async fn call(
+                &self,
+                request: ModelRequest<'_>,
+            ) -> std::result::Result<ModelResponse, ModelError> {
+                self.0.call(request).await
+            }
+
+            fn model_info(&self) -> ModelInfo {
+                self.0.model_info()
+            }
+
+            fn name(&self) -> &str {
+                "uncounted"
+            }
+
+            async fn estimate_tokens(
+                &self,
+                _request: &ModelRequest<'_>,
+            ) -> std::result::Result<u32, ModelError> {
+                Err(ModelError::Api {
+                    kind: ApiErrorKind::Other,
+                    message: "count unavailable".into(),
+                })
+            }
+        }
+
+        let long_answer = "x".repeat(200);
+        let backend = UncountedBackend(ScriptedBackend::new(vec![
+            text_response("first answer"),
+            text_response(&long_answer),
+            text_response("short summary"),
+            text_response("third answer"),
+        ]));
+        let store = EventStore::in_memory().unwrap();
+        let mut session = Session::new(store, backend, Policy::default())
+            .unwrap()
+            .summarize_with(SummarizingTrimmer::new(20).keep_recent(2));
+
+        session.chat("one").await.unwrap();
+        session.chat("two").await.unwrap();
+        session.chat("three").await.unwrap();
+
+        assert!(
+            session
+                .transcript()
+                .starts_with("user: [Summary of earlier conversation]\nshort summary\n"),
+            "{}",
+            session.transcript()
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_turn_records_error_event() {
+        let mut session = session_with_policy(Policy::default());
+
+        assert!(session.chat("hi").await.is_err());
+
+        let errors = session
+            .store
+            .load_events(session.id, Some("error"))
+            .unwrap();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            &errors[0].kind,
+            EventKind::Error { source, message }
+                if source == "api" && message.contains("no scripted response")
+        ));
+    }
+
+    #[tokio::test]
+    async fn chat_records_usage_events() {
+        let mut response = text_response("hello");
+        response.usage = Usage {
+            input_tokens: 12,
+            output_tokens: 3,
+        };
+        let store = EventStore::in_memory().unwrap();
+        let backend = ScriptedBackend::new(vec![response]);
+        let mut session = Session::new(store, backend, Policy::default()).unwrap();
+
+        session.chat("hi").await.unwrap();
+
+        let usage = session
+            .store
+            .load_events(session.id, Some("usage"))
+            .unwrap();
+        assert_eq!(usage.len(), 1);
+        assert!(matches!(
+            &usage[0].kind,
+            EventKind::Usage { model, input_tokens: 12, output_tokens: 3 }
+                if model == "scripted-model"
+        ));
+    }
+
+    #[tokio::test]
+    async fn failed_turn_keeps_usage_of_billed_responses() {
+        let mut response = tool_call_response("call_1", "search", json!({}));
+        response.usage = Usage {
+            input_tokens: 20,
+            output_tokens: 5,
+        };
+        let store = EventStore::in_memory().unwrap();
+        // The call after the tool round fails: the script has run out.
+        let backend = ScriptedBackend::new(vec![response]);
+        let mut session = Session::new(store, backend, Policy::default()).unwrap();
+
+        let err = session
+            .chat_with_tools("go", &FixedToolHost(json!("hit")))
+            .await
+            .unwrap_err();
+
+        // The backend's error arrives with its structure intact.
+        assert!(matches!(err, Error::Model(ModelError::Api { .. })));
+        assert_eq!(session.usage().input_tokens, 20);
+        assert_eq!(session.usage().output_tokens, 5);
+    }
+
+    #[tokio::test]
+    async fn end_reports_usage_and_answered_turns() {
+        let mut response = text_response("hello");
+        response.usage = Usage {
+            input_tokens: 12,
+            output_tokens: 3,
+        };
+        let store = EventStore::in_memory().unwrap();
+        let backend = ScriptedBackend::new(vec![response]);
+        let mut session = Session::new(store.clone(), backend, Policy::default()).unwrap();
+        let id = session.id;
+
+        session.chat("hi").await.unwrap();
+        // The script is exhausted, so this turn fails and is not counted.
+        session.chat("again").await.unwrap_err();
+        let outcome = session.end().unwrap();
+
+        assert_eq!(outcome.id, id);
+        assert_eq!(outcome.turns, 1);
+        assert_eq!(outcome.usage.input_tokens, 12);
+        assert_eq!(outcome.usage.output_tokens, 3);
+        assert!(store.list_sessions().unwrap()[0].ended_at.is_some());
+    }
 }
@@ -0,0 +1,60 @@
+//! Forwarding of session events beyond the event store.
+
+use std::io::{self, Write};
+use std::sync::Arc;
+use storage::Event;
+
+/// Receives every event a session records, after the store has it.
+///
+/// Implement this to feed events into an existing pipeline, such as a
+/// message queue or an HTTP collector. `record` runs on the session's
+/// task, so a slow sink should hand events off rather than block.
+pub trait EventSink: Send + Sync {
+    fn record(&self, event: &Event);
+}
+
+impl<S: EventSink + ?Sized> EventSink for Arc<S> {
+    fn record(&self, event: &Event) {
+        (**self).record(event);
+    }
+}
+
+/// Writes each event to stdout as one line of JSON.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdoutJsonSink;
+
+impl EventSink for StdoutJsonSink {
+    fn record(&self, event: &Event) {
+        // A closed stdout is no reason to fail the session.
+        let _ = write_json_line(&mut io::stdout().lock(), event);
+    }
+}
+
+fn write_json_line(out: &mut impl Write, event: &Event) -> io::Result<()> {
+    serde_json::to_writer(&mut *out, event)?;
+    out.write_all(b"\n")?;
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use storage::{Role, SessionId};
+
+    #[test]
+    fn writes_one_json_object_per_line() {
+        let id = SessionId::new();
+        let mut out = Vec::new();
+        write_json_line(&mut out, &Event::message(id, Role::User, "hi")).unwrap();
+        write_json_line(&mut out, &Event::message(id, Role::Assistant, "hello")).unwrap();
+
+        let lines: Vec<Event> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1].session_id, id);
+        assert_eq!(lines[1].kind.name(), "message");
+    }
+}
@@ -0,0 +1,142 @@
+//! Conversation summarization to stay under context limits.
+
+use crate::model::{Message, Part, Role};
+use crate::session::render_transcript;
+
+const DEFAULT_PROMPT: &str = "Summarize the conversation below so it can replace the original \
+    messages. Keep facts, decisions, open questions, and tool results that later turns may \
+    rely on. Be concise.";
+
+const SUMMARY_HEADER: &str = "[Summary of earlier conversation]";
+
+/// Compresses old turns into a summary once the context grows too large.
+///
+/// Only the in-memory conversation is rewritten; the event log keeps every
+/// original message.
+#[derive(Debug, Clone)]
+pub struct SummarizingTrimmer {
+    threshold_tokens: u32,
+    keep_recent: usize,
+    prompt: String,
+}
+
+impl SummarizingTrimmer {
+    /// Summarize once a request's context exceeds `threshold_tokens`.
+    pub fn new(threshold_tokens: u32) -> Self {
+        Self {
+            threshold_tokens,
+            keep_recent: 4,
+            prompt: DEFAULT_PROMPT.to_string(),
+        }
+    }
+
+    /// Minimum number of recent messages kept verbatim.
+    pub fn keep_recent(mut self, keep_recent: usize) -> Self {
+        self.keep_recent = keep_recent;
+        self
+    }
+
+    /// Instruction sent to the model along with the messages to summarize.
+    pub fn prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.prompt = prompt.into();
+        self
+    }
+
+    pub(crate) fn should_summarize(&self, context_tokens: u32) -> bool {
+        context_tokens > self.threshold_tokens
+    }
+
+    /// Number of leading messages to replace with a summary, if any.
+    ///
+    /// The cut always lands on a user turn so tool calls stay paired with
+    /// their results.
+    pub(crate) fn split_point(&self, messages: &[Message]) -> Option<usize> {
+        let latest = messages.len().checked_sub(self.keep_recent)?;
+        (1..=latest)
+            .rev()
+            .find(|&i| i < messages.len() && is_user_turn(&messages[i]))
+    }
+
+    /// The request asking the model to summarize `messages`.
+    ///
+    /// Messages are flattened to text so tool blocks need no tool specs.
+    pub(crate) fn summary_request(&self, messages: &[Message]) -> Vec<Message> {
+        let prompt = &self.prompt;
+        let transcript = render_transcript(messages);
+        vec![Message {
+            role: Role::User,
+            parts: vec![Part::Text(format!("{prompt}\n\n{transcript}"))],
+        }]
+    }
+
+    pub(crate) fn summary_message(summary: &str) -> Message {
+        Message {
+            role: Role::User,
+            parts: vec![Part::Text(format!("{SUMMARY_HEADER}\n{summary}"))],
+        }
+    }
+}
+
+fn is_user_turn(message: &Message) -> bool {
+    message.role == Role::User && message.parts.iter().any(|p| matches!(p, Part::Text(_)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::{ToolCall, ToolResult};
+    use serde_json::Value;
+
+    fn text(role: Role, text: &str) -> Message {
+        Message {
+            role,
+            parts: vec![Part::Text(text.into())],
+        }
+    }
+
+    #[test]
+    fn split_point_keeps_recent_messages() {
+        let messages = vec![
+            text(Role::User, "1"),
+            text(Role::Assistant, "2"),
+            text(Role::User, "3"),
+            text(Role::Assistant, "4"),
+            text(Role::User, "5"),
+            text(Role::Assistant, "6"),
+        ];
+        let trimmer = SummarizingTrimmer::new(0).keep_recent(2);
+        assert_eq!(trimmer.split_point(&messages), Some(4));
+    }
+
+    #[test]
+    fn split_point_skips_tool_result_messages() {
+        let messages = vec![
+            text(Role::User, "1"),
+            Message {
+                role: Role::Assistant,
+                parts: vec![Part::ToolCall(ToolCall {
+                    id: "a".into(),
+                    name: "t".into(),
+                    input: Value::Null,
+                })],
+            },
+            Message {
+                role: Role::Tool,
+                parts: vec![Part::ToolResult(ToolResult::Success {
+                    tool_call_id: "a".into(),
+                    output: Value::Null,
+                })],
+            },
+            text(Role::Assistant, "done"),
+        ];
+        let trimmer = SummarizingTrimmer::new(0).keep_recent(2);
+        assert_eq!(trimmer.split_point(&messages), None);
+    }
+
+    #[test]
+    fn split_point_short_conversation_returns_none() {
+        let messages = vec![text(Role::User, "1"), text(Role::Assistant, "2")];
+        let trimmer = SummarizingTrimmer::new(0).keep_recent(4);
+        assert_eq!(trimmer.split_point(&messages), None);
+    }
+}
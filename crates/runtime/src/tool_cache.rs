@@ -0,0 +1,228 @@
+//! Reuse of tool results within a session.
+
+use crate::tools::ToolCall;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+const DEFAULT_TTL: Duration = Duration::from_secs(300);
+const DEFAULT_MAX_ENTRIES: usize = 256;
+
+/// Remembers the results of tools marked cacheable, so a repeated call
+/// with the same arguments is answered without running the tool again.
+///
+/// Only successful results are kept. Mark only tools without side effects,
+/// such as file reads; a call to any other tool clears the cache, since it
+/// may have changed what the cached tools would return.
+#[derive(Debug, Clone)]
+pub struct ToolCache {
+    tools: HashSet<String>,
+    ttl: Duration,
+    max_entries: usize,
+    /// Results keyed by tool name and canonical JSON arguments.
+    entries: HashMap<(String, String), Entry>,
+    /// Insertion counter, so eviction order does not depend on clock resolution.
+    next_seq: u64,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    seq: u64,
+    stored: Instant,
+    output: Value,
+}
+
+impl ToolCache {
+    /// Cache the results of the tools named in `tools`.
+    pub fn new<I, S>(tools: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            tools: tools.into_iter().map(Into::into).collect(),
+            ttl: DEFAULT_TTL,
+            max_entries: DEFAULT_MAX_ENTRIES,
+            entries: HashMap::new(),
+            next_seq: 0,
+        }
+    }
+
+    /// How long a result stays usable (default 5 minutes).
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Most results kept at once (default 256); the oldest goes first.
+    pub fn max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// Whether results of the tool named `name` are cached.
+    pub fn is_cacheable(&self, name: &str) -> bool {
+        self.tools.contains(name)
+    }
+
+    /// Number of results currently held, including expired ones.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The stored result for `call`, if it has not expired.
+    pub(crate) fn get(&mut self, call: &ToolCall) -> Option<Value> {
+        let key = cache_key(call);
+        let entry = self.entries.get(&key)?;
+        if entry.stored.elapsed() < self.ttl {
+            return Some(entry.output.clone());
+        }
+        self.entries.remove(&key);
+        None
+    }
+
+    /// Record the outcome of running `call`: keep a successful result of a
+    /// cacheable tool, or forget everything after any other tool.
+    pub(crate) fn record(&mut self, call: &ToolCall, output: Option<&Value>) {
+        if !self.is_cacheable(&call.name) {
+            self.entries.clear();
+            return;
+        }
+        let Some(output) = output else { return };
+        if self.max_entries == 0 {
+            return;
+        }
+
+        let ttl = self.ttl;
+        self.entries.retain(|_, entry| entry.stored.elapsed() < ttl);
+        if self.entries.len() >= self.max_entries
+            && let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.seq)
+                .map(|(key, _)| key.clone())
+        {
+            self.entries.remove(&oldest);
+        }
+        self.next_seq += 1;
+        self.entries.insert(
+            cache_key(call),
+            Entry {
+                seq: self.next_seq,
+                stored: Instant::now(),
+                output: output.clone(),
+            },
+        );
+    }
+}
+
+fn cache_key(call: &ToolCall) -> (String, String) {
+    let mut args = String::new();
+    write_canonical(&call.input, &mut args);
+    (call.name.clone(), args)
+}
+
+/// Write `value` as JSON with object keys sorted, so arguments that differ
+/// only in key order share a cache entry.
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<_> = map.keys().collect();
+            keys.sort();
+            out.push('{');
+            for (index, key) in keys.into_iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(&map[key], out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        other => out.push_str(&other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn call(name: &str, input: Value) -> ToolCall {
+        ToolCall {
+            id: "call_1".into(),
+            name: name.into(),
+            input,
+        }
+    }
+
+    #[test]
+    fn reuses_results_of_cacheable_tools() {
+        let mut cache = ToolCache::new(["read_file"]);
+        let read = call("read_file", json!({ "path": "a.txt", "limit": 10 }));
+        assert_eq!(cache.get(&read), None);
+
+        cache.record(&read, Some(&json!("contents")));
+        let reordered = call("read_file", json!({ "limit": 10, "path": "a.txt" }));
+        assert_eq!(cache.get(&reordered), Some(json!("contents")));
+        assert_eq!(
+            cache.get(&call("read_file", json!({ "path": "b.txt" }))),
+            None
+        );
+
+        // Failures are not kept.
+        let missing = call("read_file", json!({ "path": "missing" }));
+        cache.record(&missing, None);
+        assert_eq!(cache.get(&missing), None);
+    }
+
+    #[test]
+    fn other_tools_clear_the_cache() {
+        let mut cache = ToolCache::new(["read_file"]);
+        let read = call("read_file", json!({ "path": "a.txt" }));
+        cache.record(&read, Some(&json!("old")));
+
+        cache.record(
+            &call("write_file", json!({ "path": "a.txt" })),
+            Some(&json!({})),
+        );
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn expires_and_evicts_entries() {
+        let mut cache = ToolCache::new(["read_file"]).ttl(Duration::ZERO);
+        let read = call("read_file", json!({ "path": "a.txt" }));
+        cache.record(&read, Some(&json!("contents")));
+        assert_eq!(cache.get(&read), None);
+
+        let mut cache = ToolCache::new(["read_file"]).max_entries(2);
+        for path in ["a", "b", "c"] {
+            cache.record(
+                &call("read_file", json!({ "path": path })),
+                Some(&json!(path)),
+            );
+        }
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&call("read_file", json!({ "path": "a" }))), None);
+        assert_eq!(
+            cache.get(&call("read_file", json!({ "path": "c" }))),
+            Some(json!("c"))
+        );
+    }
+}
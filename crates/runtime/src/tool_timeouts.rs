@@ -0,0 +1,90 @@
+//! Time limits for tool calls.
+
+use std::time::Duration;
+
+/// How long each tool may run before its call fails with
+/// [`ToolError::Timeout`](crate::ToolError::Timeout).
+///
+/// Limits are set by tool name. A pattern ending in `*` matches any name
+/// with that prefix (`"read_*"` covers `read_file` and `read_secret`). An
+/// exact name wins over patterns, and a longer pattern over a shorter
+/// one. Tools nothing matches get the default, if there is one.
+///
+/// A timed-out call is abandoned: the built-in `exec` tool kills its
+/// program, but an MCP server is not told and may keep working on it.
+#[derive(Debug, Clone, Default)]
+pub struct ToolTimeouts {
+    default: Option<Duration>,
+    tools: Vec<(String, Duration)>,
+}
+
+impl ToolTimeouts {
+    /// No limits until some are added.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limit for tools without a limit of their own.
+    pub fn default_timeout(mut self, timeout: Duration) -> Self {
+        self.default = Some(timeout);
+        self
+    }
+
+    /// Limit for tools matching `pattern`, replacing any earlier limit
+    /// for the same pattern.
+    pub fn tool(mut self, pattern: impl Into<String>, timeout: Duration) -> Self {
+        let pattern = pattern.into();
+        self.tools.retain(|(existing, _)| *existing != pattern);
+        self.tools.push((pattern, timeout));
+        self
+    }
+
+    /// The limit for the tool named `name`, or `None` to let it run as long
+    /// as it takes.
+    pub fn get(&self, name: &str) -> Option<Duration> {
+        if let Some((_, timeout)) = self.tools.iter().find(|(pattern, _)| pattern == name) {
+            return Some(*timeout);
+        }
+        self.tools
+            .iter()
+            .filter_map(|(pattern, timeout)| {
+                let prefix = pattern.strip_suffix('*')?;
+                name.starts_with(prefix).then_some((prefix.len(), *timeout))
+            })
+            .max_by_key(|(len, _)| *len)
+            .map(|(_, timeout)| timeout)
+            .or(self.default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_the_most_specific_limit() {
+        let timeouts = ToolTimeouts::new()
+            .default_timeout(Duration::from_secs(30))
+            .tool("*", Duration::from_secs(60))
+            .tool("read_*", Duration::from_secs(5))
+            .tool("read_secret", Duration::from_secs(1))
+            .tool("run_tests", Duration::from_secs(600));
+
+        assert_eq!(timeouts.get("read_secret"), Some(Duration::from_secs(1)));
+        assert_eq!(timeouts.get("read_file"), Some(Duration::from_secs(5)));
+        assert_eq!(timeouts.get("run_tests"), Some(Duration::from_secs(600)));
+        assert_eq!(timeouts.get("exec"), Some(Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn falls_back_to_the_default() {
+        let timeouts = ToolTimeouts::new().tool("run_tests", Duration::from_secs(600));
+        assert_eq!(timeouts.get("read_file"), None);
+
+        let timeouts = timeouts
+            .default_timeout(Duration::from_secs(30))
+            .tool("run_tests", Duration::from_secs(900));
+        assert_eq!(timeouts.get("read_file"), Some(Duration::from_secs(30)));
+        assert_eq!(timeouts.get("run_tests"), Some(Duration::from_secs(900)));
+    }
+}
@@ -8,8 +8,12 @@ pub enum ToolError {
     NotFound(String),
     #[error("invalid input: {0}")]
     InvalidInput(String),
+    #[error("invalid output: {0}")]
+    InvalidOutput(String),
     #[error("capability denied: {0}")]
     CapabilityDenied(String),
+    #[error("rejected: {0}")]
+    Rejected(String),
     #[error("timeout after {0}ms")]
     Timeout(u64),
     #[error("execution failed: {0}")]
@@ -0,0 +1,573 @@
+//! Built-in file, shell, HTTP and secret tools.
+
+use super::{ToolCall, ToolError, ToolHost, ToolSpec};
+use policy::{CapabilityKind, CapabilityRequest, Decision, Policy};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::path::{Component, Path};
+use std::sync::{Mutex, PoisonError};
+use storage::{Event, EventKind, EventStore, SessionId};
+use tokio::process::Command;
+
+/// Placeholder recorded in the event log instead of secret values.
+const REDACTED: &str = "****";
+
+/// Most redirects `http_get` follows, matching reqwest's default.
+const MAX_REDIRECTS: usize = 10;
+
+/// Tool host providing `read_file`, `write_file`, `exec`, `http_get` and
+/// `read_secret` without an external MCP server.
+///
+/// Every call is checked against the policy as the matching capability
+/// (`fs_read`, `fs_write`, `exec`, `net_http`, `secrets_read`) before
+/// anything happens, including the kind's `[limits]` call cap.
+pub struct LocalToolHost {
+    policy: Policy,
+    specs: Vec<ToolSpec>,
+    http: reqwest::Client,
+    audit: Option<(EventStore, SessionId)>,
+    /// Granted calls per capability kind, for `[limits]`.
+    calls: Mutex<HashMap<CapabilityKind, u32>>,
+}
+
+#[derive(Deserialize)]
+struct ReadFileInput {
+    path: String,
+}
+
+#[derive(Deserialize)]
+struct WriteFileInput {
+    path: String,
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct ExecInput {
+    command: String,
+    #[serde(default)]
+    args: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct HttpGetInput {
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct ReadSecretInput {
+    name: String,
+}
+
+/// A redirect to a host the policy does not allow.
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+struct RedirectDenied(String);
+
+impl LocalToolHost {
+    pub fn new(policy: Policy) -> Self {
+        Self {
+            specs: specs(),
+            http: http_client(policy.clone()),
+            audit: None,
+            calls: Mutex::new(HashMap::new()),
+            policy,
+        }
+    }
+
+    /// Record a `SecretAccess` event in `store` for every secret request,
+    /// allowed or not.
+    pub fn audit_to(mut self, store: EventStore, session_id: SessionId) -> Self {
+        self.audit = Some((store, session_id));
+        self
+    }
+
+    /// Number of granted calls for a capability kind so far.
+    pub fn capability_calls(&self, kind: CapabilityKind) -> u32 {
+        self.calls().get(&kind).copied().unwrap_or(0)
+    }
+
+    fn calls(&self) -> std::sync::MutexGuard<'_, HashMap<CapabilityKind, u32>> {
+        self.calls.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Check `request` against the policy and the kind's call limit,
+    /// counting it if granted.
+    fn decide(&self, request: &CapabilityRequest) -> Decision {
+        let mut calls = self.calls();
+        let made = calls.get(&request.kind).copied().unwrap_or(0);
+        let decision = match self.policy.check(request) {
+            Decision::Allow => self.policy.check_limit(request.kind, made),
+            deny => deny,
+        };
+        if decision.is_allowed() {
+            *calls.entry(request.kind).or_default() += 1;
+        }
+        decision
+    }
+
+    fn require(&self, request: &CapabilityRequest) -> Result<(), ToolError> {
+        match self.decide(request) {
+            Decision::Allow => Ok(()),
+            Decision::Deny { reason } => Err(ToolError::CapabilityDenied(reason)),
+        }
+    }
+
+    async fn read_file(&self, input: ReadFileInput) -> Result<Value, ToolError> {
+        check_path(&input.path)?;
+        self.require(&CapabilityRequest::fs_read(&input.path))?;
+
+        let content = tokio::fs::read_to_string(&input.path)
+            .await
+            .map_err(|e| ToolError::Execution(format!("read {}: {e}", input.path)))?;
+        Ok(json!({ "content": content }))
+    }
+
+    async fn write_file(&self, input: WriteFileInput) -> Result<Value, ToolError> {
+        check_path(&input.path)?;
+        self.require(&CapabilityRequest::fs_write(&input.path))?;
+
+        tokio::fs::write(&input.path, &input.content)
+            .await
+            .map_err(|e| ToolError::Execution(format!("write {}: {e}", input.path)))?;
+        Ok(json!({ "bytes_written": input.content.len() }))
+    }
+
+    async fn exec(&self, input: ExecInput) -> Result<Value, ToolError> {
+        // Policy matches the program and each argument, e.g. "git status".
+        self.require(&CapabilityRequest::exec_args(&input.command, &input.args))?;
+
+        // Run the program directly rather than through a shell, so the
+        // arguments cannot smuggle in extra commands. A call abandoned on
+        // timeout takes the program down with it.
+        let output = Command::new(&input.command)
+            .args(&input.args)
+            .kill_on_drop(true)
+            .output()
+            .await
+            .map_err(|e| ToolError::Execution(format!("spawn {}: {e}", input.command)))?;
+        Ok(json!({
+            "status": output.status.code(),
+            "stdout": String::from_utf8_lossy(&output.stdout),
+            "stderr": String::from_utf8_lossy(&output.stderr),
+        }))
+    }
+
+    async fn http_get(&self, input: HttpGetInput) -> Result<Value, ToolError> {
+        let url = reqwest::Url::parse(&input.url)
+            .map_err(|e| ToolError::InvalidInput(format!("url: {e}")))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(ToolError::InvalidInput(format!(
+                "url: unsupported scheme {}",
+                url.scheme()
+            )));
+        }
+        let domain = url
+            .host_str()
+            .ok_or_else(|| ToolError::InvalidInput("url: missing host".into()))?;
+        self.require(&CapabilityRequest::net_http(domain))?;
+
+        let response = self.http.get(url).send().await.map_err(request_error)?;
+        let status = response.status().as_u16();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| ToolError::Execution(e.to_string()))?;
+        Ok(json!({ "status": status, "body": body }))
+    }
+
+    /// Secrets are read from environment variables.
+    async fn read_secret(&self, input: ReadSecretInput) -> Result<Value, ToolError> {
+        let decision = self.decide(&CapabilityRequest::secrets_read(&input.name));
+
+        // A secret is never handed out unless its access was recorded.
+        if let Some((store, session_id)) = &self.audit {
+            let event = Event::new(
+                *session_id,
+                EventKind::SecretAccess {
+                    key: input.name.clone(),
+                    allowed: decision.is_allowed(),
+                },
+            );
+            store
+                .append(&event)
+                .map_err(|e| ToolError::Execution(format!("audit secret access: {e}")))?;
+        }
+
+        if let Decision::Deny { reason } = decision {
+            return Err(ToolError::CapabilityDenied(reason));
+        }
+        let value = std::env::var(&input.name)
+            .map_err(|_| ToolError::Execution(format!("secret not set: {}", input.name)))?;
+        Ok(json!({ "value": value }))
+    }
+}
+
+impl ToolHost for LocalToolHost {
+    fn specs(&self) -> &[ToolSpec] {
+        &self.specs
+    }
+
+    async fn execute(&self, call: &ToolCall) -> Result<Value, ToolError> {
+        match call.name.as_str() {
+            "read_file" => self.read_file(parse_input(call)?).await,
+            "write_file" => self.write_file(parse_input(call)?).await,
+            "exec" => self.exec(parse_input(call)?).await,
+            "http_get" => self.http_get(parse_input(call)?).await,
+            "read_secret" => self.read_secret(parse_input(call)?).await,
+            _ => Err(ToolError::NotFound(call.name.clone())),
+        }
+    }
+
+    fn log_output(&self, call: &ToolCall, output: &Value) -> Value {
+        if call.name == "read_secret" {
+            json!({ "value": REDACTED })
+        } else {
+            output.clone()
+        }
+    }
+}
+
+/// An HTTP client that follows a redirect only to a host `policy` allows
+/// for `net_http`, so an allowed site cannot send the tool elsewhere.
+fn http_client(policy: Policy) -> reqwest::Client {
+    let redirects = reqwest::redirect::Policy::custom(move |attempt| {
+        if attempt.previous().len() >= MAX_REDIRECTS {
+            return attempt.error(format!("more than {MAX_REDIRECTS} redirects"));
+        }
+        let Some(host) = attempt.url().host_str().map(str::to_string) else {
+            return attempt.error(RedirectDenied("redirect without a host".into()));
+        };
+        match policy.check(&CapabilityRequest::net_http(&host)) {
+            Decision::Allow => attempt.follow(),
+            Decision::Deny { reason } => {
+                attempt.error(RedirectDenied(format!("redirect to {host}: {reason}")))
+            }
+        }
+    });
+    reqwest::Client::builder()
+        .redirect(redirects)
+        .build()
+        // Fails only where `Client::new` would panic too.
+        .expect("HTTP client")
+}
+
+/// Report a redirect the policy refused as a denied capability.
+fn request_error(error: reqwest::Error) -> ToolError {
+    let mut source = std::error::Error::source(&error);
+    while let Some(cause) = source {
+        if let Some(denied) = cause.downcast_ref::<RedirectDenied>() {
+            return ToolError::CapabilityDenied(denied.0.clone());
+        }
+        source = cause.source();
+    }
+    ToolError::Execution(error.to_string())
+}
+
+fn parse_input<T: DeserializeOwned>(call: &ToolCall) -> Result<T, ToolError> {
+    serde_json::from_value(call.input.clone()).map_err(|e| ToolError::InvalidInput(e.to_string()))
+}
+
+/// Reject `..` components.
+///
+/// Path rules in the policy are prefix matches, so `./../secret` would
+/// otherwise pass an allowlist entry of `.`.
+fn check_path(path: &str) -> Result<(), ToolError> {
+    if Path::new(path)
+        .components()
+        .any(|c| c == Component::ParentDir)
+    {
+        return Err(ToolError::InvalidInput(format!(
+            "path must not contain '..': {path}"
+        )));
+    }
+    Ok(())
+}
+
+fn specs() -> Vec<ToolSpec> {
+    vec![
+        ToolSpec {
+            name: "read_file".into(),
+            description: "Read a UTF-8 text file.".into(),
+            schema: json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Path to the file" },
+                },
+                "required": ["path"],
+            }),
+        },
+        ToolSpec {
+            name: "write_file".into(),
+            description: "Write text to a file, replacing any existing content.".into(),
+            schema: json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Path to the file" },
+                    "content": { "type": "string", "description": "Text to write" },
+                },
+                "required": ["path", "content"],
+            }),
+        },
+        ToolSpec {
+            name: "exec".into(),
+            description: "Run a program (not a shell) and capture its output.".into(),
+            schema: json!({
+                "type": "object",
+                "properties": {
+                    "command": { "type": "string", "description": "Program to run" },
+                    "args": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Arguments to pass",
+                    },
+                },
+                "required": ["command"],
+            }),
+        },
+        ToolSpec {
+            name: "http_get".into(),
+            description: "Fetch a URL with an HTTP GET request.".into(),
+            schema: json!({
+                "type": "object",
+                "properties": {
+                    "url": { "type": "string", "description": "http or https URL" },
+                },
+                "required": ["url"],
+            }),
+        },
+        ToolSpec {
+            name: "read_secret".into(),
+            description: "Read a secret, such as an API token, by name.".into(),
+            schema: json!({
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string", "description": "Secret name" },
+                },
+                "required": ["name"],
+            }),
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn host(policy: &str) -> LocalToolHost {
+        LocalToolHost::new(Policy::parse(policy).unwrap())
+    }
+
+    fn call(name: &str, input: Value) -> ToolCall {
+        ToolCall {
+            id: "call_1".into(),
+            name: name.into(),
+            input,
+        }
+    }
+
+    fn temp_dir(name: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("bosun-local-{name}-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.to_string_lossy().into_owned()
+    }
+
+    #[tokio::test]
+    async fn write_then_read_within_policy() {
+        let dir = temp_dir("rw");
+        let host = host(&format!(
+            "[allow]\nfs_read = [\"{dir}\"]\nfs_write = [\"{dir}\"]\n"
+        ));
+        let path = format!("{dir}/note.txt");
+
+        let written = host
+            .execute(&call(
+                "write_file",
+                json!({ "path": path, "content": "hello" }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(written, json!({ "bytes_written": 5 }));
+
+        let read = host
+            .execute(&call("read_file", json!({ "path": path })))
+            .await
+            .unwrap();
+        assert_eq!(read, json!({ "content": "hello" }));
+    }
+
+    #[tokio::test]
+    async fn denies_paths_outside_policy() {
+        let dir = temp_dir("deny");
+        let host = host(&format!("[allow]\nfs_read = [\"{dir}\"]\n"));
+
+        let err = host
+            .execute(&call("read_file", json!({ "path": "/etc/hostname" })))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::CapabilityDenied(_)), "{err:?}");
+
+        let err = host
+            .execute(&call(
+                "write_file",
+                json!({ "path": format!("{dir}/x"), "content": "" }),
+            ))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::CapabilityDenied(_)), "{err:?}");
+    }
+
+    #[tokio::test]
+    async fn rejects_parent_dir_components() {
+        let host = host("[allow]\nfs_read = [\".\"]\n");
+        let err = host
+            .execute(&call("read_file", json!({ "path": "./../secret" })))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::InvalidInput(_)), "{err:?}");
+    }
+
+    #[tokio::test]
+    async fn exec_checks_full_command_line() {
+        let host = host("[allow]\nexec = [\"echo hi\"]\n");
+
+        let output = host
+            .execute(&call("exec", json!({ "command": "echo", "args": ["hi"] })))
+            .await
+            .unwrap();
+        assert_eq!(output["status"], 0);
+        assert_eq!(output["stdout"], "hi\n");
+
+        let err = host
+            .execute(&call("exec", json!({ "command": "echo", "args": ["bye"] })))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::CapabilityDenied(_)), "{err:?}");
+    }
+
+    #[tokio::test]
+    async fn http_get_checks_domain_before_connecting() {
+        let host = host("[allow]\nnet_http = [\"example.com\"]\n");
+        let err = host
+            .execute(&call("http_get", json!({ "url": "http://127.0.0.1:1/" })))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::CapabilityDenied(_)), "{err:?}");
+
+        let err = host
+            .execute(&call("http_get", json!({ "url": "file:///etc/passwd" })))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::InvalidInput(_)), "{err:?}");
+    }
+
+    #[tokio::test]
+    async fn http_get_checks_redirect_targets() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = [0; 1024];
+                let _ = stream.read(&mut request).await;
+                let response = format!(
+                    "HTTP/1.1 302 Found\r\nLocation: http://localhost:{port}/\r\n\
+                     Content-Length: 0\r\nConnection: close\r\n\r\n"
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+
+        let host = host("[allow]\nnet_http = [\"127.0.0.1\"]\n");
+        let err = host
+            .execute(&call(
+                "http_get",
+                json!({ "url": format!("http://127.0.0.1:{port}/") }),
+            ))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, ToolError::CapabilityDenied(reason) if reason.contains("localhost")),
+            "{err:?}"
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn exec_kills_the_program_when_abandoned() {
+        let dir = temp_dir("kill");
+        let pid_file = format!("{dir}/pid");
+        let host = host("[allow]\nexec = [\"sh\"]\n");
+        let script = format!("echo $$ > {pid_file}; exec sleep 30");
+        let exec = call("exec", json!({ "command": "sh", "args": ["-c", script] }));
+
+        let timed_out =
+            tokio::time::timeout(std::time::Duration::from_millis(300), host.execute(&exec)).await;
+        assert!(timed_out.is_err());
+
+        let pid = std::fs::read_to_string(&pid_file).unwrap();
+        let stat = format!("/proc/{}/stat", pid.trim());
+        let mut alive = true;
+        for _ in 0..50 {
+            // Gone, or killed and waiting to be reaped.
+            alive = std::fs::read_to_string(&stat)
+                .is_ok_and(|stat| !stat.rsplit(')').next().unwrap_or("").starts_with(" Z"));
+            if !alive {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert!(!alive, "sleep still running after the call was dropped");
+    }
+
+    #[tokio::test]
+    async fn unknown_tool_and_bad_input() {
+        let host = host("");
+        let err = host.execute(&call("rm", json!({}))).await.unwrap_err();
+        assert!(matches!(err, ToolError::NotFound(_)));
+
+        let err = host
+            .execute(&call("read_file", json!({ "file": "a" })))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::InvalidInput(_)));
+    }
+
+    #[tokio::test]
+    async fn read_secret_is_policy_checked_and_audited() {
+        let store = EventStore::in_memory().unwrap();
+        let session_id = SessionId::new();
+        let host = host("[allow]\nsecrets_read = [\"CARGO_PKG_NAME\"]\n")
+            .audit_to(store.clone(), session_id);
+
+        let secret = call("read_secret", json!({ "name": "CARGO_PKG_NAME" }));
+        let output = host.execute(&secret).await.unwrap();
+        assert_eq!(output["value"], env!("CARGO_PKG_NAME"));
+        assert_eq!(
+            host.log_output(&secret, &output),
+            json!({ "value": REDACTED })
+        );
+
+        let err = host
+            .execute(&call("read_secret", json!({ "name": "HOME" })))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::CapabilityDenied(_)), "{err:?}");
+
+        let audit: Vec<_> = store
+            .load_events(session_id, Some("secret_access"))
+            .unwrap()
+            .into_iter()
+            .map(|e| match e.kind {
+                EventKind::SecretAccess { key, allowed } => (key, allowed),
+                other => panic!("unexpected event {other:?}"),
+            })
+            .collect();
+        assert_eq!(
+            audit,
+            vec![("CARGO_PKG_NAME".into(), true), ("HOME".into(), false)]
+        );
+    }
+}
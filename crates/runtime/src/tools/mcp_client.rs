@@ -18,24 +18,201 @@
 //! # }
 //! ```
 
+use super::ToolError;
 use rmcp::{
-    ServiceExt,
-    model::CallToolRequestParams,
-    service::RunningService,
+    ClientHandler, ServiceExt,
+    model::{
+        CallToolRequestParams, Meta, NumberOrString, ProgressNotificationParam, ProgressToken,
+    },
+    service::{NotificationContext, RoleClient, RunningService},
     transport::{ConfigureCommandExt, TokioChildProcess},
 };
-use std::sync::Arc;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
 use tokio::process::Command;
 
 /// Error type for MCP operations.
 pub type McpError = Box<dyn std::error::Error + Send + Sync>;
 
+/// An MCP server did not finish starting up in time.
+///
+/// Returned inside [`McpError`]; downcast to detect it.
+#[derive(Debug, thiserror::Error)]
+#[error("MCP server did not start within {0:?}")]
+pub struct StartupTimeout(pub std::time::Duration);
+
 // Re-export rmcp types for convenience
 pub use rmcp::model::{CallToolResult, Tool};
 
+/// Convenience access to the content of a tool result.
+///
+/// Single items can be inspected with rmcp's own `as_text` and
+/// `as_image` on each entry of `content`.
+pub trait CallToolResultExt {
+    /// All text content joined together; other content is skipped.
+    fn text(&self) -> String;
+
+    /// Base64 data of every image in the result, in order.
+    fn images(&self) -> Vec<&str>;
+
+    /// Whether the result carries no content at all.
+    fn is_empty(&self) -> bool;
+
+    /// Concatenate the text content and deserialize it as `T`.
+    ///
+    /// Non-text content such as images is skipped. Fails with
+    /// [`ToolError::InvalidOutput`] if the text is not valid JSON for `T`.
+    fn parse_text_json<T: DeserializeOwned>(&self) -> Result<T, ToolError>;
+}
+
+impl CallToolResultExt for CallToolResult {
+    fn text(&self) -> String {
+        self.content
+            .iter()
+            .filter_map(|content| content.as_text())
+            .map(|text| text.text.as_str())
+            .collect()
+    }
+
+    fn images(&self) -> Vec<&str> {
+        self.content
+            .iter()
+            .filter_map(|content| content.as_image())
+            .map(|image| image.data.as_str())
+            .collect()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.content.is_empty()
+    }
+
+    fn parse_text_json<T: DeserializeOwned>(&self) -> Result<T, ToolError> {
+        serde_json::from_str(&self.text()).map_err(|e| {
+            ToolError::InvalidOutput(format!(
+                "expected JSON for {}: {e}",
+                std::any::type_name::<T>()
+            ))
+        })
+    }
+}
+
+/// A progress update from a tool that is still running, for servers that
+/// send MCP progress notifications.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolProgress {
+    /// Name of the tool reporting progress.
+    pub tool: String,
+    /// Progress so far; it increases with every update.
+    pub progress: f64,
+    /// What `progress` counts up to, if known.
+    pub total: Option<f64>,
+    pub message: Option<String>,
+}
+
+type ProgressFn = Arc<dyn Fn(ToolProgress) + Send + Sync>;
+
+/// Client handler passing progress notifications to a callback.
+///
+/// Each call sent while a callback is set carries a fresh progress token,
+/// mapped back to the tool name when a notification arrives.
+#[derive(Clone, Default)]
+struct ProgressRelay(Arc<RelayState>);
+
+#[derive(Default)]
+struct RelayState {
+    callback: Mutex<Option<ProgressFn>>,
+    /// Tool name for each progress token of a call in flight.
+    calls: Mutex<HashMap<i64, String>>,
+    next_token: AtomicI64,
+}
+
+impl ProgressRelay {
+    fn set_callback(&self, callback: ProgressFn) {
+        *self
+            .0
+            .callback
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(callback);
+    }
+
+    /// Register a call to `tool`, returning its token if anyone is listening.
+    fn start(&self, tool: &str) -> Option<CallToken<'_>> {
+        let listening = self
+            .0
+            .callback
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_some();
+        if !listening {
+            return None;
+        }
+        let token = self.0.next_token.fetch_add(1, Ordering::Relaxed);
+        self.calls().insert(token, tool.to_string());
+        Some(CallToken { relay: self, token })
+    }
+
+    fn calls(&self) -> std::sync::MutexGuard<'_, HashMap<i64, String>> {
+        self.0.calls.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn relay(&self, params: ProgressNotificationParam) {
+        let NumberOrString::Number(token) = params.progress_token.0 else {
+            return;
+        };
+        let Some(tool) = self.calls().get(&token).cloned() else {
+            return;
+        };
+        let callback = self
+            .0
+            .callback
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        if let Some(callback) = callback {
+            callback(ToolProgress {
+                tool,
+                progress: params.progress,
+                total: params.total,
+                message: params.message,
+            });
+        }
+    }
+}
+
+impl ClientHandler for ProgressRelay {
+    async fn on_progress(
+        &self,
+        params: ProgressNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        self.relay(params);
+    }
+}
+
+/// Forgets a call's progress token once the call ends or is dropped.
+struct CallToken<'a> {
+    relay: &'a ProgressRelay,
+    token: i64,
+}
+
+impl CallToken<'_> {
+    fn meta(&self) -> Meta {
+        Meta::with_progress_token(ProgressToken(NumberOrString::Number(self.token)))
+    }
+}
+
+impl Drop for CallToken<'_> {
+    fn drop(&mut self) {
+        self.relay.calls().remove(&self.token);
+    }
+}
+
 /// An MCP client connected to a server process.
 pub struct McpClient {
-    service: Arc<RunningService<rmcp::service::RoleClient, ()>>,
+    service: RunningService<RoleClient, ProgressRelay>,
+    relay: ProgressRelay,
 }
 
 impl McpClient {
@@ -58,11 +235,23 @@ impl McpClient {
             }
         }))?;
 
-        let service = ().serve(transport).await?;
+        let relay = ProgressRelay::default();
+        let service = relay.clone().serve(transport).await?;
 
-        Ok(Self {
-            service: Arc::new(service),
-        })
+        Ok(Self { service, relay })
+    }
+
+    /// Ask the server for progress on every later tool call and pass each
+    /// update to `callback`. Servers that do not report progress send none.
+    pub fn on_progress(&self, callback: impl Fn(ToolProgress) + Send + Sync + 'static) {
+        self.relay.set_callback(Arc::new(callback));
+    }
+
+    /// Close the connection and wait for the server process to exit.
+    pub async fn shutdown(self) {
+        if let Err(e) = self.service.cancel().await {
+            tracing::warn!(error = %e, "MCP client did not shut down cleanly");
+        }
     }
 
     /// List available tools from the server.
@@ -77,10 +266,12 @@ impl McpClient {
         name: impl Into<String>,
         arguments: Option<serde_json::Map<String, serde_json::Value>>,
     ) -> Result<CallToolResult, McpError> {
+        let name = name.into();
+        let token = self.relay.start(&name);
         let params = CallToolRequestParams {
-            name: name.into().into(),
+            name: name.into(),
             arguments,
-            meta: None,
+            meta: token.as_ref().map(CallToken::meta),
             task: None,
         };
 
@@ -88,3 +279,101 @@ impl McpClient {
         Ok(result)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::Content;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Weather {
+        city: String,
+        celsius: i32,
+    }
+
+    #[test]
+    fn content_helpers_split_text_and_images() {
+        let result = CallToolResult::success(vec![
+            Content::text("a"),
+            Content::image("aGk=", "image/png"),
+            Content::text("b"),
+        ]);
+        assert_eq!(result.text(), "ab");
+        assert_eq!(result.images(), ["aGk="]);
+        assert!(!result.is_empty());
+
+        let empty = CallToolResult::success(Vec::new());
+        assert!(empty.is_empty());
+        assert_eq!(empty.text(), "");
+        assert!(empty.images().is_empty());
+    }
+
+    #[test]
+    fn progress_is_relayed_for_calls_in_flight() {
+        let relay = ProgressRelay::default();
+        assert!(
+            relay.start("build").is_none(),
+            "no token without a callback"
+        );
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        relay.set_callback(Arc::new(move |progress| {
+            sink.lock().unwrap().push(progress)
+        }));
+        let notify = |token, progress| ProgressNotificationParam {
+            progress_token: ProgressToken(NumberOrString::Number(token)),
+            progress,
+            total: Some(10.0),
+            message: Some("compiling".into()),
+        };
+
+        let call = relay.start("build").unwrap();
+        let token = call.token;
+        relay.relay(notify(token, 3.0));
+        relay.relay(notify(token + 1, 4.0));
+        drop(call);
+        relay.relay(notify(token, 5.0));
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            [ToolProgress {
+                tool: "build".into(),
+                progress: 3.0,
+                total: Some(10.0),
+                message: Some("compiling".into()),
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_text_json_joins_text_content() {
+        let result = CallToolResult::success(vec![
+            Content::text(r#"{"city": "Oslo", "#),
+            Content::image("aGk=", "image/png"),
+            Content::text(r#""celsius": -3}"#),
+        ]);
+
+        let weather: Weather = result.parse_text_json().unwrap();
+        assert_eq!(
+            weather,
+            Weather {
+                city: "Oslo".into(),
+                celsius: -3
+            }
+        );
+    }
+
+    #[test]
+    fn parse_text_json_reports_type_mismatch() {
+        let result = CallToolResult::success(vec![Content::text(r#"{"city": "Oslo"}"#)]);
+
+        let err = result.parse_text_json::<Weather>().unwrap_err();
+        let ToolError::InvalidOutput(message) = err else {
+            panic!("expected InvalidOutput, got {err:?}");
+        };
+        assert!(message.contains("Weather"), "{message}");
+        assert!(message.contains("celsius"), "{message}");
+    }
+}
@@ -1,28 +1,108 @@
 //! MCP-backed tool host.
 
-use super::{McpClient, McpError, ToolArguments, ToolCall, ToolError, ToolHost, ToolSpec};
-use serde_json::Value;
+use super::validate::InputValidator;
+use super::{
+    CallToolResult, CallToolResultExt, McpClient, McpError, StartupTimeout, ToolArguments,
+    ToolCall, ToolError, ToolHost, ToolProgress, ToolSpec,
+};
+use rmcp::model::{Content, RawContent, ResourceContents};
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+use std::time::Duration;
+
+/// How long a server gets to answer `initialize` and `tools/list`.
+const DEFAULT_STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
 
 /// Tool host backed by an MCP server.
 pub struct McpToolHost {
     client: McpClient,
     specs: Vec<ToolSpec>,
+    validator: Option<InputValidator>,
 }
 
 impl McpToolHost {
-    /// Spawn MCP server and cache tool specs.
+    /// Spawn MCP server and cache tool specs, giving it 30 seconds to
+    /// start.
     pub async fn spawn(
         command: impl AsRef<str>,
         args: impl IntoIterator<Item = impl AsRef<str>>,
     ) -> Result<Self, McpError> {
-        let client = McpClient::spawn(command, args).await?;
-        let specs = client
-            .list_tools()
-            .await?
-            .into_iter()
-            .map(ToolSpec::from)
-            .collect();
-        Ok(Self { client, specs })
+        Self::spawn_with_timeout(command, args, DEFAULT_STARTUP_TIMEOUT).await
+    }
+
+    /// Spawn MCP server and cache tool specs, failing with
+    /// [`StartupTimeout`] if initialization and listing its tools take
+    /// longer than `timeout`. The server process is killed in that case.
+    pub async fn spawn_with_timeout(
+        command: impl AsRef<str>,
+        args: impl IntoIterator<Item = impl AsRef<str>>,
+        timeout: Duration,
+    ) -> Result<Self, McpError> {
+        let start = async {
+            let client = McpClient::spawn(command, args).await?;
+            let specs = client
+                .list_tools()
+                .await?
+                .into_iter()
+                .map(ToolSpec::from)
+                .collect::<Vec<_>>();
+            Ok::<_, McpError>((client, specs))
+        };
+        // Dropping the unfinished start-up drops the child process
+        // handle, which kills it.
+        let (client, specs) = tokio::time::timeout(timeout, start)
+            .await
+            .map_err(|_| StartupTimeout(timeout))??;
+        Ok(Self {
+            client,
+            specs,
+            validator: None,
+        })
+    }
+
+    /// Validate call arguments against each tool's input schema before
+    /// sending them to the server.
+    ///
+    /// Invalid arguments fail with [`ToolError::InvalidInput`] naming the
+    /// offending fields, which is clearer to the model than most server
+    /// errors.
+    pub fn validate_input(mut self, enabled: bool) -> Self {
+        self.validator = enabled.then(|| InputValidator::new(&self.specs));
+        self
+    }
+
+    /// Pass progress updates from running tools to `callback`; see
+    /// [`McpClient::on_progress`].
+    pub fn on_progress(self, callback: impl Fn(ToolProgress) + Send + Sync + 'static) -> Self {
+        self.client.on_progress(callback);
+        self
+    }
+
+    /// Execute a call whose tool answers in JSON text, deserializing it
+    /// as `T`.
+    ///
+    /// A result the server flags as an error fails with
+    /// [`ToolError::Execution`] carrying its text.
+    pub async fn execute_typed<T: DeserializeOwned>(
+        &self,
+        call: &ToolCall,
+    ) -> Result<T, ToolError> {
+        let result = self.call_tool(call).await?;
+        if result.is_error == Some(true) {
+            return Err(ToolError::Execution(result.text()));
+        }
+        result.parse_text_json()
+    }
+
+    async fn call_tool(&self, call: &ToolCall) -> Result<CallToolResult, ToolError> {
+        if let Some(validator) = &self.validator {
+            validator.validate(call)?;
+        }
+        let arguments = ToolArguments::try_from(call.input.clone())?;
+        self.client
+            .call_tool(&call.name, arguments.0)
+            .await
+            .map_err(|e| ToolError::Execution(e.to_string()))
     }
 }
 
@@ -31,15 +111,149 @@ impl ToolHost for McpToolHost {
         &self.specs
     }
 
-    async fn execute(&self, call: &ToolCall) -> Result<Value, ToolError> {
-        let arguments = ToolArguments::try_from(call.input.clone())?;
-        let result = self
-            .client
-            .call_tool(&call.name, arguments.0)
-            .await
-            .map_err(|e| ToolError::Execution(e.to_string()))?;
+    async fn shutdown(self) {
+        self.client.shutdown().await;
+    }
 
-        serde_json::to_value(&result.content)
+    async fn execute(&self, call: &ToolCall) -> Result<Value, ToolError> {
+        let result = self.call_tool(call).await?;
+        result
+            .content
+            .iter()
+            .map(content_to_value)
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::Array)
             .map_err(|e| ToolError::Execution(format!("serialize result: {e}")))
     }
 }
+
+/// Convert one content item to what the model is shown.
+///
+/// Embedded resources are reshaped by MIME type: JSON is passed through
+/// parsed, other text is inlined, and binary data is replaced by a short
+/// descriptor so the model can refer to it by URI. Everything else keeps
+/// its MCP form.
+fn content_to_value(content: &Content) -> serde_json::Result<Value> {
+    let RawContent::Resource(embedded) = &content.raw else {
+        return serde_json::to_value(content);
+    };
+
+    Ok(match &embedded.resource {
+        ResourceContents::TextResourceContents {
+            uri,
+            mime_type,
+            text,
+            ..
+        } => {
+            let parsed = mime_type
+                .as_deref()
+                .filter(|mime| is_json_mime(mime))
+                .and_then(|_| serde_json::from_str::<Value>(text).ok());
+            match parsed {
+                Some(value) => {
+                    json!({ "type": "resource", "uri": uri, "mimeType": mime_type, "json": value })
+                }
+                None => {
+                    json!({ "type": "resource", "uri": uri, "mimeType": mime_type, "text": text })
+                }
+            }
+        }
+        ResourceContents::BlobResourceContents {
+            uri,
+            mime_type,
+            blob,
+            ..
+        } => {
+            // Base64 carries 3 bytes in every 4 characters.
+            let bytes = blob.trim_end_matches('=').len() * 3 / 4;
+            let mime = mime_type.as_deref().unwrap_or("application/octet-stream");
+            json!({
+                "type": "resource",
+                "uri": uri,
+                "mimeType": mime_type,
+                "description": format!("{mime} resource, {bytes} bytes, not inlined"),
+            })
+        }
+    })
+}
+
+/// Whether a MIME type denotes JSON, such as `application/json` or
+/// `application/ld+json`.
+fn is_json_mime(mime: &str) -> bool {
+    let essence = mime.split(';').next().unwrap_or_default().trim();
+    essence.eq_ignore_ascii_case("application/json")
+        || essence.to_ascii_lowercase().ends_with("+json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resource(mime_type: &str, text: &str) -> Content {
+        Content::resource(ResourceContents::TextResourceContents {
+            uri: "file:///data".into(),
+            mime_type: Some(mime_type.into()),
+            text: text.into(),
+            meta: None,
+        })
+    }
+
+    #[test]
+    fn json_resources_are_parsed_and_text_inlined() {
+        let value = content_to_value(&resource("application/json; charset=utf-8", r#"{"a": 1}"#));
+        assert_eq!(
+            value.unwrap(),
+            json!({
+                "type": "resource",
+                "uri": "file:///data",
+                "mimeType": "application/json; charset=utf-8",
+                "json": { "a": 1 },
+            })
+        );
+
+        let value = content_to_value(&resource("text/csv", "a,b\n1,2")).unwrap();
+        assert_eq!(value["text"], "a,b\n1,2");
+        assert!(value.get("json").is_none());
+
+        // Malformed JSON is still shown as text.
+        let value = content_to_value(&resource("application/json", "{oops")).unwrap();
+        assert_eq!(value["text"], "{oops");
+    }
+
+    #[test]
+    fn binary_resources_are_described_by_uri() {
+        let content = Content::resource(ResourceContents::BlobResourceContents {
+            uri: "file:///report.pdf".into(),
+            mime_type: Some("application/pdf".into()),
+            blob: "aGVsbG8=".into(),
+            meta: None,
+        });
+        assert_eq!(
+            content_to_value(&content).unwrap(),
+            json!({
+                "type": "resource",
+                "uri": "file:///report.pdf",
+                "mimeType": "application/pdf",
+                "description": "application/pdf resource, 5 bytes, not inlined",
+            })
+        );
+    }
+
+    #[test]
+    fn other_content_keeps_mcp_form() {
+        assert_eq!(
+            content_to_value(&Content::text("hi")).unwrap(),
+            json!({ "type": "text", "text": "hi" })
+        );
+    }
+
+    #[tokio::test]
+    async fn unresponsive_server_times_out() {
+        let timeout = Duration::from_millis(200);
+        let err = McpToolHost::spawn_with_timeout("sleep", ["30"], timeout)
+            .await
+            .err()
+            .expect("sleep never answers initialize");
+        assert!(err.downcast_ref::<StartupTimeout>().is_some(), "{err}");
+    }
+}
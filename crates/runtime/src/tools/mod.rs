@@ -2,14 +2,23 @@
 
 mod empty;
 pub mod errors;
+#[cfg(feature = "local-tools")]
+mod local;
 mod mcp_client;
 mod mcp_host;
+mod multi;
 mod r#trait;
 mod types;
+mod validate;
 
 pub use empty::EmptyToolHost;
 pub use errors::ToolError;
-pub use mcp_client::{CallToolResult, McpClient, McpError, Tool};
+#[cfg(feature = "local-tools")]
+pub use local::LocalToolHost;
+pub use mcp_client::{
+    CallToolResult, CallToolResultExt, McpClient, McpError, StartupTimeout, Tool, ToolProgress,
+};
 pub use mcp_host::McpToolHost;
+pub use multi::MultiToolHost;
 pub use r#trait::ToolHost;
 pub use types::{ToolArguments, ToolCall, ToolResult, ToolSpec};
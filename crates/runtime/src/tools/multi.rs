@@ -0,0 +1,144 @@
+//! Tool host combining several others.
+
+use super::{ToolCall, ToolError, ToolHost, ToolSpec};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Offers the tools of several hosts as one, routing each call by name.
+///
+/// When two hosts expose a tool with the same name, the first host keeps
+/// it and the later one is hidden.
+pub struct MultiToolHost<H> {
+    hosts: Vec<H>,
+    specs: Vec<ToolSpec>,
+    /// Index into `hosts` for each tool name.
+    routes: HashMap<String, usize>,
+}
+
+impl<H: ToolHost> MultiToolHost<H> {
+    pub fn new(hosts: Vec<H>) -> Self {
+        let mut specs = Vec::new();
+        let mut routes = HashMap::new();
+        for (index, host) in hosts.iter().enumerate() {
+            for spec in host.specs() {
+                if routes.contains_key(&spec.name) {
+                    tracing::warn!(tool = %spec.name, "duplicate tool name; keeping the first");
+                    continue;
+                }
+                routes.insert(spec.name.clone(), index);
+                specs.push(spec.clone());
+            }
+        }
+        Self {
+            hosts,
+            specs,
+            routes,
+        }
+    }
+
+    /// Number of combined hosts.
+    pub fn len(&self) -> usize {
+        self.hosts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hosts.is_empty()
+    }
+
+    fn host_for(&self, call: &ToolCall) -> Option<&H> {
+        self.routes.get(&call.name).map(|&index| &self.hosts[index])
+    }
+}
+
+impl<H: ToolHost> ToolHost for MultiToolHost<H> {
+    fn specs(&self) -> &[ToolSpec] {
+        &self.specs
+    }
+
+    async fn execute(&self, call: &ToolCall) -> Result<Value, ToolError> {
+        match self.host_for(call) {
+            Some(host) => host.execute(call).await,
+            None => Err(ToolError::NotFound(call.name.clone())),
+        }
+    }
+
+    fn log_output(&self, call: &ToolCall, output: &Value) -> Value {
+        match self.host_for(call) {
+            Some(host) => host.log_output(call, output),
+            None => output.clone(),
+        }
+    }
+
+    fn answered(&self, call: &ToolCall, result: &Result<Value, ToolError>) {
+        if let Some(host) = self.host_for(call) {
+            host.answered(call, result);
+        }
+    }
+
+    async fn shutdown(self) {
+        for host in self.hosts {
+            host.shutdown().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Host whose tools answer with the host's label.
+    struct LabelHost {
+        label: &'static str,
+        specs: Vec<ToolSpec>,
+    }
+
+    impl LabelHost {
+        fn new(label: &'static str, tools: &[&str]) -> Self {
+            let specs = tools
+                .iter()
+                .map(|name| ToolSpec {
+                    name: name.to_string(),
+                    description: String::new(),
+                    schema: json!({ "type": "object" }),
+                })
+                .collect();
+            Self { label, specs }
+        }
+    }
+
+    impl ToolHost for LabelHost {
+        fn specs(&self) -> &[ToolSpec] {
+            &self.specs
+        }
+
+        async fn execute(&self, _call: &ToolCall) -> Result<Value, ToolError> {
+            Ok(json!(self.label))
+        }
+    }
+
+    fn call(name: &str) -> ToolCall {
+        ToolCall {
+            id: "call_1".into(),
+            name: name.into(),
+            input: json!({}),
+        }
+    }
+
+    #[tokio::test]
+    async fn routes_calls_to_the_owning_host() {
+        let host = MultiToolHost::new(vec![
+            LabelHost::new("fs", &["read", "write"]),
+            LabelHost::new("git", &["status", "read"]),
+        ]);
+
+        let names: Vec<_> = host.specs().iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["read", "write", "status"]);
+        assert_eq!(host.execute(&call("read")).await.unwrap(), json!("fs"));
+        assert_eq!(host.execute(&call("status")).await.unwrap(), json!("git"));
+        assert!(matches!(
+            host.execute(&call("push")).await,
+            Err(ToolError::NotFound(_))
+        ));
+    }
+}
@@ -14,4 +14,29 @@ pub trait ToolHost: Send + Sync {
 
     /// Execute a tool call.
     fn execute(&self, call: &ToolCall) -> impl Future<Output = Result<Value, ToolError>> + Send;
+
+    /// The form of a successful result to record in the event log.
+    ///
+    /// Hosts whose tools return secrets override this so the log never
+    /// holds them. The model still receives the full output.
+    fn log_output(&self, _call: &ToolCall, output: &Value) -> Value {
+        output.clone()
+    }
+
+    /// Called instead of [`Self::execute`] when the session answers a call
+    /// itself: from its result cache, with a denial from its approval
+    /// hook, or in dry-run mode.
+    ///
+    /// Hosts that display or audit calls override this to see those too.
+    fn answered(&self, _call: &ToolCall, _result: &Result<Value, ToolError>) {}
+
+    /// Release the host's resources, such as tool server processes.
+    ///
+    /// Hosts without any have nothing to do.
+    fn shutdown(self) -> impl Future<Output = ()> + Send
+    where
+        Self: Sized,
+    {
+        async {}
+    }
 }
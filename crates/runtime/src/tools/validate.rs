@@ -0,0 +1,116 @@
+//! JSON Schema validation of tool arguments.
+
+use super::{ToolCall, ToolError, ToolSpec};
+use jsonschema::Validator;
+use std::collections::HashMap;
+
+/// Compiled input schemas, keyed by tool name.
+///
+/// Tools whose schema fails to compile are skipped, leaving the server
+/// to judge their arguments.
+pub(crate) struct InputValidator {
+    validators: HashMap<String, Validator>,
+}
+
+impl InputValidator {
+    pub(crate) fn new(specs: &[ToolSpec]) -> Self {
+        let validators = specs
+            .iter()
+            .filter_map(|spec| {
+                let validator = jsonschema::validator_for(&spec.schema).ok()?;
+                Some((spec.name.clone(), validator))
+            })
+            .collect();
+        Self { validators }
+    }
+
+    /// Check a call's input against its tool's schema.
+    ///
+    /// The error lists every failing location as a JSON pointer so the
+    /// model can correct its arguments.
+    pub(crate) fn validate(&self, call: &ToolCall) -> Result<(), ToolError> {
+        let Some(validator) = self.validators.get(&call.name) else {
+            return Ok(());
+        };
+
+        let problems: Vec<String> = validator
+            .iter_errors(&call.input)
+            .map(|error| {
+                let path = error.instance_path().as_str();
+                let path = if path.is_empty() { "/" } else { path };
+                format!("{path}: {error}")
+            })
+            .collect();
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ToolError::InvalidInput(problems.join("; ")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+
+    fn read_file_spec() -> ToolSpec {
+        ToolSpec {
+            name: "read_file".into(),
+            description: "Read a file".into(),
+            schema: json!({
+                "type": "object",
+                "properties": { "path": { "type": "string" } },
+                "required": ["path"],
+            }),
+        }
+    }
+
+    fn call(name: &str, input: Value) -> ToolCall {
+        ToolCall {
+            id: "call_1".into(),
+            name: name.into(),
+            input,
+        }
+    }
+
+    #[test]
+    fn accepts_matching_input() {
+        let validator = InputValidator::new(&[read_file_spec()]);
+        assert!(
+            validator
+                .validate(&call("read_file", json!({ "path": "a.txt" })))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn reports_failing_path() {
+        let validator = InputValidator::new(&[read_file_spec()]);
+
+        let err = validator
+            .validate(&call("read_file", json!({ "path": 5 })))
+            .unwrap_err();
+        let ToolError::InvalidInput(message) = err else {
+            panic!("expected InvalidInput, got {err:?}");
+        };
+        assert!(message.starts_with("/path: "), "{message}");
+
+        let err = validator
+            .validate(&call("read_file", json!({})))
+            .unwrap_err();
+        assert!(err.to_string().contains("\"path\" is a required property"));
+    }
+
+    #[test]
+    fn skips_unknown_tools_and_bad_schemas() {
+        let mut broken = read_file_spec();
+        broken.name = "broken".into();
+        broken.schema = json!({ "type": 12 });
+        let validator = InputValidator::new(&[broken]);
+
+        assert!(validator.validate(&call("broken", json!(1))).is_ok());
+        assert!(validator.validate(&call("missing", json!(1))).is_ok());
+    }
+}
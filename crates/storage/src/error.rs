@@ -21,6 +21,10 @@ pub enum Error {
     #[error("row deserialization error: {0}")]
     RowDeserialization(#[from] serde_rusqlite::Error),
 
+    /// The database is in use by another connection.
+    #[error("database busy: {0}")]
+    Busy(String),
+
     /// Requested item was not found.
     #[error("not found: {0}")]
     NotFound(String),
@@ -28,7 +28,7 @@ use uuid::Uuid;
 /// let parsed: SessionId = id_str.parse().unwrap();
 /// assert_eq!(id, parsed);
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct SessionId(pub Uuid);
 
 // Clippy wants Default for types with new(), but SessionId generates
@@ -39,6 +39,28 @@ impl SessionId {
     pub fn new() -> Self {
         Self(Uuid::new_v4())
     }
+
+    /// Wraps an existing UUID.
+    pub const fn from_uuid(uuid: Uuid) -> Self {
+        Self(uuid)
+    }
+
+    /// The all-zero identifier.
+    pub const fn nil() -> Self {
+        Self(Uuid::nil())
+    }
+
+    /// Builds an identifier from a number, for readable fixed IDs in tests.
+    ///
+    /// ```
+    /// use storage::SessionId;
+    ///
+    /// let id = SessionId::from_u128(1);
+    /// assert_eq!(id.to_string(), "00000000-0000-0000-0000-000000000001");
+    /// ```
+    pub const fn from_u128(value: u128) -> Self {
+        Self(Uuid::from_u128(value))
+    }
 }
 
 impl fmt::Display for SessionId {
@@ -122,6 +144,48 @@ pub enum EventKind {
         name: String,
         output: serde_json::Value,
     },
+    /// The last user message was replaced before regenerating.
+    MessageEdited { content: String },
+    /// The last response was discarded so the model could answer again.
+    Regenerated,
+    /// The conversation was cleared; later messages start a new one.
+    Cleared,
+    /// A turn failed before producing a response.
+    Error { source: String, message: String },
+    /// Tokens billed for one model call.
+    Usage {
+        model: String,
+        input_tokens: u32,
+        output_tokens: u32,
+    },
+    /// A secret was requested; `allowed` records the policy decision.
+    SecretAccess { key: String, allowed: bool },
+    /// What was sent to the model, recorded just before the call.
+    ///
+    /// `system` is the session's prompt; `None` means the backend's
+    /// default applied. `request` holds the full messages and tools when
+    /// verbose request logging is on.
+    ModelRequest {
+        system: Option<String>,
+        message_count: u32,
+        tool_names: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        request: Option<serde_json::Value>,
+    },
+    /// Snapshot of the conversation for resuming after a crash.
+    ///
+    /// `turn_complete` is false while a tool loop is still in flight, i.e.
+    /// the model or a tool still owes a reply.
+    Checkpoint {
+        messages: serde_json::Value,
+        turn_complete: bool,
+    },
+    /// The session's title and tags, replacing any set earlier.
+    SessionMeta {
+        title: Option<String>,
+        #[serde(default)]
+        tags: Vec<String>,
+    },
     /// Session started.
     SessionStart,
     /// Session ended.
@@ -145,6 +209,15 @@ impl EventKind {
             Self::Message { .. } => "message",
             Self::ToolCall { .. } => "tool_call",
             Self::ToolResult { .. } => "tool_result",
+            Self::MessageEdited { .. } => "message_edited",
+            Self::Regenerated => "regenerated",
+            Self::Cleared => "cleared",
+            Self::Error { .. } => "error",
+            Self::Usage { .. } => "usage",
+            Self::SecretAccess { .. } => "secret_access",
+            Self::ModelRequest { .. } => "model_request",
+            Self::Checkpoint { .. } => "checkpoint",
+            Self::SessionMeta { .. } => "session_meta",
             Self::SessionStart => "session_start",
             Self::SessionEnd => "session_end",
         }
@@ -152,6 +225,9 @@ impl EventKind {
 }
 
 /// An event in the session log.
+///
+/// Events order by `(timestamp, id)`, and two events are equal when
+/// those match; event IDs are unique, so `kind` is not compared.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Event {
     pub id: Uuid,
@@ -160,6 +236,26 @@ pub struct Event {
     pub kind: EventKind,
 }
 
+impl PartialEq for Event {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Event {}
+
+impl PartialOrd for Event {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Event {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.timestamp, self.id).cmp(&(other.timestamp, other.id))
+    }
+}
+
 impl Event {
     pub fn new(session_id: SessionId, kind: EventKind) -> Self {
         Self {
@@ -215,6 +311,40 @@ mod tests {
         // Verify that name() returns values consistent with serde serialization
         assert_eq!(EventKind::SessionStart.name(), "session_start");
         assert_eq!(EventKind::SessionEnd.name(), "session_end");
+        assert_eq!(EventKind::Regenerated.name(), "regenerated");
+        assert_eq!(EventKind::Cleared.name(), "cleared");
+        assert_eq!(
+            EventKind::Error {
+                source: "api".into(),
+                message: "test".into()
+            }
+            .name(),
+            "error"
+        );
+        assert_eq!(
+            EventKind::Usage {
+                model: "test".into(),
+                input_tokens: 1,
+                output_tokens: 1
+            }
+            .name(),
+            "usage"
+        );
+        assert_eq!(
+            EventKind::SecretAccess {
+                key: "TEST".into(),
+                allowed: false
+            }
+            .name(),
+            "secret_access"
+        );
+        assert_eq!(
+            EventKind::MessageEdited {
+                content: "test".into()
+            }
+            .name(),
+            "message_edited"
+        );
         assert_eq!(
             EventKind::Message {
                 role: Role::User,
@@ -239,6 +369,14 @@ mod tests {
             .name(),
             "tool_result"
         );
+        assert_eq!(
+            EventKind::SessionMeta {
+                title: None,
+                tags: Vec::new()
+            }
+            .name(),
+            "session_meta"
+        );
     }
 
     #[test]
@@ -249,6 +387,34 @@ mod tests {
         assert_eq!(id, parsed);
     }
 
+    #[test]
+    fn session_id_fixed_constructors() {
+        assert_eq!(SessionId::nil(), SessionId::from_u128(0));
+        assert_eq!(SessionId::from_uuid(Uuid::nil()), SessionId::nil());
+        assert_ne!(SessionId::from_u128(1), SessionId::from_u128(2));
+    }
+
+    #[test]
+    fn events_sort_by_timestamp_then_id() {
+        let session_id = SessionId::nil();
+        let mut later = Event::new(session_id, EventKind::SessionEnd);
+        let mut tie = Event::new(session_id, EventKind::SessionStart);
+        let mut earlier = Event::new(session_id, EventKind::Regenerated);
+        tie.timestamp = later.timestamp;
+        earlier.timestamp = later.timestamp - chrono::Duration::seconds(1);
+        later.id = Uuid::from_u128(2);
+        tie.id = Uuid::from_u128(1);
+
+        let mut events = [later.clone(), tie.clone(), earlier.clone()];
+        events.sort();
+        let ids: Vec<_> = events.iter().map(|e| e.id).collect();
+        assert_eq!(ids, [earlier.id, tie.id, later.id]);
+
+        let sessions: std::collections::BTreeSet<_> =
+            [SessionId::from_u128(2), SessionId::from_u128(1)].into();
+        assert_eq!(sessions.first(), Some(&SessionId::from_u128(1)));
+    }
+
     #[test]
     fn session_id_parse_invalid() {
         let result: Result<SessionId, _> = "not-a-uuid".parse();
@@ -6,4 +6,7 @@ mod store;
 
 pub use error::{Error, Result};
 pub use event::{Event, EventKind, Role, SessionId};
-pub use store::{EventStore, SessionSummary};
+pub use store::{
+    EventStore, ModelUsage, PrefixMatch, SessionFilter, SessionStatus, SessionSummary,
+    ToolUsageStat, UsageStats,
+};
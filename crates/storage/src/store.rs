@@ -3,11 +3,30 @@
 /// Table name for events storage.
 const EVENTS_TABLE: &str = "events";
 
+/// Schema version recorded in `PRAGMA user_version`.
+///
+/// Version 1 stores timestamps as integer microseconds since the Unix
+/// epoch; version 0 stored RFC 3339 text.
+const SCHEMA_VERSION: i64 = 1;
+
+const CREATE_EVENTS: &str = r#"
+    CREATE TABLE IF NOT EXISTS events (
+        id TEXT PRIMARY KEY,
+        session_id TEXT NOT NULL,
+        timestamp INTEGER NOT NULL,
+        kind TEXT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_events_session
+        ON events(session_id, timestamp);
+"#;
+
 use crate::{Error, Event, EventKind, Result, SessionId};
 use chrono::{DateTime, Utc};
-use rusqlite::{Connection, params};
+use rusqlite::{Connection, OptionalExtension, Transaction, params};
 use serde::Deserialize;
 use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 
 /// Summary of a session for listing.
 #[derive(Debug, Clone)]
@@ -16,6 +35,93 @@ pub struct SessionSummary {
     pub started_at: DateTime<Utc>,
     pub ended_at: Option<DateTime<Utc>>,
     pub message_count: u32,
+    /// Title from the latest `session_meta` event, if any.
+    pub title: Option<String>,
+    /// Tags from the latest `session_meta` event.
+    pub tags: Vec<String>,
+}
+
+/// Whether a session has ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionStatus {
+    Active,
+    Ended,
+}
+
+/// Which sessions [`EventStore::list_sessions_matching`] returns.
+///
+/// Every field left unset matches all sessions.
+#[derive(Debug, Clone, Default)]
+pub struct SessionFilter {
+    pub status: Option<SessionStatus>,
+    /// Only sessions started at or after this time.
+    pub since: Option<DateTime<Utc>>,
+    /// Only sessions started before this time.
+    pub until: Option<DateTime<Utc>>,
+    /// Only sessions whose latest metadata carries every one of these tags.
+    pub tags: Vec<String>,
+    /// Return at most this many sessions, newest first.
+    pub limit: Option<usize>,
+}
+
+/// Usage totals across sessions, from `usage` events.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UsageStats {
+    pub sessions: u64,
+    pub messages: u64,
+    /// Per-model totals, ordered by model name.
+    pub models: Vec<ModelUsage>,
+}
+
+impl UsageStats {
+    /// Input tokens across all models.
+    pub fn input_tokens(&self) -> u64 {
+        self.models.iter().map(|m| m.input_tokens).sum()
+    }
+
+    /// Output tokens across all models.
+    pub fn output_tokens(&self) -> u64 {
+        self.models.iter().map(|m| m.output_tokens).sum()
+    }
+}
+
+/// Token totals for a single model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelUsage {
+    pub model: String,
+    pub calls: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+/// How often one tool ran in a session, from `tool_call` and
+/// `tool_result` events.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolUsageStat {
+    pub tool: String,
+    pub calls: u64,
+    pub succeeded: u64,
+    /// Results logged as `{"error": ...}`, including denied calls.
+    pub failed: u64,
+}
+
+impl ToolUsageStat {
+    /// Fraction of finished calls that succeeded, if any finished.
+    pub fn success_rate(&self) -> Option<f64> {
+        let finished = self.succeeded + self.failed;
+        (finished > 0).then(|| self.succeeded as f64 / finished as f64)
+    }
+}
+
+/// Result of looking up a session by ID prefix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrefixMatch {
+    /// No session matches the prefix.
+    None,
+    /// Exactly one session matches.
+    One(SessionId),
+    /// Several sessions match; the prefix must be made longer.
+    Ambiguous(Vec<SessionId>),
 }
 
 /// Raw event row from SQLite — used for type-safe deserialization.
@@ -23,7 +129,7 @@ pub struct SessionSummary {
 struct EventRow {
     id: String,
     session_id: String,
-    timestamp: String,
+    timestamp: i64,
     data: String,
 }
 
@@ -31,21 +137,29 @@ struct EventRow {
 #[derive(Debug, Deserialize)]
 struct SessionRow {
     session_id: String,
-    started_at: String,
-    ended_at: Option<String>,
+    started_at: i64,
+    ended_at: Option<i64>,
     message_count: u32,
+    meta: Option<String>,
 }
 
 /// SQLite-backed event store.
+///
+/// Cloning is cheap: clones share one connection, so several sessions in
+/// the same process can write to the same database. The store is `Send`
+/// and `Sync`; each call holds the connection only while it runs, and a
+/// batch append is one transaction, so concurrent writers never interleave
+/// within a batch.
+#[derive(Clone)]
 pub struct EventStore {
-    conn: Connection,
+    conn: Arc<Mutex<Connection>>,
 }
 
 impl EventStore {
     /// Open or create an event store at the given path.
     pub fn open(path: impl AsRef<Path>) -> Result<Self> {
         let conn = Connection::open(path)?;
-        let store = Self { conn };
+        let store = Self::from_connection(conn);
         store.init_schema()?;
         Ok(store)
     }
@@ -53,79 +167,260 @@ impl EventStore {
     /// Create an in-memory event store (useful for testing).
     pub fn in_memory() -> Result<Self> {
         let conn = Connection::open_in_memory()?;
-        let store = Self { conn };
+        let store = Self::from_connection(conn);
         store.init_schema()?;
         Ok(store)
     }
 
+    fn from_connection(conn: Connection) -> Self {
+        Self {
+            conn: Arc::new(Mutex::new(conn)),
+        }
+    }
+
+    /// Lock the shared connection.
+    ///
+    /// A panic while holding the lock cannot leave SQLite in a broken state,
+    /// so a poisoned lock is recovered rather than propagated.
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
     fn init_schema(&self) -> Result<()> {
-        self.conn.execute_batch(
-            r#"
-            CREATE TABLE IF NOT EXISTS events (
-                id TEXT PRIMARY KEY,
-                session_id TEXT NOT NULL,
-                timestamp TEXT NOT NULL,
-                kind TEXT NOT NULL,
-                data TEXT NOT NULL
-            );
-            CREATE INDEX IF NOT EXISTS idx_events_session 
-                ON events(session_id, timestamp);
-            "#,
-        )?;
+        let mut conn = self.conn();
+        // WAL lets readers proceed while a writer is active. In-memory
+        // databases report "memory" and ignore the request.
+        conn.query_row("PRAGMA journal_mode=WAL", [], |_| Ok(()))?;
+        let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+        let tx = conn.transaction()?;
+        if version < SCHEMA_VERSION && table_exists(&tx, EVENTS_TABLE)? {
+            migrate_text_timestamps(&tx)?;
+        }
+        tx.execute_batch(CREATE_EVENTS)?;
+        tx.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        tx.commit()?;
         Ok(())
     }
 
+    /// Write the WAL back into the database file and truncate it.
+    ///
+    /// Every append is already durable once it returns; this gives a point
+    /// where the main file alone holds all data, e.g. before copying it for
+    /// a backup. Fails with [`Error::Busy`] if another process's readers
+    /// keep the WAL from being fully checkpointed.
+    pub fn flush(&self) -> Result<()> {
+        let busy: i64 = self
+            .conn()
+            .query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| row.get(0))?;
+        if busy != 0 {
+            return Err(Error::Busy("WAL checkpoint blocked by readers".into()));
+        }
+        Ok(())
+    }
+
+    /// Flush and close the store.
+    ///
+    /// Dropping the store closes the connection too, but silently; this
+    /// reports any error. Clones share the connection, so it is only
+    /// closed here if this is the last one; otherwise the remaining clones
+    /// keep using it.
+    pub fn close(self) -> Result<()> {
+        self.flush()?;
+        let Ok(conn) = Arc::try_unwrap(self.conn) else {
+            return Ok(());
+        };
+        let conn = conn.into_inner().unwrap_or_else(PoisonError::into_inner);
+        conn.close().map_err(|(_, e)| Error::from(e))
+    }
+
     /// Append an event to the store.
     pub fn append(&self, event: &Event) -> Result<()> {
-        self.conn.execute(
-            "INSERT INTO events (id, session_id, timestamp, kind, data) VALUES (?1, ?2, ?3, ?4, ?5)",
-            params![
-                event.id.to_string(),
-                event.session_id.to_string(),
-                event.timestamp.to_rfc3339(),
-                event.kind.name(),
-                serde_json::to_string(&event.kind)?,
-            ],
-        )?;
+        insert_event(&self.conn(), event)
+    }
+
+    /// Append several events in a single transaction.
+    ///
+    /// Either all events are stored or none are.
+    pub fn append_batch(&self, events: &[Event]) -> Result<()> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        for event in events {
+            insert_event(&tx, event)?;
+        }
+        tx.commit()?;
         Ok(())
     }
 
     /// Load all events for a session, ordered by timestamp.
     pub fn load_session(&self, session_id: SessionId) -> Result<Vec<Event>> {
-        let mut stmt = self.conn.prepare(
+        let mut events = Vec::new();
+        self.for_each_event(session_id, |event| {
+            events.push(event);
+            Ok(())
+        })?;
+        Ok(events)
+    }
+
+    /// Visit a session's events in timestamp order without loading them
+    /// all into memory.
+    ///
+    /// Rows are read one at a time; the first error from the store or from
+    /// `f` stops the walk and is returned. The connection stays locked
+    /// throughout, so `f` must not use this store or any of its clones.
+    pub fn for_each_event(
+        &self,
+        session_id: SessionId,
+        mut f: impl FnMut(Event) -> Result<()>,
+    ) -> Result<()> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
             "SELECT id, session_id, timestamp, data FROM events 
-             WHERE session_id = ?1 ORDER BY timestamp",
+             WHERE session_id = ?1 ORDER BY timestamp, rowid",
         )?;
 
         let rows = stmt.query_and_then([session_id.to_string()], |row| {
             serde_rusqlite::from_row::<EventRow>(row).map_err(Error::from)
         })?;
 
-        let mut events = Vec::new();
         for row in rows {
-            let row = row?;
-            events.push(parse_event_row(row)?);
+            f(parse_event_row(row?)?)?;
         }
+        Ok(())
+    }
 
-        Ok(events)
+    /// Check whether any events exist for a session.
+    pub fn session_exists(&self, session_id: SessionId) -> Result<bool> {
+        let exists = self.conn().query_row(
+            "SELECT EXISTS(SELECT 1 FROM events WHERE session_id = ?1 LIMIT 1)",
+            [session_id.to_string()],
+            |row| row.get(0),
+        )?;
+        Ok(exists)
+    }
+
+    /// Count the events recorded for a session.
+    pub fn count_events(&self, session_id: SessionId) -> Result<u64> {
+        let count: i64 = self.conn().query_row(
+            "SELECT COUNT(*) FROM events WHERE session_id = ?1",
+            [session_id.to_string()],
+            |row| row.get(0),
+        )?;
+        // COUNT(*) is never negative.
+        Ok(count as u64)
+    }
+
+    /// The session with the most recent event, if the store has any.
+    pub fn latest_session(&self) -> Result<Option<SessionId>> {
+        let raw: Option<String> = self
+            .conn()
+            .query_row(
+                "SELECT session_id FROM events ORDER BY timestamp DESC, rowid DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?;
+        raw.map(|raw| {
+            raw.parse().map(SessionId).map_err(|_| Error::Corrupted {
+                table: EVENTS_TABLE,
+                id: raw.clone(),
+                reason: format!("invalid UUID for session_id: {raw}"),
+            })
+        })
+        .transpose()
+    }
+
+    /// Find the session whose ID starts with `prefix`.
+    pub fn find_session_by_prefix(&self, prefix: &str) -> Result<PrefixMatch> {
+        let pattern = format!("{}%", escape_like(prefix));
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            r"SELECT DISTINCT session_id FROM events
+              WHERE session_id LIKE ?1 ESCAPE '\'
+              ORDER BY session_id",
+        )?;
+
+        let mut ids = Vec::new();
+        let rows = stmt.query_map([pattern], |row| row.get::<_, String>(0))?;
+        for row in rows {
+            let raw = row?;
+            let id = raw.parse().map_err(|_| Error::Corrupted {
+                table: EVENTS_TABLE,
+                id: raw.clone(),
+                reason: format!("invalid UUID for session_id: {raw}"),
+            })?;
+            ids.push(SessionId(id));
+        }
+
+        Ok(match ids.len() {
+            0 => PrefixMatch::None,
+            1 => PrefixMatch::One(ids[0]),
+            _ => PrefixMatch::Ambiguous(ids),
+        })
     }
 
     /// List all sessions with summary info.
     pub fn list_sessions(&self) -> Result<Vec<SessionSummary>> {
-        let mut stmt = self.conn.prepare(
+        self.list_sessions_matching(&SessionFilter::default())
+    }
+
+    /// List the sessions matching `filter`, newest first.
+    pub fn list_sessions_matching(&self, filter: &SessionFilter) -> Result<Vec<SessionSummary>> {
+        let mut conditions = Vec::new();
+        let mut values: Vec<rusqlite::types::Value> = Vec::new();
+        match filter.status {
+            Some(SessionStatus::Active) => conditions.push("ended_at IS NULL".to_string()),
+            Some(SessionStatus::Ended) => conditions.push("ended_at IS NOT NULL".to_string()),
+            None => {}
+        }
+        if let Some(since) = &filter.since {
+            values.push(timestamp_micros(since).into());
+            conditions.push(format!("started_at >= ?{}", values.len()));
+        }
+        if let Some(until) = &filter.until {
+            values.push(timestamp_micros(until).into());
+            conditions.push(format!("started_at < ?{}", values.len()));
+        }
+        for tag in &filter.tags {
+            values.push(tag.clone().into());
+            conditions.push(format!(
+                "EXISTS (SELECT 1 FROM json_each(meta, '$.tags') WHERE value = ?{})",
+                values.len()
+            ));
+        }
+        let filter_sql = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+        let limit_sql = match filter.limit {
+            Some(limit) => {
+                values.push((limit.min(i64::MAX as usize) as i64).into());
+                format!("LIMIT ?{}", values.len())
+            }
+            None => String::new(),
+        };
+
+        let conn = self.conn();
+        let mut stmt = conn.prepare(&format!(
             r#"
-            SELECT 
+            SELECT * FROM (SELECT
                 session_id,
                 MIN(timestamp) as started_at,
                 MAX(CASE WHEN kind = 'session_end' THEN timestamp END) as ended_at,
-                SUM(CASE WHEN kind = 'message' THEN 1 ELSE 0 END) as message_count
+                SUM(CASE WHEN kind = 'message' THEN 1 ELSE 0 END) as message_count,
+                (SELECT m.data FROM events AS m
+                    WHERE m.session_id = events.session_id AND m.kind = 'session_meta'
+                    ORDER BY m.timestamp DESC, m.rowid DESC LIMIT 1) as meta
             FROM events
-            GROUP BY session_id
-            ORDER BY started_at DESC
+            GROUP BY session_id)
+            {filter_sql}
+            ORDER BY started_at DESC, session_id
+            {limit_sql}
             "#,
-        )?;
+        ))?;
 
-        let rows = stmt.query_and_then([], |row| {
+        let rows = stmt.query_and_then(rusqlite::params_from_iter(values), |row| {
             serde_rusqlite::from_row::<SessionRow>(row).map_err(Error::from)
         })?;
 
@@ -138,6 +433,83 @@ impl EventStore {
         Ok(sessions)
     }
 
+    /// Aggregate usage across all sessions, counting only events at or
+    /// after `since` when given.
+    pub fn usage_stats(&self, since: Option<DateTime<Utc>>) -> Result<UsageStats> {
+        let since = since.as_ref().map(timestamp_micros).unwrap_or(i64::MIN);
+        let conn = self.conn();
+
+        let (sessions, messages): (i64, i64) = conn.query_row(
+            r"SELECT
+                COUNT(DISTINCT session_id),
+                COALESCE(SUM(kind = 'message'), 0)
+            FROM events
+            WHERE timestamp >= ?1",
+            [since],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+
+        let mut stmt = conn.prepare(
+            r"SELECT
+                json_extract(data, '$.model') AS model,
+                COUNT(*),
+                SUM(json_extract(data, '$.input_tokens')),
+                SUM(json_extract(data, '$.output_tokens'))
+            FROM events
+            WHERE kind = 'usage' AND timestamp >= ?1
+            GROUP BY model
+            ORDER BY model",
+        )?;
+        let rows = stmt.query_map([since], |row| {
+            // Counts and sums of u32 token fields are never negative.
+            Ok(ModelUsage {
+                model: row.get(0)?,
+                calls: row.get::<_, i64>(1)? as u64,
+                input_tokens: row.get::<_, i64>(2)? as u64,
+                output_tokens: row.get::<_, i64>(3)? as u64,
+            })
+        })?;
+
+        Ok(UsageStats {
+            sessions: sessions as u64,
+            messages: messages as u64,
+            models: rows.collect::<rusqlite::Result<_>>()?,
+        })
+    }
+
+    /// Per-tool call counts and outcomes for a session, most used first.
+    pub fn tool_usage(&self, session_id: SessionId) -> Result<Vec<ToolUsageStat>> {
+        let conn = self.conn();
+        // Failures are logged as an object whose only key is `error`.
+        let mut stmt = conn.prepare(
+            r"SELECT tool, SUM(is_call), SUM(is_result AND NOT failed), SUM(failed)
+            FROM (
+                SELECT
+                    json_extract(data, '$.name') AS tool,
+                    kind = 'tool_call' AS is_call,
+                    kind = 'tool_result' AS is_result,
+                    kind = 'tool_result'
+                        AND json_type(data, '$.output') = 'object'
+                        AND json_type(data, '$.output.error') IS NOT NULL
+                        AND (SELECT COUNT(*) FROM json_each(data, '$.output')) = 1
+                        AS failed
+                FROM events
+                WHERE session_id = ?1 AND kind IN ('tool_call', 'tool_result')
+            )
+            GROUP BY tool
+            ORDER BY SUM(is_call) DESC, tool",
+        )?;
+        let rows = stmt.query_map([session_id.to_string()], |row| {
+            Ok(ToolUsageStat {
+                tool: row.get(0)?,
+                calls: row.get::<_, i64>(1)? as u64,
+                succeeded: row.get::<_, i64>(2)? as u64,
+                failed: row.get::<_, i64>(3)? as u64,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
     /// Load events for a session, optionally filtering by kind.
     pub fn load_events(
         &self,
@@ -147,15 +519,16 @@ impl EventStore {
         let sql = match kind_filter {
             Some(_) => {
                 "SELECT id, session_id, timestamp, data FROM events 
-                 WHERE session_id = ?1 AND kind = ?2 ORDER BY timestamp"
+                 WHERE session_id = ?1 AND kind = ?2 ORDER BY timestamp, rowid"
             }
             None => {
                 "SELECT id, session_id, timestamp, data FROM events 
-                 WHERE session_id = ?1 ORDER BY timestamp"
+                 WHERE session_id = ?1 ORDER BY timestamp, rowid"
             }
         };
 
-        let mut stmt = self.conn.prepare(sql)?;
+        let conn = self.conn();
+        let mut stmt = conn.prepare(sql)?;
 
         let rows: Vec<EventRow> = if let Some(kind) = kind_filter {
             let iter = stmt.query_and_then(params![session_id.to_string(), kind], |row| {
@@ -178,6 +551,90 @@ impl EventStore {
     }
 }
 
+/// Convert a timestamp to its stored form: microseconds since the Unix
+/// epoch, so column order is time order.
+fn timestamp_micros(timestamp: &DateTime<Utc>) -> i64 {
+    timestamp.timestamp_micros()
+}
+
+fn table_exists(conn: &Connection, name: &str) -> Result<bool> {
+    let exists = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+        [name],
+        |row| row.get(0),
+    )?;
+    Ok(exists)
+}
+
+/// Rewrite a version 0 events table, whose timestamps are RFC 3339 text,
+/// with integer timestamps.
+///
+/// The table is rebuilt rather than updated in place because the old
+/// column's `TEXT` affinity would turn stored integers back into text.
+/// Row ids are kept so same-tick events keep their order.
+fn migrate_text_timestamps(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "DROP INDEX IF EXISTS idx_events_session;
+         ALTER TABLE events RENAME TO events_v0;",
+    )?;
+    tx.execute_batch(CREATE_EVENTS)?;
+    {
+        let mut select = tx.prepare(
+            "SELECT rowid, id, session_id, timestamp, kind, data FROM events_v0 ORDER BY rowid",
+        )?;
+        let mut insert = tx.prepare(
+            "INSERT INTO events (rowid, id, session_id, timestamp, kind, data)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+        let mut rows = select.query([])?;
+        while let Some(row) = rows.next()? {
+            let id: String = row.get(1)?;
+            let text: String = row.get(3)?;
+            let timestamp: DateTime<Utc> = text.parse().map_err(|_| Error::Corrupted {
+                table: EVENTS_TABLE,
+                id: id.clone(),
+                reason: format!("invalid timestamp: {text}"),
+            })?;
+            insert.execute(params![
+                row.get::<_, i64>(0)?,
+                id,
+                row.get::<_, String>(2)?,
+                timestamp_micros(&timestamp),
+                row.get::<_, String>(4)?,
+                row.get::<_, String>(5)?,
+            ])?;
+        }
+    }
+    tx.execute_batch("DROP TABLE events_v0")?;
+    Ok(())
+}
+
+fn insert_event(conn: &Connection, event: &Event) -> Result<()> {
+    conn.execute(
+        "INSERT INTO events (id, session_id, timestamp, kind, data) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            event.id.to_string(),
+            event.session_id.to_string(),
+            timestamp_micros(&event.timestamp),
+            event.kind.name(),
+            serde_json::to_string(&event.kind)?,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Escape SQL `LIKE` wildcards so `input` matches literally.
+fn escape_like(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for c in input.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
 /// Parse a typed event row into an Event, with proper error reporting.
 fn parse_event_row(row: EventRow) -> Result<Event> {
     let parsed_id = row.id.parse().map_err(|_| Error::Corrupted {
@@ -192,11 +649,12 @@ fn parse_event_row(row: EventRow) -> Result<Event> {
         reason: format!("invalid UUID for session_id: {}", row.session_id),
     })?;
 
-    let parsed_timestamp = row.timestamp.parse().map_err(|_| Error::Corrupted {
-        table: EVENTS_TABLE,
-        id: row.id.clone(),
-        reason: format!("invalid timestamp: {}", row.timestamp),
-    })?;
+    let parsed_timestamp =
+        DateTime::from_timestamp_micros(row.timestamp).ok_or_else(|| Error::Corrupted {
+            table: EVENTS_TABLE,
+            id: row.id.clone(),
+            reason: format!("invalid timestamp: {}", row.timestamp),
+        })?;
 
     let parsed_kind: EventKind = serde_json::from_str(&row.data).map_err(|e| Error::Corrupted {
         table: EVENTS_TABLE,
@@ -220,26 +678,45 @@ fn parse_session_row(row: SessionRow) -> Result<SessionSummary> {
         reason: format!("invalid UUID for session_id: {}", row.session_id),
     })?;
 
-    let parsed_started_at = row.started_at.parse().map_err(|_| Error::Corrupted {
-        table: EVENTS_TABLE,
-        id: row.session_id.clone(),
-        reason: format!("invalid started_at timestamp: {}", row.started_at),
-    })?;
-
-    let parsed_ended_at = match row.ended_at {
-        Some(ts) => Some(ts.parse().map_err(|_| Error::Corrupted {
+    let parsed_started_at =
+        DateTime::from_timestamp_micros(row.started_at).ok_or_else(|| Error::Corrupted {
             table: EVENTS_TABLE,
             id: row.session_id.clone(),
-            reason: format!("invalid ended_at timestamp: {ts}"),
-        })?),
+            reason: format!("invalid started_at timestamp: {}", row.started_at),
+        })?;
+
+    let parsed_ended_at = match row.ended_at {
+        Some(ts) => Some(
+            DateTime::from_timestamp_micros(ts).ok_or_else(|| Error::Corrupted {
+                table: EVENTS_TABLE,
+                id: row.session_id.clone(),
+                reason: format!("invalid ended_at timestamp: {ts}"),
+            })?,
+        ),
         None => None,
     };
 
+    let (title, tags) = match row.meta {
+        Some(data) => match serde_json::from_str(&data) {
+            Ok(EventKind::SessionMeta { title, tags }) => (title, tags),
+            _ => {
+                return Err(Error::Corrupted {
+                    table: EVENTS_TABLE,
+                    id: row.session_id,
+                    reason: format!("invalid session_meta data: {data}"),
+                });
+            }
+        },
+        None => (None, Vec::new()),
+    };
+
     Ok(SessionSummary {
         id: SessionId(parsed_session_id),
         started_at: parsed_started_at,
         ended_at: parsed_ended_at,
         message_count: row.message_count,
+        title,
+        tags,
     })
 }
 
@@ -247,6 +724,7 @@ fn parse_session_row(row: SessionRow) -> Result<SessionSummary> {
 mod tests {
     use super::*;
     use crate::Role;
+    use chrono::TimeZone;
 
     #[test]
     fn test_append_and_load_events() {
@@ -274,6 +752,143 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_same_tick_ordering_is_stable() {
+        let store = EventStore::in_memory().unwrap();
+        let tick = Utc::now();
+        let mut ids: Vec<SessionId> = (0..3).map(|_| SessionId::new()).collect();
+        for &id in &ids {
+            let mut event = Event::new(id, EventKind::SessionStart);
+            event.timestamp = tick;
+            store.append(&event).unwrap();
+        }
+        for kind in [EventKind::Regenerated, EventKind::SessionEnd] {
+            let mut event = Event::new(ids[0], kind);
+            event.timestamp = tick;
+            store.append(&event).unwrap();
+        }
+
+        let names: Vec<_> = store
+            .load_session(ids[0])
+            .unwrap()
+            .iter()
+            .map(|e| e.kind.name())
+            .collect();
+        assert_eq!(names, ["session_start", "regenerated", "session_end"]);
+
+        ids.sort_by_key(|id| id.to_string());
+        let listed: Vec<_> = store
+            .list_sessions()
+            .unwrap()
+            .iter()
+            .map(|s| s.id)
+            .collect();
+        assert_eq!(listed, ids);
+    }
+
+    #[test]
+    fn test_timestamps_round_trip_in_time_order() {
+        let store = EventStore::in_memory().unwrap();
+        let session_id = SessionId::new();
+        // Text order of these differs from time order.
+        let stamps = ["2026-01-02T05:04:05.5+02:00", "2026-01-02T03:04:05.75Z"];
+        for stamp in stamps.iter().rev() {
+            let mut event = Event::new(session_id, EventKind::SessionStart);
+            event.timestamp = stamp.parse().unwrap();
+            store.append(&event).unwrap();
+        }
+
+        let loaded: Vec<_> = store
+            .load_session(session_id)
+            .unwrap()
+            .iter()
+            .map(|e| e.timestamp)
+            .collect();
+        let expected: Vec<DateTime<Utc>> = stamps.iter().map(|s| s.parse().unwrap()).collect();
+        assert_eq!(loaded, expected);
+    }
+
+    #[test]
+    fn test_open_migrates_text_timestamps() {
+        let path = std::env::temp_dir().join(format!("bosun-{}.db", uuid::Uuid::new_v4()));
+        let session_id = SessionId::new();
+        let old = Connection::open(&path).unwrap();
+        old.execute_batch(
+            "CREATE TABLE events (
+                id TEXT PRIMARY KEY,
+                session_id TEXT NOT NULL,
+                timestamp TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                data TEXT NOT NULL
+            );
+            CREATE INDEX idx_events_session ON events(session_id, timestamp);",
+        )
+        .unwrap();
+        let stamps = ["2026-01-02T05:04:05.5+02:00", "2026-01-02T03:04:05.75Z"];
+        for (stamp, kind) in stamps
+            .iter()
+            .zip([EventKind::SessionStart, EventKind::SessionEnd])
+        {
+            old.execute(
+                "INSERT INTO events VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    uuid::Uuid::new_v4().to_string(),
+                    session_id.to_string(),
+                    stamp,
+                    kind.name(),
+                    serde_json::to_string(&kind).unwrap(),
+                ],
+            )
+            .unwrap();
+        }
+        old.close().unwrap();
+
+        let store = EventStore::open(&path).unwrap();
+        let events = store.load_session(session_id).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            events[0].timestamp,
+            stamps[0].parse::<DateTime<Utc>>().unwrap()
+        );
+        assert!(matches!(events[1].kind, EventKind::SessionEnd));
+        let sessions = store.list_sessions().unwrap();
+        assert_eq!(sessions[0].ended_at, Some(events[1].timestamp));
+        store.close().unwrap();
+
+        // Already migrated: reopening leaves the data alone.
+        let reopened = EventStore::open(&path).unwrap();
+        assert_eq!(reopened.count_events(session_id).unwrap(), 2);
+        reopened.close().unwrap();
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{suffix}", path.display()));
+        }
+    }
+
+    #[test]
+    fn test_for_each_event_stops_on_error() {
+        let store = EventStore::in_memory().unwrap();
+        let session_id = SessionId::new();
+        store
+            .append_batch(&[
+                Event::new(session_id, EventKind::SessionStart),
+                Event::message(session_id, Role::User, "one"),
+                Event::message(session_id, Role::User, "two"),
+            ])
+            .unwrap();
+
+        let mut seen = Vec::new();
+        let result = store.for_each_event(session_id, |event| {
+            seen.push(event.kind.name());
+            if seen.len() == 2 {
+                return Err(Error::NotFound("stop".into()));
+            }
+            Ok(())
+        });
+
+        assert!(matches!(result, Err(Error::NotFound(_))));
+        assert_eq!(seen, ["session_start", "message"]);
+    }
+
     #[test]
     fn test_list_sessions() {
         let store = EventStore::in_memory().unwrap();
@@ -307,6 +922,99 @@ mod tests {
         let s2 = sessions.iter().find(|s| s.id == session2).unwrap();
         assert_eq!(s1.message_count, 2);
         assert_eq!(s2.message_count, 1);
+        assert_eq!(s1.title, None);
+    }
+
+    #[test]
+    fn test_list_sessions_uses_latest_meta() {
+        let store = EventStore::in_memory().unwrap();
+        let session_id = SessionId::new();
+        let meta = |title: &str, tags: &[&str]| {
+            Event::new(
+                session_id,
+                EventKind::SessionMeta {
+                    title: Some(title.into()),
+                    tags: tags.iter().map(|t| t.to_string()).collect(),
+                },
+            )
+        };
+        store
+            .append_batch(&[
+                Event::new(session_id, EventKind::SessionStart),
+                meta("Draft", &[]),
+                meta("Fix the parser", &["bug", "parser"]),
+            ])
+            .unwrap();
+
+        let sessions = store.list_sessions().unwrap();
+        assert_eq!(sessions[0].title.as_deref(), Some("Fix the parser"));
+        assert_eq!(sessions[0].tags, ["bug", "parser"]);
+        assert_eq!(sessions[0].message_count, 0);
+    }
+
+    #[test]
+    fn test_list_sessions_matching_filters() {
+        let store = EventStore::in_memory().unwrap();
+        let at = |session_id, day: u32, kind| Event {
+            timestamp: Utc.with_ymd_and_hms(2026, 3, day, 12, 0, 0).unwrap(),
+            ..Event::new(session_id, kind)
+        };
+        let (old, ended, tagged) = (SessionId::new(), SessionId::new(), SessionId::new());
+        store
+            .append_batch(&[
+                at(old, 1, EventKind::SessionStart),
+                at(ended, 5, EventKind::SessionStart),
+                at(ended, 5, EventKind::SessionEnd),
+                at(tagged, 9, EventKind::SessionStart),
+                at(
+                    tagged,
+                    9,
+                    EventKind::SessionMeta {
+                        title: None,
+                        tags: vec!["bug".into(), "parser".into()],
+                    },
+                ),
+            ])
+            .unwrap();
+
+        let ids = |filter: SessionFilter| -> Vec<SessionId> {
+            let sessions = store.list_sessions_matching(&filter).unwrap();
+            sessions.into_iter().map(|s| s.id).collect()
+        };
+        let day = |day| Some(Utc.with_ymd_and_hms(2026, 3, day, 0, 0, 0).unwrap());
+
+        assert_eq!(ids(SessionFilter::default()), [tagged, ended, old]);
+        let active = SessionFilter {
+            status: Some(SessionStatus::Active),
+            ..Default::default()
+        };
+        assert_eq!(ids(active), [tagged, old]);
+        let ended_only = SessionFilter {
+            status: Some(SessionStatus::Ended),
+            ..Default::default()
+        };
+        assert_eq!(ids(ended_only), [ended]);
+        let range = SessionFilter {
+            since: day(2),
+            until: day(9),
+            ..Default::default()
+        };
+        assert_eq!(ids(range), [ended]);
+        let tag = SessionFilter {
+            tags: vec!["parser".into()],
+            ..Default::default()
+        };
+        assert_eq!(ids(tag), [tagged]);
+        let missing_tag = SessionFilter {
+            tags: vec!["parser".into(), "docs".into()],
+            ..Default::default()
+        };
+        assert!(ids(missing_tag).is_empty());
+        let newest = SessionFilter {
+            limit: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(ids(newest), [tagged]);
     }
 
     #[test]
@@ -341,4 +1049,313 @@ mod tests {
         let all = store.load_events(session_id, None).unwrap();
         assert_eq!(all.len(), 4);
     }
+
+    #[test]
+    fn test_session_exists_and_count_events() {
+        let store = EventStore::in_memory().unwrap();
+        let session_id = SessionId::new();
+
+        assert!(!store.session_exists(session_id).unwrap());
+        assert_eq!(store.count_events(session_id).unwrap(), 0);
+
+        store
+            .append(&Event::new(session_id, EventKind::SessionStart))
+            .unwrap();
+        store
+            .append(&Event::message(session_id, Role::User, "Hello"))
+            .unwrap();
+
+        assert!(store.session_exists(session_id).unwrap());
+        assert_eq!(store.count_events(session_id).unwrap(), 2);
+        assert!(!store.session_exists(SessionId::new()).unwrap());
+    }
+
+    #[test]
+    fn test_find_session_by_prefix() {
+        let store = EventStore::in_memory().unwrap();
+        let session1: SessionId = "aaaa1111-0000-4000-8000-000000000000".parse().unwrap();
+        let session2: SessionId = "aaaa2222-0000-4000-8000-000000000000".parse().unwrap();
+
+        for id in [session1, session2] {
+            store
+                .append(&Event::new(id, EventKind::SessionStart))
+                .unwrap();
+            store.append(&Event::message(id, Role::User, "Hi")).unwrap();
+        }
+
+        assert_eq!(
+            store.find_session_by_prefix("aaaa1").unwrap(),
+            PrefixMatch::One(session1)
+        );
+        assert_eq!(
+            store.find_session_by_prefix("aaaa").unwrap(),
+            PrefixMatch::Ambiguous(vec![session1, session2])
+        );
+        assert_eq!(
+            store.find_session_by_prefix("bbbb").unwrap(),
+            PrefixMatch::None
+        );
+    }
+
+    #[test]
+    fn test_latest_session_has_the_newest_event() {
+        let store = EventStore::in_memory().unwrap();
+        assert_eq!(store.latest_session().unwrap(), None);
+
+        let (first, second) = (SessionId::new(), SessionId::new());
+        store
+            .append_batch(&[
+                Event::new(first, EventKind::SessionStart),
+                Event::new(second, EventKind::SessionStart),
+            ])
+            .unwrap();
+        assert_eq!(store.latest_session().unwrap(), Some(second));
+
+        store
+            .append(&Event::message(first, Role::User, "back again"))
+            .unwrap();
+        assert_eq!(store.latest_session().unwrap(), Some(first));
+    }
+
+    #[test]
+    fn test_find_session_by_prefix_treats_wildcards_literally() {
+        let store = EventStore::in_memory().unwrap();
+        store
+            .append(&Event::new(SessionId::new(), EventKind::SessionStart))
+            .unwrap();
+
+        assert_eq!(
+            store.find_session_by_prefix("%").unwrap(),
+            PrefixMatch::None
+        );
+        assert_eq!(
+            store.find_session_by_prefix("_").unwrap(),
+            PrefixMatch::None
+        );
+    }
+
+    #[test]
+    fn test_cloned_store_shares_database() {
+        let store = EventStore::in_memory().unwrap();
+        let session_id = SessionId::new();
+
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let store = store.clone();
+                std::thread::spawn(move || {
+                    store
+                        .append(&Event::message(session_id, Role::User, format!("msg {i}")))
+                        .unwrap();
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(store.count_events(session_id).unwrap(), 4);
+    }
+
+    #[test]
+    fn test_flush_truncates_wal_and_close_keeps_data() {
+        let path = std::env::temp_dir().join(format!("bosun-{}.db", uuid::Uuid::new_v4()));
+        let wal = path.with_extension("db-wal");
+        let session_id = SessionId::new();
+
+        let store = EventStore::open(&path).unwrap();
+        store
+            .append(&Event::new(session_id, EventKind::SessionStart))
+            .unwrap();
+        assert!(std::fs::metadata(&wal).unwrap().len() > 0);
+        store.flush().unwrap();
+        assert_eq!(std::fs::metadata(&wal).unwrap().len(), 0);
+
+        let clone = store.clone();
+        store.close().unwrap();
+        clone
+            .append(&Event::new(session_id, EventKind::SessionEnd))
+            .unwrap();
+        clone.close().unwrap();
+
+        let reopened = EventStore::open(&path).unwrap();
+        assert_eq!(reopened.count_events(session_id).unwrap(), 2);
+        reopened.close().unwrap();
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{suffix}", path.display()));
+        }
+    }
+
+    #[test]
+    fn test_append_batch() {
+        let store = EventStore::in_memory().unwrap();
+        let session_id = SessionId::new();
+
+        let events: Vec<_> = (0..100)
+            .map(|i| Event::message(session_id, Role::User, format!("msg {i}")))
+            .collect();
+        store.append_batch(&events).unwrap();
+
+        assert_eq!(store.count_events(session_id).unwrap(), 100);
+    }
+
+    #[test]
+    fn test_append_batch_is_atomic() {
+        let store = EventStore::in_memory().unwrap();
+        let session_id = SessionId::new();
+
+        let event = Event::new(session_id, EventKind::SessionStart);
+        let duplicate = event.clone();
+        assert!(store.append_batch(&[event, duplicate]).is_err());
+
+        assert!(!store.session_exists(session_id).unwrap());
+    }
+
+    fn usage_event(session_id: SessionId, model: &str, input: u32, output: u32) -> Event {
+        Event::new(
+            session_id,
+            EventKind::Usage {
+                model: model.into(),
+                input_tokens: input,
+                output_tokens: output,
+            },
+        )
+    }
+
+    #[test]
+    fn test_usage_stats_aggregates_by_model() {
+        let store = EventStore::in_memory().unwrap();
+        let session1 = SessionId::new();
+        let session2 = SessionId::new();
+
+        store
+            .append_batch(&[
+                Event::message(session1, Role::User, "hi"),
+                usage_event(session1, "model-b", 10, 5),
+                usage_event(session1, "model-a", 100, 50),
+                Event::message(session2, Role::User, "hello"),
+                usage_event(session2, "model-a", 200, 25),
+            ])
+            .unwrap();
+
+        let stats = store.usage_stats(None).unwrap();
+        assert_eq!(stats.sessions, 2);
+        assert_eq!(stats.messages, 2);
+        assert_eq!(
+            stats.models,
+            vec![
+                ModelUsage {
+                    model: "model-a".into(),
+                    calls: 2,
+                    input_tokens: 300,
+                    output_tokens: 75,
+                },
+                ModelUsage {
+                    model: "model-b".into(),
+                    calls: 1,
+                    input_tokens: 10,
+                    output_tokens: 5,
+                },
+            ]
+        );
+        assert_eq!(stats.input_tokens(), 310);
+        assert_eq!(stats.output_tokens(), 80);
+    }
+
+    #[test]
+    fn test_usage_stats_since() {
+        let store = EventStore::in_memory().unwrap();
+        let session_id = SessionId::new();
+
+        let mut old = usage_event(session_id, "model-a", 100, 50);
+        old.timestamp = Utc::now() - chrono::Duration::days(10);
+        store
+            .append_batch(&[old, usage_event(session_id, "model-a", 1, 2)])
+            .unwrap();
+
+        let since = Utc::now() - chrono::Duration::days(1);
+        let stats = store.usage_stats(Some(since)).unwrap();
+        assert_eq!(stats.input_tokens(), 1);
+        assert_eq!(stats.output_tokens(), 2);
+
+        let empty = EventStore::in_memory().unwrap();
+        assert_eq!(empty.usage_stats(None).unwrap(), UsageStats::default());
+    }
+
+    #[test]
+    fn test_tool_usage_counts_calls_and_failures() {
+        let store = EventStore::in_memory().unwrap();
+        let session_id = SessionId::new();
+        let call = |name: &str| {
+            Event::new(
+                session_id,
+                EventKind::ToolCall {
+                    name: name.into(),
+                    input: serde_json::json!({}),
+                },
+            )
+        };
+        let result = |name: &str, output: serde_json::Value| {
+            Event::new(
+                session_id,
+                EventKind::ToolResult {
+                    name: name.into(),
+                    output,
+                },
+            )
+        };
+
+        store
+            .append_batch(&[
+                call("read_file"),
+                result("read_file", serde_json::json!("contents")),
+                call("read_file"),
+                result("read_file", serde_json::json!({ "error": "not found" })),
+                call("read_file"),
+                result(
+                    "read_file",
+                    serde_json::json!({ "error": null, "rows": [] }),
+                ),
+                call("write_file"),
+                usage_event(session_id, "model-a", 1, 1),
+                call("other_session_tool"),
+            ])
+            .unwrap();
+        store
+            .append(&Event::new(
+                SessionId::new(),
+                EventKind::ToolCall {
+                    name: "elsewhere".into(),
+                    input: serde_json::json!({}),
+                },
+            ))
+            .unwrap();
+
+        let usage = store.tool_usage(session_id).unwrap();
+        assert_eq!(
+            usage,
+            vec![
+                ToolUsageStat {
+                    tool: "read_file".into(),
+                    calls: 3,
+                    succeeded: 2,
+                    failed: 1,
+                },
+                ToolUsageStat {
+                    tool: "other_session_tool".into(),
+                    calls: 1,
+                    succeeded: 0,
+                    failed: 0,
+                },
+                ToolUsageStat {
+                    tool: "write_file".into(),
+                    calls: 1,
+                    succeeded: 0,
+                    failed: 0,
+                },
+            ]
+        );
+        assert_eq!(usage[0].success_rate(), Some(2.0 / 3.0));
+        assert_eq!(usage[2].success_rate(), None);
+        assert!(store.tool_usage(SessionId::new()).unwrap().is_empty());
+    }
 }